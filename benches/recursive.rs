@@ -0,0 +1,96 @@
+//! Compares generating values from the boxed, unboxed and memoized forms of
+//! `prop_mutually_recursive`, against proptest's own `prop_recursive` as a baseline. Generating
+//! the same values through `with_depth` shows the cost of the per-node hooks once they are in use.
+//!
+//! Run with `cargo bench`.
+
+use std::time::{Duration, Instant};
+
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{with_depth, RecursiveParams, StrategyExt, StrategySet};
+
+const ITERATIONS: u32 = 10_000;
+const ROUNDS: u32 = 5;
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    fn size(&self) -> usize {
+        match self {
+            Tree::Leaf => 1,
+            Tree::Node(children) => 1 + children.iter().map(Tree::size).sum::<usize>(),
+        }
+    }
+}
+
+fn branch(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    vec(set.get::<Tree, _>(arb_tree), 0..4)
+        .prop_map(Tree::Node)
+        .sboxed()
+}
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive(4, 32, 4, set, branch)
+}
+
+fn bench<S: Strategy>(name: &str, strategy: &S, size: impl Fn(&S::Value) -> usize) {
+    let mut best = None;
+    for _ in 0..ROUNDS {
+        let mut runner = TestRunner::deterministic();
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            let tree = strategy.new_tree(&mut runner).unwrap();
+            std::hint::black_box(size(&tree.current()));
+        }
+        let elapsed = start.elapsed();
+        best = Some(best.map_or(elapsed, |best: Duration| best.min(elapsed)));
+    }
+
+    let best = best.unwrap();
+    println!(
+        "{:<10} {:>10.2?} best of {}, {:>8.2?} per value",
+        name,
+        best,
+        ROUNDS,
+        best / ITERATIONS
+    );
+}
+
+fn main() {
+    let set = StrategySet::default();
+
+    bench(
+        "baseline",
+        &Just(Tree::Leaf).prop_recursive(4, 32, 4, |inner| vec(inner, 0..4).prop_map(Tree::Node)),
+        Tree::size,
+    );
+    bench("boxed", &arb_tree(&mut set.clone()), Tree::size);
+    bench(
+        "unboxed",
+        &Just(Tree::Leaf).prop_mutually_recursive_unboxed(4, 32, 4, &set, branch),
+        Tree::size,
+    );
+    bench(
+        "memoized",
+        &Just(Tree::Leaf).prop_mutually_recursive_memoized(
+            RecursiveParams::new(4, 32, 4),
+            4,
+            0.5,
+            &set,
+            branch,
+        ),
+        Tree::size,
+    );
+    bench(
+        "with_depth",
+        &with_depth(arb_tree(&mut set.clone())),
+        |tree| tree.value.size(),
+    );
+}
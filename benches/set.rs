@@ -0,0 +1,65 @@
+//! Measures the cost of the `StrategySet` operations performed while building strategies: cloning
+//! a set, looking up a registered strategy and registering a new one.
+//!
+//! Run with `cargo bench`.
+
+use std::time::{Duration, Instant};
+
+use proptest::strategy::{Just, SBoxedStrategy, Strategy};
+
+use proptest_recurse::StrategySet;
+
+const ITERATIONS: u32 = 100_000;
+const ROUNDS: u32 = 5;
+
+fn just<T: Clone + std::fmt::Debug + Send + Sync + 'static>(
+    value: T,
+) -> impl FnOnce(&mut StrategySet) -> SBoxedStrategy<T> {
+    move |_| Just(value).sboxed()
+}
+
+/// Returns a set with strategies registered for several types, as a typical grammar would have.
+fn populated() -> StrategySet {
+    let mut set = StrategySet::default();
+    let _ = set.get(just(0u8));
+    let _ = set.get(just(0u16));
+    let _ = set.get(just(0u32));
+    let _ = set.get(just(0u64));
+    let _ = set.get(just(0i8));
+    let _ = set.get(just(0i16));
+    let _ = set.get(just(0i32));
+    let _ = set.get(just(0i64));
+    let _ = set.get(just(String::new()));
+    let _ = set.get(just(false));
+    set
+}
+
+fn bench<R>(name: &str, mut f: impl FnMut() -> R) {
+    let mut best = None;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        for _ in 0..ITERATIONS {
+            std::hint::black_box(f());
+        }
+        let elapsed = start.elapsed();
+        best = Some(best.map_or(elapsed, |best: Duration| best.min(elapsed)));
+    }
+
+    let best = best.unwrap();
+    println!(
+        "{:<10} {:>10.2?} best of {}, {:>8.2?} per call",
+        name,
+        best,
+        ROUNDS,
+        best / ITERATIONS
+    );
+}
+
+fn main() {
+    let set = populated();
+
+    bench("clone", || set.clone());
+    bench("get", || set.clone().get(just(0u32)));
+    bench("insert", || set.clone().get(just('a')));
+    bench("populate", populated);
+}
@@ -0,0 +1,111 @@
+#![deny(missing_docs)]
+
+//! Derive macros for [`proptest-recurse`](https://crates.io/crates/proptest-recurse). This crate
+//! should be used through the `derive` feature of `proptest-recurse` rather than directly.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, Data, DeriveInput, Field, Fields, GenericParam};
+
+/// Derives `TreeSize`, counting one node for the value itself plus the node counts of its fields.
+/// Fields marked `#[tree_size(skip)]` are not counted.
+#[proc_macro_derive(TreeSize, attributes(tree_size))]
+pub fn derive_tree_size(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match tree_size(input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn tree_size(mut input: DeriveInput) -> syn::Result<TokenStream> {
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let (pattern, count) = fields(&data.fields)?;
+            quote! {
+                let Self #pattern = self;
+                1 #count
+            }
+        }
+        Data::Enum(data) => {
+            let arms = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let ident = &variant.ident;
+                    let (pattern, count) = fields(&variant.fields)?;
+                    Ok(quote! { Self::#ident #pattern => 1 #count, })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! {
+                match self {
+                    #(#arms)*
+                }
+            }
+        }
+        Data::Union(data) => {
+            return Err(syn::Error::new_spanned(
+                data.union_token,
+                "`TreeSize` cannot be derived for unions",
+            ))
+        }
+    };
+
+    for param in &mut input.generics.params {
+        if let GenericParam::Type(param) = param {
+            param
+                .bounds
+                .push(syn::parse_quote!(::proptest_recurse::TreeSize));
+        }
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::proptest_recurse::TreeSize for #ident #ty_generics #where_clause {
+            fn node_count(&self) -> u64 {
+                #body
+            }
+        }
+    })
+}
+
+/// Returns a pattern binding the fields, and an expression adding their node counts.
+fn fields(fields: &Fields) -> syn::Result<(TokenStream, TokenStream)> {
+    let mut bindings = Vec::new();
+    let mut counts = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let binding = format_ident!("__field{}", index);
+        if !is_skipped(field)? {
+            counts.push(quote! { + ::proptest_recurse::TreeSize::node_count(#binding) });
+        }
+        bindings.push(match &field.ident {
+            Some(ident) => quote! { #ident: #binding },
+            None => quote! { #binding },
+        });
+    }
+
+    let pattern = match fields {
+        Fields::Named(_) => quote! { { #(#bindings),* } },
+        Fields::Unnamed(_) => quote! { ( #(#bindings),* ) },
+        Fields::Unit => quote! {},
+    };
+    Ok((pattern, quote! { #(#counts)* }))
+}
+
+fn is_skipped(field: &Field) -> syn::Result<bool> {
+    let mut skip = false;
+    for attr in &field.attrs {
+        if attr.path().is_ident("tree_size") {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    skip = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown `tree_size` attribute"))
+                }
+            })?;
+        }
+    }
+    Ok(skip)
+}
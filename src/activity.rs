@@ -0,0 +1,43 @@
+//! Cheap checks for whether a thread-local hook is in use.
+//!
+//! Recursive strategies call hooks such as `guard::node` and `depth::leaf` for every node they
+//! generate or build, but the thread-local state these hooks look at is only set up by a few
+//! wrappers, like `with_depth` or `replay`. Each hook has an [`Activity`] counting the scopes in
+//! which its state is set up, on any thread, so that while there are none the hook costs a single
+//! atomic load instead of a thread-local access.
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+/// The number of scopes, across all threads, in which a hook's thread-local state is set up.
+///
+/// A thread only needs to observe the scopes it entered itself, which it always does, so relaxed
+/// ordering is enough. Scopes on other threads merely make the check fall back to the thread-local
+/// state, which is then empty on this thread.
+pub(crate) struct Activity(AtomicUsize);
+
+impl Activity {
+    pub(crate) const fn new() -> Self {
+        Activity(AtomicUsize::new(0))
+    }
+
+    /// Returns whether the hook's thread-local state may be set up on this thread.
+    pub(crate) fn is_active(&self) -> bool {
+        self.0.load(Ordering::Relaxed) != 0
+    }
+
+    /// Enters a scope, which lasts until the returned guard is dropped. Enter it before setting up
+    /// the thread-local state, and drop it after tearing the state down.
+    pub(crate) fn enter(&'static self) -> Active {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        Active(self)
+    }
+}
+
+/// Guard returned by [`Activity::enter`].
+pub(crate) struct Active(&'static Activity);
+
+impl Drop for Active {
+    fn drop(&mut self) {
+        self.0 .0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
@@ -0,0 +1,126 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::sync::Mutex;
+
+use proptest::strategy::{NewTree, SBoxedStrategy, Strategy, Union, ValueTree};
+use proptest::test_runner::TestRunner;
+
+/// The factor by which the weight of an underrepresented alternative is increased.
+const BOOST: u32 = 16;
+
+/// Strategy returned by [`balanced_oneof`](crate::balanced_oneof).
+pub(crate) struct Balanced<T> {
+    alternatives: Vec<(u32, SBoxedStrategy<T>)>,
+    /// The number of times each alternative has been generated, shared between clones. These
+    /// persist across test cases, so generated values depend on the earlier cases as well as the
+    /// seed.
+    counts: Arc<Mutex<Vec<u64>>>,
+}
+
+impl<T: fmt::Debug> fmt::Debug for Balanced<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Balanced")
+            .field("alternatives", &self.alternatives)
+            .field("counts", &self.counts)
+            .finish()
+    }
+}
+
+impl<T: fmt::Debug + 'static> Balanced<T> {
+    pub(crate) fn new(alternatives: Vec<(u32, SBoxedStrategy<T>)>) -> Self {
+        assert!(
+            !alternatives.is_empty(),
+            "balanced_oneof requires at least one alternative"
+        );
+        assert!(
+            alternatives.iter().all(|&(weight, _)| weight > 0),
+            "balanced_oneof weights must be positive"
+        );
+        Balanced {
+            counts: Arc::new(Mutex::new(vec![0; alternatives.len()])),
+            alternatives,
+        }
+    }
+
+    /// Returns the weight of each alternative, increased for alternatives which have been
+    /// generated less often than their target share of values. The target share is halfway
+    /// between the share given by the original weights and an equal share, so every alternative
+    /// is eventually generated reasonably often.
+    fn weights(&self) -> Vec<u32> {
+        let counts = self.counts.lock().unwrap();
+        let total_count: u64 = counts.iter().sum();
+        let total_weight: u64 = self
+            .alternatives
+            .iter()
+            .map(|&(weight, _)| u64::from(weight))
+            .sum();
+
+        self.alternatives
+            .iter()
+            .zip(counts.iter())
+            .map(|(&(weight, _), &count)| {
+                let share = f64::from(weight) / total_weight as f64;
+                let equal_share = 1.0 / self.alternatives.len() as f64;
+                let expected = total_count as f64 * (share + equal_share) / 2.0;
+                if (count as f64) < expected {
+                    weight.saturating_mul(BOOST)
+                } else {
+                    weight
+                }
+            })
+            .collect()
+    }
+}
+
+impl<T: fmt::Debug + 'static> Strategy for Balanced<T> {
+    type Tree = Box<dyn ValueTree<Value = T>>;
+    type Value = T;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let union = Union::new_weighted(
+            self.weights()
+                .into_iter()
+                .zip(&self.alternatives)
+                .enumerate()
+                .map(|(index, (weight, (_, strategy)))| {
+                    let counted = Counted {
+                        inner: strategy.clone(),
+                        counts: Arc::clone(&self.counts),
+                        index,
+                    };
+                    (weight, counted.sboxed())
+                })
+                .collect(),
+        );
+        Ok(Box::new(union.new_tree(runner)?))
+    }
+}
+
+/// Wraps an alternative, counting each value tree created from it.
+struct Counted<T> {
+    inner: SBoxedStrategy<T>,
+    counts: Arc<Mutex<Vec<u64>>>,
+    index: usize,
+}
+
+impl<T: fmt::Debug> fmt::Debug for Counted<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Counted")
+            .field("inner", &self.inner)
+            .field("index", &self.index)
+            .finish()
+    }
+}
+
+impl<T: fmt::Debug> Strategy for Counted<T> {
+    type Tree = Box<dyn ValueTree<Value = T>>;
+    type Value = T;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.counts.lock().unwrap()[self.index] += 1;
+        self.inner.new_tree(runner)
+    }
+}
@@ -0,0 +1,182 @@
+//! Collection strategies for use inside branch functions.
+//!
+//! A branch function which generates a collection of nested values, such as
+//! `vec(set.get(arb_tree), 0..8)`, multiplies the number of nodes at every level. The strategies
+//! in this module instead shrink the maximum length of the collection as the remaining depth of
+//! the element type decreases, so the outermost levels may be wide while the innermost levels stay
+//! small.
+//!
+//! The maximum length at a level of a recursive strategy with depth `d` is scaled by
+//! `(d - level) / d`, where the outermost level is level 0, and is never below the minimum length.
+//! Outside the branch function of a recursive strategy for the element type, the full size range
+//! is used.
+//!
+//! Common wrapper types implement [`Family`], so their strategies can be built from the element
+//! strategy with [`StrategySet::get_family`] instead of a factory for each wrapper type.
+//!
+//! # Examples
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! # use proptest::strategy::{Just, SBoxedStrategy};
+//! use proptest_recurse::collection::recursive_vec;
+//! use proptest_recurse::{StrategyExt, StrategySet};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Tree {
+//!     Leaf,
+//!     Node(Vec<Tree>),
+//! }
+//!
+//! fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+//!     Just(Tree::Leaf).prop_mutually_recursive(4, 64, 4, set, |set| {
+//!         recursive_vec(set, arb_tree, 0..8).prop_map(Tree::Node).sboxed()
+//!     })
+//! }
+//! # let _ = arb_tree(&mut StrategySet::default());
+//! ```
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::{Any, TypeId};
+use core::fmt;
+#[cfg(feature = "std")]
+use core::hash::Hash;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[cfg(feature = "std")]
+use proptest::collection::hash_map;
+use proptest::collection::{btree_map, vec, vec_deque, SizeRange};
+use proptest::prop_oneof;
+use proptest::strategy::{LazyJust, SBoxedStrategy, Strategy};
+
+use crate::StrategySet;
+
+/// Returns a strategy for vectors of `T`, whose length is in `size` scaled by the remaining depth
+/// of `T`. The element strategy is looked up in `set`, and created using `factory` if necessary.
+pub fn recursive_vec<T, F>(
+    set: &mut StrategySet,
+    factory: F,
+    size: impl Into<SizeRange>,
+) -> SBoxedStrategy<Vec<T>>
+where
+    T: Any + fmt::Debug,
+    F: FnOnce(&mut StrategySet) -> SBoxedStrategy<T>,
+{
+    let size = scale::<T>(set, size.into());
+    vec(set.get(factory), size).sboxed()
+}
+
+/// Returns a strategy for maps from keys generated by `keys` to values of `T`, whose length is in
+/// `size` scaled by the remaining depth of `T`. The value strategy is looked up in `set`, and
+/// created using `factory` if necessary.
+///
+/// As with `proptest::collection::btree_map`, duplicate keys may make the map smaller than the
+/// minimum size, if `keys` cannot produce enough distinct values.
+pub fn recursive_btree_map<K, T, F>(
+    set: &mut StrategySet,
+    keys: K,
+    factory: F,
+    size: impl Into<SizeRange>,
+) -> SBoxedStrategy<BTreeMap<K::Value, T>>
+where
+    K: Strategy + Send + Sync + 'static,
+    K::Value: Ord,
+    T: Any + fmt::Debug,
+    F: FnOnce(&mut StrategySet) -> SBoxedStrategy<T>,
+{
+    let size = scale::<T>(set, size.into());
+    btree_map(keys, set.get(factory), size).sboxed()
+}
+
+/// Returns a strategy for hash maps from keys generated by `keys` to values of `T`. See
+/// [`recursive_btree_map`] for details.
+#[cfg(feature = "std")]
+pub fn recursive_hash_map<K, T, F>(
+    set: &mut StrategySet,
+    keys: K,
+    factory: F,
+    size: impl Into<SizeRange>,
+) -> SBoxedStrategy<HashMap<K::Value, T>>
+where
+    K: Strategy + Send + Sync + 'static,
+    K::Value: Hash + Eq,
+    T: Any + fmt::Debug,
+    F: FnOnce(&mut StrategySet) -> SBoxedStrategy<T>,
+{
+    let size = scale::<T>(set, size.into());
+    hash_map(keys, set.get(factory), size).sboxed()
+}
+
+/// The length of collections built by [`Family`] implementations, before scaling by the remaining
+/// depth of the element type.
+pub const FAMILY_SIZE: core::ops::Range<usize> = 0..8;
+
+/// Types whose strategy can be built from the strategy for a single element type, such as
+/// `Vec<T>` or `Option<T>`. See [`StrategySet::get_family`].
+pub trait Family: Any + fmt::Debug + Sized {
+    /// The type of the contained values.
+    type Element: Any + fmt::Debug;
+
+    /// Returns a strategy for this type, given a strategy for its elements. `set` is the set the
+    /// element strategy was looked up in.
+    fn strategy(set: &StrategySet, element: SBoxedStrategy<Self::Element>) -> SBoxedStrategy<Self>;
+}
+
+/// Vectors with lengths in [`FAMILY_SIZE`], scaled by the remaining depth of `T`.
+impl<T: Any + fmt::Debug> Family for Vec<T> {
+    type Element = T;
+
+    fn strategy(set: &StrategySet, element: SBoxedStrategy<T>) -> SBoxedStrategy<Self> {
+        vec(element, scale::<T>(set, FAMILY_SIZE.into())).sboxed()
+    }
+}
+
+/// Double-ended queues with lengths in [`FAMILY_SIZE`], scaled by the remaining depth of `T`.
+impl<T: Any + fmt::Debug> Family for VecDeque<T> {
+    type Element = T;
+
+    fn strategy(set: &StrategySet, element: SBoxedStrategy<T>) -> SBoxedStrategy<Self> {
+        vec_deque(element, scale::<T>(set, FAMILY_SIZE.into())).sboxed()
+    }
+}
+
+/// Options which are `Some` half of the time.
+impl<T: Any + fmt::Debug> Family for Option<T> {
+    type Element = T;
+
+    fn strategy(_: &StrategySet, element: SBoxedStrategy<T>) -> SBoxedStrategy<Self> {
+        prop_oneof![LazyJust::new(|| None), element.prop_map(Some)].sboxed()
+    }
+}
+
+impl<T: Any + fmt::Debug> Family for Box<T> {
+    type Element = T;
+
+    fn strategy(_: &StrategySet, element: SBoxedStrategy<T>) -> SBoxedStrategy<Self> {
+        element.prop_map(Box::new).sboxed()
+    }
+}
+
+impl<T: Any + fmt::Debug + Send + Sync> Family for Arc<T> {
+    type Element = T;
+
+    fn strategy(_: &StrategySet, element: SBoxedStrategy<T>) -> SBoxedStrategy<Self> {
+        element.prop_map(Arc::new).sboxed()
+    }
+}
+
+pub(crate) fn scale<T: Any>(set: &StrategySet, size: SizeRange) -> SizeRange {
+    let (start, end) = size.start_end_incl();
+    match set.levels.get(&TypeId::of::<T>()) {
+        Some(&(level, depth)) if depth > 0 => {
+            let remaining = depth.saturating_sub(level);
+            let end = (end as u64 * u64::from(remaining)).div_ceil(u64::from(depth)) as usize;
+            SizeRange::new(start..=end.max(start))
+        }
+        _ => size,
+    }
+}
@@ -0,0 +1,237 @@
+//! Recursive strategies which thread a context value down to each subtree.
+//!
+//! The branch functions of [`prop_mutually_recursive`](crate::StrategyExt::prop_mutually_recursive)
+//! are called once per level, so every node at a level is generated from the same strategy. This
+//! makes some invariants impossible to express, such as requiring every variable in a generated
+//! program to be bound by an enclosing binder. [`recursive_in_context`] instead builds the
+//! strategy for each node from a context value, such as the names in scope, which the branch
+//! function can extend for the subtrees it generates. Values are then well-formed by
+//! construction, and stay well-formed while shrinking.
+//!
+//! The depth and branch probabilities are chosen in the same way as for
+//! `prop_mutually_recursive`, from the same depth and size arguments. The other options of
+//! [`RecursiveParams`](crate::RecursiveParams) rely on the strategy for each level being built
+//! once, so they aren't available here. Since strategies are built for each node as it is
+//! generated, this is slower than a recursive strategy whose branch function doesn't depend on a
+//! context.
+//!
+//! # Examples
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! # use proptest::sample::select;
+//! # use proptest::strategy::Just;
+//! use proptest_recurse::context::recursive_in_context;
+//!
+//! #[derive(Clone, Debug)]
+//! enum Term {
+//!     Unit,
+//!     Var(usize),
+//!     Lam(Box<Term>),
+//!     App(Box<Term>, Box<Term>),
+//! }
+//!
+//! // The context is the number of enclosing lambdas, so variables are de Bruijn indices which are
+//! // always in range.
+//! let terms = recursive_in_context(
+//!     0usize,
+//!     6,
+//!     32,
+//!     2,
+//!     |&bound| match bound {
+//!         0 => Just(Term::Unit).sboxed(),
+//!         bound => select((0..bound).collect::<Vec<_>>()).prop_map(Term::Var).sboxed(),
+//!     },
+//!     |&bound, subtree| {
+//!         prop_oneof![
+//!             subtree.get(bound + 1).prop_map(|body| Term::Lam(Box::new(body))),
+//!             (subtree.get(bound), subtree.get(bound))
+//!                 .prop_map(|(f, x)| Term::App(Box::new(f), Box::new(x))),
+//!         ]
+//!         .sboxed()
+//!     },
+//! );
+//! # let _ = terms;
+//! ```
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use proptest::strategy::{NewTree, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use crate::shape::{coin, LevelTree};
+use crate::{depth, guard, mutate, stack, RecursiveParams};
+
+type Base<C, T> = Box<dyn Fn(&C) -> SBoxedStrategy<T> + Send + Sync>;
+type Branch<C, T> = Box<dyn Fn(&C, &Subtree<C, T>) -> SBoxedStrategy<T> + Send + Sync>;
+
+/// Returns a strategy for a recursive type whose nodes are generated from a context value,
+/// starting with `context` for the outermost node.
+///
+/// `depth`, `desired_size` and `expected_branch_size` have the same meaning as for
+/// [`prop_mutually_recursive`](crate::StrategyExt::prop_mutually_recursive). `base` returns the
+/// strategy for leaves in a given context. `branch` returns the strategy for nodes which recurse,
+/// and obtains the strategies for their children from the [`Subtree`] argument, passing each
+/// child the context it should be generated in. See the [module documentation](self) for details.
+pub fn recursive_in_context<T, C, B, F>(
+    context: C,
+    depth: u32,
+    desired_size: u32,
+    expected_branch_size: u32,
+    base: B,
+    branch: F,
+) -> SBoxedStrategy<T>
+where
+    T: fmt::Debug + 'static,
+    C: Clone + fmt::Debug + Send + Sync + 'static,
+    B: Fn(&C) -> SBoxedStrategy<T> + Send + Sync + 'static,
+    F: Fn(&C, &Subtree<C, T>) -> SBoxedStrategy<T> + Send + Sync + 'static,
+{
+    let definition = Arc::new(Definition {
+        base: Box::new(base),
+        branch: Box::new(branch),
+        branch_probabilities: RecursiveParams::new(depth, desired_size, expected_branch_size)
+            .branch_probabilities(None),
+    });
+    Node {
+        definition,
+        context,
+        level: 0,
+    }
+    .sboxed()
+}
+
+struct Definition<C, T> {
+    base: Base<C, T>,
+    branch: Branch<C, T>,
+    branch_probabilities: Vec<f64>,
+}
+
+/// Provides the strategies for the children of a node generated by
+/// [`recursive_in_context`].
+pub struct Subtree<C, T> {
+    definition: Arc<Definition<C, T>>,
+    level: usize,
+}
+
+impl<C, T> fmt::Debug for Subtree<C, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Subtree")
+            .field("level", &self.level)
+            .finish()
+    }
+}
+
+impl<C, T> Subtree<C, T>
+where
+    T: fmt::Debug + 'static,
+    C: Clone + fmt::Debug + Send + Sync + 'static,
+{
+    /// Returns the strategy for a child generated in `context`.
+    pub fn get(&self, context: C) -> SBoxedStrategy<T> {
+        Node {
+            definition: Arc::clone(&self.definition),
+            context,
+            level: self.level,
+        }
+        .sboxed()
+    }
+}
+
+/// Strategy for a node which may recurse at `level` or any level inside it.
+struct Node<C, T> {
+    definition: Arc<Definition<C, T>>,
+    context: C,
+    level: usize,
+}
+
+impl<C: fmt::Debug, T> fmt::Debug for Node<C, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Node")
+            .field("context", &self.context)
+            .field("level", &self.level)
+            .finish()
+    }
+}
+
+impl<C, T> Strategy for Node<C, T>
+where
+    T: fmt::Debug + 'static,
+    C: Clone + fmt::Debug + Send + Sync + 'static,
+{
+    type Tree = Box<dyn ValueTree<Value = T>>;
+    type Value = T;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        stack::grow(|| -> NewTree<Self> {
+            guard::node();
+            let definition = &self.definition;
+            // As in `Tower`, the node falls through the levels until one of them recurses, and
+            // the branch probability is clamped so that nodes which don't recurse remain
+            // reasonably common.
+            for level in self.level..definition.branch_probabilities.len() {
+                let branch_probability = definition.branch_probabilities[level].min(0.9);
+                if branch_probability <= 0.0 || !coin(runner, branch_probability)? {
+                    continue;
+                }
+
+                let branch = (definition.branch)(
+                    &self.context,
+                    &Subtree {
+                        definition: Arc::clone(definition),
+                        level: level + 1,
+                    },
+                )
+                .new_tree(runner)?;
+                let leaf = self.at_level(level + 1).sboxed();
+                let regenerate = self.at_level(self.level).sboxed();
+                return Ok(Box::new(LevelTree::new(branch, leaf, regenerate, runner)));
+            }
+
+            Ok(Box::new(LeafTree {
+                tree: (definition.base)(&self.context).new_tree(runner)?,
+                regenerate: self.at_level(self.level).sboxed(),
+            }))
+        })
+    }
+}
+
+impl<C: Clone, T> Node<C, T> {
+    /// Returns the strategy for a node in the same context, which may recurse at `level` or any
+    /// level inside it.
+    fn at_level(&self, level: usize) -> Self {
+        Node {
+            definition: Arc::clone(&self.definition),
+            context: self.context.clone(),
+            level,
+        }
+    }
+}
+
+/// Value tree for a node generated by `base`.
+struct LeafTree<T> {
+    tree: Box<dyn ValueTree<Value = T>>,
+    regenerate: SBoxedStrategy<T>,
+}
+
+impl<T: fmt::Debug> ValueTree for LeafTree<T> {
+    type Value = T;
+
+    fn current(&self) -> T {
+        mutate::node(&self.regenerate, || {
+            depth::leaf();
+            self.tree.current()
+        })
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.tree.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.tree.complicate()
+    }
+}
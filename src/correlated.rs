@@ -0,0 +1,90 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use core::fmt;
+
+use proptest::strategy::{NewTree, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use crate::sync::Mutex;
+use crate::StrategySet;
+
+type Factory<T> = Arc<dyn Fn(&mut StrategySet) -> SBoxedStrategy<T> + Send + Sync>;
+type Pair<A, B> = (SBoxedStrategy<A>, SBoxedStrategy<B>);
+
+/// The fraction of each recursive strategy's size parameters available to values generated from a
+/// set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct Budget(pub(crate) f64);
+
+impl Default for Budget {
+    fn default() -> Self {
+        Budget(1.0)
+    }
+}
+
+/// The number of ways the budget can be split between the two values.
+const SPLITS: u32 = 100;
+
+/// Strategy for a pair of values which split a single budget between them.
+pub(crate) struct Correlated<A, B> {
+    set: StrategySet,
+    first: Factory<A>,
+    second: Factory<B>,
+    /// The strategies created by the factories for each split of the budget.
+    strategies: Mutex<BTreeMap<u32, Pair<A, B>>>,
+}
+
+impl<A, B> Correlated<A, B> {
+    pub(crate) fn new(set: StrategySet, first: Factory<A>, second: Factory<B>) -> Self {
+        Correlated {
+            set,
+            first,
+            second,
+            strategies: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns the strategies for the two values when the first is given `split` hundredths of
+    /// the budget, creating them on first use.
+    fn strategies(&self, split: u32) -> Pair<A, B> {
+        if let Some(strategies) = self.strategies.lock().unwrap().get(&split) {
+            return strategies.clone();
+        }
+
+        let fraction = f64::from(split) / f64::from(SPLITS);
+        let budget = self.set.budget.0;
+        let mut first_set = self.set.clone();
+        first_set.budget = Budget(budget * fraction);
+        let mut second_set = self.set.clone();
+        second_set.budget = Budget(budget * (1.0 - fraction));
+
+        let strategies = ((self.first)(&mut first_set), (self.second)(&mut second_set));
+        self.strategies
+            .lock()
+            .unwrap()
+            .entry(split)
+            .or_insert(strategies)
+            .clone()
+    }
+}
+
+impl<A, B> fmt::Debug for Correlated<A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Correlated")
+            .field("set", &self.set)
+            .field("first", &"<function>")
+            .field("second", &"<function>")
+            .finish()
+    }
+}
+
+impl<A: fmt::Debug + 'static, B: fmt::Debug + 'static> Strategy for Correlated<A, B> {
+    type Tree = Box<dyn ValueTree<Value = (A, B)>>;
+    type Value = (A, B);
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let split = (0..=SPLITS).new_tree(runner)?.current();
+        Ok(Box::new(self.strategies(split).new_tree(runner)?))
+    }
+}
@@ -0,0 +1,169 @@
+//! Support for generating cyclic structures.
+//!
+//! Strategies can't directly produce values containing cycles, so instead a branch function asks
+//! for a [`BackRef`] using [`StrategySet::back_ref`](crate::StrategySet::back_ref), which refers to
+//! an ancestor of the node being generated. After generation, the tree is walked to build the
+//! real graph, using an [`Ancestors`] stack to resolve each back-reference into a `Weak` pointer
+//! or index.
+//!
+//! # Examples
+//!
+//! ```
+//! # use std::cell::RefCell;
+//! # use std::rc::{Rc, Weak};
+//! # use proptest::collection::vec;
+//! # use proptest::prelude::*;
+//! # use proptest::strategy::Just;
+//! use proptest_recurse::cycle::{Ancestors, BackRef};
+//! use proptest_recurse::{StrategyExt, StrategySet};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Edge {
+//!     Child(Tree),
+//!     Back(BackRef<Tree>),
+//! }
+//!
+//! #[derive(Clone, Debug)]
+//! struct Tree(Vec<Edge>);
+//!
+//! fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+//!     Just(Tree(vec![])).prop_mutually_recursive(4, 16, 2, set, |set| {
+//!         let edge = prop_oneof![
+//!             set.get::<Tree, _>(arb_tree).prop_map(Edge::Child),
+//!             set.back_ref::<Tree>().prop_map(Edge::Back),
+//!         ];
+//!         vec(edge, 0..3).prop_map(Tree).sboxed()
+//!     })
+//! }
+//!
+//! struct Node {
+//!     children: Vec<Rc<RefCell<Node>>>,
+//!     back_edges: Vec<Weak<RefCell<Node>>>,
+//! }
+//!
+//! fn build(tree: &Tree, ancestors: &mut Ancestors<Weak<RefCell<Node>>>) -> Rc<RefCell<Node>> {
+//!     let node = Rc::new(RefCell::new(Node { children: vec![], back_edges: vec![] }));
+//!     ancestors.push(Rc::downgrade(&node));
+//!     for edge in &tree.0 {
+//!         match edge {
+//!             Edge::Child(child) => {
+//!                 let child = build(child, ancestors);
+//!                 node.borrow_mut().children.push(child);
+//!             }
+//!             Edge::Back(back_ref) => {
+//!                 let target = ancestors.resolve(*back_ref).clone();
+//!                 node.borrow_mut().back_edges.push(target);
+//!             }
+//!         }
+//!     }
+//!     ancestors.pop();
+//!     node
+//! }
+//! # let _ = arb_tree(&mut StrategySet::default());
+//! # let _ = build(&Tree(vec![]), &mut Ancestors::new());
+//! ```
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::marker::PhantomData;
+
+/// A reference to an ancestor of type `T` of the node containing it.
+///
+/// An index of 0 refers to the innermost enclosing node of type `T`, 1 to its parent of type `T`,
+/// and so on. Since the number of ancestors isn't known while generating, indices wrap around
+/// when resolved, so a back-reference is always valid for the tree it was generated in.
+pub struct BackRef<T> {
+    index: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> BackRef<T> {
+    /// Creates a back-reference to the `index`th ancestor of type `T`.
+    pub fn new(index: u32) -> Self {
+        BackRef {
+            index,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the unresolved index of the ancestor.
+    pub fn index(self) -> u32 {
+        self.index
+    }
+}
+
+impl<T> fmt::Debug for BackRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("BackRef").field(&self.index).finish()
+    }
+}
+
+impl<T> Clone for BackRef<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for BackRef<T> {}
+
+impl<T> PartialEq for BackRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for BackRef<T> {}
+
+/// A stack of the ancestors of the node currently being materialized, used to resolve
+/// [`BackRef`]s.
+#[derive(Clone, Debug)]
+pub struct Ancestors<P> {
+    stack: Vec<P>,
+}
+
+impl<P> Default for Ancestors<P> {
+    fn default() -> Self {
+        Ancestors { stack: Vec::new() }
+    }
+}
+
+impl<P> Ancestors<P> {
+    /// Creates an empty stack.
+    pub fn new() -> Self {
+        Ancestors::default()
+    }
+
+    /// Pushes a node before materializing its children.
+    pub fn push(&mut self, node: P) {
+        self.stack.push(node);
+    }
+
+    /// Pops the innermost node after materializing its children.
+    pub fn pop(&mut self) -> Option<P> {
+        self.stack.pop()
+    }
+
+    /// Returns the number of nodes in the stack.
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns `true` if the stack is empty.
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    /// Returns the ancestor referred to by `back_ref`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the stack is empty.
+    pub fn resolve<T>(&self, back_ref: BackRef<T>) -> &P {
+        assert!(
+            !self.is_empty(),
+            "cannot resolve a back-reference with no ancestors"
+        );
+        let distance = back_ref.index as usize % self.stack.len();
+        &self.stack[self.stack.len() - 1 - distance]
+    }
+}
@@ -0,0 +1,303 @@
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+use core::fmt;
+#[cfg(feature = "std")]
+use core::mem;
+use core::ops::Deref;
+
+#[cfg(feature = "std")]
+use proptest::strategy::{NewTree, Strategy, ValueTree};
+#[cfg(feature = "std")]
+use proptest::test_runner::TestRunner;
+
+#[cfg(feature = "std")]
+use crate::activity::Activity;
+
+/// A generated value together with the shape of the recursion that produced it. Returned by
+/// [`with_depth`](crate::with_depth).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct WithDepth<T> {
+    /// The generated value.
+    pub value: T,
+    /// The number of recursive nodes on the longest path from the root of the value to a leaf.
+    /// A value generated by the base strategy has a depth of 0.
+    pub depth: u32,
+    /// The total number of nodes in the value, counting both recursive nodes and leaves.
+    pub node_count: u64,
+}
+
+/// A generated value whose `Debug` output is followed by the shape of the recursion that produced
+/// it, such as `Add(Lit(0), Lit(1)) (depth 1, 3 nodes)`. Returned by
+/// [`diagnosed`](crate::diagnosed).
+///
+/// This type dereferences to the value, so it can usually be used in place of it.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Diagnosed<T>(WithDepth<T>);
+
+impl<T> Diagnosed<T> {
+    /// Returns the value, discarding its depth and node count.
+    pub fn into_inner(self) -> T {
+        self.0.value
+    }
+
+    /// Returns the depth and node count of the value along with the value itself.
+    pub fn with_depth(&self) -> &WithDepth<T> {
+        &self.0
+    }
+}
+
+impl<T> From<WithDepth<T>> for Diagnosed<T> {
+    fn from(with_depth: WithDepth<T>) -> Self {
+        Diagnosed(with_depth)
+    }
+}
+
+impl<T> Deref for Diagnosed<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Diagnosed<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.value.fmt(f)?;
+        write!(
+            f,
+            " (depth {}, {} node{})",
+            self.0.depth,
+            self.0.node_count,
+            if self.0.node_count == 1 { "" } else { "s" }
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct Frame {
+    depth: u32,
+    node_count: u64,
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// The nodes currently being built by `current`, innermost last. Empty unless a value is being
+    /// built for a `WithDepth` strategy.
+    static FRAMES: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Tracks the scopes in which frames or a trace are set up.
+#[cfg(feature = "std")]
+static RECORDING: Activity = Activity::new();
+
+/// Records a node with the given depth and node count in the innermost frame, if any.
+#[cfg(feature = "std")]
+fn report(depth: u32, node_count: u64) {
+    FRAMES.with(|frames| {
+        if let Some(frame) = frames.borrow_mut().last_mut() {
+            frame.depth = frame.depth.max(depth);
+            frame.node_count += node_count;
+        }
+    })
+}
+
+/// Runs `f` in a new frame, returning its result and the depth and node count of the nodes
+/// reported while it ran.
+#[cfg(feature = "std")]
+fn in_frame<R>(f: impl FnOnce() -> R) -> (R, Frame) {
+    let _active = RECORDING.enter();
+    FRAMES.with(|frames| frames.borrow_mut().push(Frame::default()));
+    let _pop = PopFrameOnDrop;
+    let result = f();
+    let frame = FRAMES.with(|frames| mem::take(frames.borrow_mut().last_mut().unwrap()));
+    (result, frame)
+}
+
+/// Pops the innermost frame when dropped, so that a panic while building a value doesn't leave
+/// it in place for the next value built on this thread.
+#[cfg(feature = "std")]
+struct PopFrameOnDrop;
+
+#[cfg(feature = "std")]
+impl Drop for PopFrameOnDrop {
+    fn drop(&mut self) {
+        FRAMES.with(|frames| frames.borrow_mut().pop());
+    }
+}
+
+/// Builds the value of a recursive node with `f`, recording it and the nodes nested in it.
+#[cfg(feature = "std")]
+pub(crate) fn branch<T>(f: impl FnOnce() -> T) -> T {
+    if !RECORDING.is_active() {
+        return f();
+    }
+
+    let traced = enter_node();
+    let value = if FRAMES.with(|frames| frames.borrow().is_empty()) {
+        f()
+    } else {
+        let (value, children) = in_frame(f);
+        report(children.depth + 1, children.node_count + 1);
+        value
+    };
+    if traced {
+        exit_node();
+    }
+    value
+}
+
+/// Records a leaf node.
+#[cfg(feature = "std")]
+pub(crate) fn leaf() {
+    if !RECORDING.is_active() {
+        return;
+    }
+
+    report(0, 1);
+    if enter_node() {
+        exit_node();
+    }
+}
+
+// Without `std` there is nowhere to record nodes, so depth tracking is unavailable.
+#[cfg(not(feature = "std"))]
+pub(crate) fn branch<T>(f: impl FnOnce() -> T) -> T {
+    f()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn leaf() {}
+
+/// A node recorded by `trace`.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct TracedNode {
+    /// The index of the parent node in the trace, or `None` for an outermost node.
+    pub(crate) parent: Option<usize>,
+    /// The number of nodes built before this one with the same parent.
+    pub(crate) index: usize,
+}
+
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct Trace {
+    /// Every node started so far, in the order they were started.
+    nodes: Vec<TracedNode>,
+    /// The nodes currently being built, innermost last, with the number of children started so
+    /// far.
+    open: Vec<(usize, usize)>,
+    /// The number of outermost nodes started so far.
+    roots: usize,
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// The nodes built so far by `current` for a value whose construction is being traced.
+    static TRACE: RefCell<Option<Trace>> = const { RefCell::new(None) };
+}
+
+/// Records the start of a node in the trace, if one is active, returning whether it was recorded.
+#[cfg(feature = "std")]
+fn enter_node() -> bool {
+    TRACE.with(|trace| match trace.borrow_mut().as_mut() {
+        Some(trace) => {
+            let (parent, index) = match trace.open.last_mut() {
+                Some((parent, children)) => (Some(*parent), post_increment(children)),
+                None => (None, post_increment(&mut trace.roots)),
+            };
+            trace.open.push((trace.nodes.len(), 0));
+            trace.nodes.push(TracedNode { parent, index });
+            true
+        }
+        None => false,
+    })
+}
+
+#[cfg(feature = "std")]
+fn exit_node() {
+    TRACE.with(|trace| {
+        if let Some(trace) = trace.borrow_mut().as_mut() {
+            trace.open.pop();
+        }
+    })
+}
+
+#[cfg(feature = "std")]
+fn post_increment(count: &mut usize) -> usize {
+    *count += 1;
+    *count - 1
+}
+
+/// Builds a value with `f`, returning it with the nodes built, in the order they were started.
+#[cfg(feature = "std")]
+pub(crate) fn trace<T>(f: impl FnOnce() -> T) -> (T, Vec<TracedNode>) {
+    let _active = RECORDING.enter();
+    let outer = TRACE.with(|trace| trace.borrow_mut().replace(Trace::default()));
+    let _restore = RestoreTraceOnDrop(outer);
+    let value = f();
+    let trace = TRACE.with(|trace| trace.borrow_mut().take()).unwrap();
+    (value, trace.nodes)
+}
+
+/// Restores the enclosing trace, if any, when dropped, so that a panic while building a traced
+/// value doesn't leave its trace active for the next value built on this thread.
+#[cfg(feature = "std")]
+struct RestoreTraceOnDrop(Option<Trace>);
+
+#[cfg(feature = "std")]
+impl Drop for RestoreTraceOnDrop {
+    fn drop(&mut self) {
+        let outer = self.0.take();
+        TRACE.with(|trace| *trace.borrow_mut() = outer);
+    }
+}
+
+/// Builds a value with `f`, returning it with the number of nodes built.
+#[cfg(feature = "std")]
+pub(crate) fn count_nodes<T>(f: impl FnOnce() -> T) -> (T, u64) {
+    let (value, frame) = in_frame(f);
+    (value, frame.node_count)
+}
+
+/// Strategy returned by [`with_depth`](crate::with_depth).
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub(crate) struct Measure<S>(pub(crate) S);
+
+#[cfg(feature = "std")]
+impl<S: Strategy> Strategy for Measure<S> {
+    type Tree = MeasureTree<S::Tree>;
+    type Value = WithDepth<S::Value>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.0.new_tree(runner).map(MeasureTree)
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) struct MeasureTree<T>(T);
+
+#[cfg(feature = "std")]
+impl<T: ValueTree> ValueTree for MeasureTree<T> {
+    type Value = WithDepth<T::Value>;
+
+    fn current(&self) -> Self::Value {
+        let (value, frame) = in_frame(|| self.0.current());
+        WithDepth {
+            value,
+            depth: frame.depth,
+            node_count: frame.node_count,
+        }
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.0.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.0.complicate()
+    }
+}
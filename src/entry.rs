@@ -0,0 +1,291 @@
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::any::{self, Any, TypeId};
+use core::error::Error;
+use core::fmt;
+
+use proptest::strategy::SBoxedStrategy;
+
+use crate::hooks::{ApplyAll, Hooks};
+use crate::sync::Mutex;
+use crate::{RecursiveParams, StrategySet};
+
+type Erased = Arc<dyn Any + Send + Sync>;
+
+/// A type-erased strategy, along with the name of its value type for error messages.
+#[derive(Clone)]
+pub(crate) struct Entry {
+    strategy: Erased,
+    type_name: &'static str,
+    /// The parameters of the recursive strategy created by the factory for this entry, if any.
+    params: Option<RecursiveParams>,
+    /// The strategy for the non-recursive values of this type, if known.
+    leaf: Option<Erased>,
+    /// Whether this entry was created by a factory passed to `StrategySet::get`, so that it can be
+    /// created again by the next call to `get` for this type.
+    factory: bool,
+    /// Applies the transforms registered with `map_all` to strategies for this type, if its
+    /// `Debug` implementation is known.
+    apply_all: Option<Erased>,
+}
+
+/// The parameters and base strategy of a recursive strategy created by a factory.
+struct Recorded {
+    params: RecursiveParams,
+    leaf: Erased,
+    apply_all: Erased,
+}
+
+/// The recursive strategy created by a running factory for its own type, if any. The set passed
+/// to the factory refers to this, so recursive strategies created from it can report themselves.
+pub(crate) struct Report {
+    type_id: TypeId,
+    recorded: Mutex<Option<Recorded>>,
+}
+
+impl Entry {
+    pub(crate) fn new<T: Any>(strategy: SBoxedStrategy<T>) -> Self {
+        Entry {
+            strategy: Arc::new(strategy),
+            type_name: any::type_name::<T>(),
+            params: None,
+            leaf: None,
+            factory: false,
+            apply_all: None,
+        }
+    }
+
+    /// Sets the strategy for the non-recursive values of this entry's type.
+    pub(crate) fn with_leaf<T: Any>(mut self, leaf: SBoxedStrategy<T>) -> Self {
+        self.leaf = Some(Arc::new(leaf));
+        self
+    }
+
+    /// Allows the transforms registered with `map_all` to be applied to this entry's strategy.
+    pub(crate) fn with_debug<T: Any + fmt::Debug>(mut self) -> Self {
+        self.apply_all = Some(Arc::new(Hooks::apply_all::<T> as ApplyAll<T>));
+        self
+    }
+
+    /// Returns a copy of this entry with its strategy replaced by `strategy`.
+    pub(crate) fn with_strategy<T: Any>(&self, strategy: SBoxedStrategy<T>) -> Self {
+        Entry {
+            strategy: Arc::new(strategy),
+            ..self.clone()
+        }
+    }
+
+    /// Runs the factory `f` for `T` with a copy of `set`, creating an entry for the strategy it
+    /// returns. Any recursive strategy for `T` created by `f` from that set is recorded, so that
+    /// its parameters can be displayed and its base strategy retrieved.
+    pub(crate) fn from_factory<T, E, F>(
+        set: &StrategySet,
+        f: F,
+    ) -> Result<(SBoxedStrategy<T>, Self), E>
+    where
+        T: Any,
+        F: FnOnce(&mut StrategySet) -> Result<SBoxedStrategy<T>, E>,
+    {
+        let report = Arc::new(Report {
+            type_id: TypeId::of::<T>(),
+            recorded: Mutex::new(None),
+        });
+        let mut factory_set = set.clone();
+        factory_set.report = Some(Arc::clone(&report));
+        let strategy = f(&mut factory_set)?;
+
+        let mut entry = Entry::new(strategy.clone());
+        entry.factory = true;
+        // If the factory didn't create a recursive strategy for `T`, its leaves are unknown: the
+        // strategy may still recurse by other means, such as `prop_recursive`.
+        if let Some(recorded) = report.recorded.lock().unwrap().take() {
+            entry.params = Some(recorded.params);
+            entry.leaf = Some(recorded.leaf);
+            entry.apply_all = Some(recorded.apply_all);
+        }
+        Ok((strategy, entry))
+    }
+
+    /// Records that a recursive strategy for `T` was created from `set` with `params` and the base
+    /// strategy `leaf`, if `set` was passed to a running factory for `T`.
+    pub(crate) fn report<T: Any + fmt::Debug>(
+        set: &StrategySet,
+        params: &RecursiveParams,
+        leaf: impl FnOnce() -> SBoxedStrategy<T>,
+    ) {
+        if let Some(report) = &set.report {
+            if report.type_id == TypeId::of::<T>() {
+                report
+                    .recorded
+                    .lock()
+                    .unwrap()
+                    .get_or_insert_with(|| Recorded {
+                        params: params.clone(),
+                        leaf: Arc::new(leaf()),
+                        apply_all: Arc::new(Hooks::apply_all::<T> as ApplyAll<T>),
+                    });
+            }
+        }
+    }
+
+    /// Returns the function applying the transforms registered with `map_all` to strategies for
+    /// `T`, if the `Debug` implementation of `T` is known.
+    pub(crate) fn apply_all<T: Any>(&self) -> Option<ApplyAll<T>> {
+        self.apply_all
+            .as_ref()
+            .and_then(|apply_all| apply_all.downcast_ref::<ApplyAll<T>>())
+            .copied()
+    }
+
+    /// Returns whether this entry was created by a factory.
+    pub(crate) fn is_factory(&self) -> bool {
+        self.factory
+    }
+
+    pub(crate) fn downcast<T: Any>(&self) -> Result<SBoxedStrategy<T>, TypeMismatch> {
+        self.downcast_erased(&self.strategy)
+    }
+
+    /// Returns the strategy for the non-recursive values of this entry's type, if known.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entry has a different value type.
+    pub(crate) fn leaf<T: Any>(&self) -> Option<SBoxedStrategy<T>> {
+        self.leaf.as_ref().map(|leaf| {
+            self.downcast_erased(leaf)
+                .unwrap_or_else(|err| panic!("{}", err))
+        })
+    }
+
+    fn downcast_erased<T: Any>(
+        &self,
+        strategy: &Erased,
+    ) -> Result<SBoxedStrategy<T>, TypeMismatch> {
+        match strategy.downcast_ref::<SBoxedStrategy<T>>() {
+            Some(strategy) => Ok(strategy.clone()),
+            None => Err(TypeMismatch {
+                expected: any::type_name::<T>(),
+                found: self.type_name,
+            }),
+        }
+    }
+
+    /// Like `downcast`, but panics with a descriptive message on failure.
+    pub(crate) fn expect<T: Any>(&self) -> SBoxedStrategy<T> {
+        self.downcast().unwrap_or_else(|err| panic!("{}", err))
+    }
+}
+
+impl fmt::Debug for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&short_type_name(self.type_name))?;
+        if let Some(params) = &self.params {
+            write_params(f, params)?;
+        }
+        Ok(())
+    }
+}
+
+/// Describes a recursive strategy by the name of its value type and its parameters, such as
+/// `Expr (depth=5, size=32)`.
+#[cfg(feature = "std")]
+pub(crate) fn describe(type_name: &str, params: &RecursiveParams) -> String {
+    let mut description = short_type_name(type_name);
+    write_params(&mut description, params).unwrap();
+    description
+}
+
+fn write_params(f: &mut impl fmt::Write, params: &RecursiveParams) -> fmt::Result {
+    write!(f, " (depth={}, size=", params.depth)?;
+    match &params.target_size {
+        Some(range) => write!(f, "{}..={}", range.start(), range.end())?,
+        None => write!(f, "{}", params.desired_size)?,
+    }
+    f.write_str(")")
+}
+
+/// Strips the module paths from a type name, so `alloc::vec::Vec<my_crate::Expr>` becomes
+/// `Vec<Expr>`.
+fn short_type_name(name: &str) -> String {
+    let mut short = String::with_capacity(name.len());
+    // The start of the path currently being written.
+    let mut path_start = 0;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ':' && chars.peek() == Some(&':') {
+            chars.next();
+            short.truncate(path_start);
+        } else {
+            short.push(c);
+            if !(c.is_alphanumeric() || c == '_') {
+                path_start = short.len();
+            }
+        }
+    }
+    short
+}
+
+/// Error returned when the strategy registered for a type has a different value type.
+///
+/// Strategies are keyed by the `TypeId` of their value type, so this indicates a bug, such as a
+/// strategy inserted under the wrong key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeMismatch {
+    expected: &'static str,
+    found: &'static str,
+}
+
+impl TypeMismatch {
+    /// Returns the name of the type that was looked up.
+    pub fn expected(&self) -> &'static str {
+        self.expected
+    }
+
+    /// Returns the name of the value type of the registered strategy.
+    pub fn found(&self) -> &'static str {
+        self.found
+    }
+}
+
+impl fmt::Display for TypeMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "StrategySet entry for `{}` has type `{}`",
+            self.expected, self.found
+        )
+    }
+}
+
+impl Error for TypeMismatch {}
+
+#[test]
+fn type_mismatch() {
+    use alloc::string::ToString;
+    use proptest::strategy::{Just, Strategy};
+
+    let entry = Entry::new(Just(0u32).sboxed());
+    assert!(entry.downcast::<u32>().is_ok());
+    let err = entry.downcast::<u64>().unwrap_err();
+    assert_eq!(err.expected(), "u64");
+    assert_eq!(err.found(), "u32");
+    assert_eq!(
+        err.to_string(),
+        "StrategySet entry for `u64` has type `u32`"
+    );
+}
+
+#[test]
+fn short_type_names() {
+    assert_eq!(short_type_name("u32"), "u32");
+    assert_eq!(short_type_name("my_crate::ast::Expr"), "Expr");
+    assert_eq!(
+        short_type_name("alloc::vec::Vec<my_crate::Expr>"),
+        "Vec<Expr>"
+    );
+    assert_eq!(
+        short_type_name("(core::option::Option<a::B>, &dyn c::D)"),
+        "(Option<B>, &dyn D)"
+    );
+}
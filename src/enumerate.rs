@@ -0,0 +1,170 @@
+//! Exhaustive enumeration of small recursive values.
+//!
+//! Random generation may take a long time to stumble on a particular small value, while
+//! "smallcheck-style" testing checks every value up to a bounded depth instead. An [`Enumeration`]
+//! is a finite, lazily produced collection of values, with the same structure as a recursive
+//! strategy: a base enumeration of leaves, and a branch function which is given the enumeration
+//! of the levels below. Each level of recursion only refers to the values of the levels below it,
+//! so [`Enumeration::recursive`] with a depth of `n` produces every value whose nodes of that type
+//! are nested at most `n` deep, each exactly once as long as the base and branch enumerations
+//! don't overlap.
+//!
+//! Enumerations are defined separately from strategies. A strategy can only sample values, so
+//! the factories registered in a [`StrategySet`](crate::StrategySet) can't be enumerated, and the
+//! base and branch definitions have to be written again in terms of enumerations. There is also no
+//! counterpart of `StrategySet`: for mutually recursive types, pick one type to recurse on, and
+//! build the enumerations of the others from the enumeration passed to its branch function.
+//!
+//! Collections are bounded with [`vec`], which takes an inclusive range of lengths. The number of
+//! values grows very quickly with the depth and collection sizes, so enumerations are only
+//! practical for small bounds.
+//!
+//! # Examples
+//!
+//! ```
+//! use proptest_recurse::enumerate::{vec, Enumeration};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Tree {
+//!     Leaf(bool),
+//!     Node(Vec<Tree>),
+//! }
+//!
+//! let trees = Enumeration::new([false, true])
+//!     .map(Tree::Leaf)
+//!     .recursive(2, |trees| vec(trees, 0..=2).map(Tree::Node));
+//! // Two leaves, plus nodes with up to two children of depth at most one, of which there are 9.
+//! assert_eq!(trees.iter().count(), 2 + 1 + 9 + 9 * 9);
+//! ```
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::type_name;
+use core::fmt;
+use core::iter;
+use core::ops::RangeInclusive;
+
+type Values<T> = Arc<dyn Fn() -> Box<dyn Iterator<Item = T>> + Send + Sync>;
+
+/// A finite collection of values which can be iterated any number of times. This type is cheap to
+/// clone.
+pub struct Enumeration<T> {
+    values: Values<T>,
+}
+
+impl<T> Clone for Enumeration<T> {
+    fn clone(&self) -> Self {
+        Enumeration {
+            values: Arc::clone(&self.values),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Enumeration<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Enumeration")
+            .field(&type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T: Clone + Send + Sync + 'static> Enumeration<T> {
+    /// Returns an enumeration of the given values.
+    pub fn new<I: IntoIterator<Item = T>>(values: I) -> Self {
+        let values: Arc<[T]> = values.into_iter().collect();
+        Enumeration::from_fn(move || {
+            let values = Arc::clone(&values);
+            (0..values.len()).map(move |index| values[index].clone())
+        })
+    }
+
+    /// Returns an enumeration of a single value.
+    pub fn just(value: T) -> Self {
+        Enumeration::from_fn(move || iter::once(value.clone()))
+    }
+
+    /// Returns an enumeration with no values.
+    pub fn empty() -> Self {
+        Enumeration::from_fn(iter::empty)
+    }
+
+    fn from_fn<I, F>(values: F) -> Self
+    where
+        I: Iterator<Item = T> + 'static,
+        F: Fn() -> I + Send + Sync + 'static,
+    {
+        Enumeration {
+            values: Arc::new(move || Box::new(values())),
+        }
+    }
+
+    /// Returns an iterator over the values.
+    pub fn iter(&self) -> impl Iterator<Item = T> {
+        (self.values)()
+    }
+
+    /// Applies `f` to each value.
+    pub fn map<U, F>(self, f: F) -> Enumeration<U>
+    where
+        U: Clone + Send + Sync + 'static,
+        F: Fn(T) -> U + Send + Sync + 'static,
+    {
+        let f = Arc::new(f);
+        Enumeration::from_fn(move || {
+            let f = Arc::clone(&f);
+            self.iter().map(move |value| f(value))
+        })
+    }
+
+    /// Returns the values of this enumeration followed by those of `other`.
+    pub fn union(self, other: Self) -> Self {
+        Enumeration::from_fn(move || self.iter().chain(other.iter()))
+    }
+
+    /// Returns every pair of a value from this enumeration and a value from `other`.
+    pub fn zip<U>(self, other: Enumeration<U>) -> Enumeration<(T, U)>
+    where
+        U: Clone + Send + Sync + 'static,
+    {
+        Enumeration::from_fn(move || {
+            let other = other.clone();
+            self.iter()
+                .flat_map(move |first| other.iter().map(move |second| (first.clone(), second)))
+        })
+    }
+
+    /// Returns the values of a recursive type up to `depth` levels deep, mirroring
+    /// [`prop_mutually_recursive`](crate::StrategyExt::prop_mutually_recursive).
+    ///
+    /// This enumeration provides the leaves. At each level, `branch` is called with the values of
+    /// the levels below, and returns the values which recurse at that level.
+    pub fn recursive<F>(self, depth: u32, branch: F) -> Self
+    where
+        F: Fn(Self) -> Self,
+    {
+        (0..depth).fold(self.clone(), |values, _| self.clone().union(branch(values)))
+    }
+}
+
+/// Returns every vector with a length in `size` whose elements are values of `element`.
+pub fn vec<T>(element: Enumeration<T>, size: RangeInclusive<usize>) -> Enumeration<Vec<T>>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    size.fold(Enumeration::empty(), |values, len| {
+        values.union(vec_of_len(element.clone(), len))
+    })
+}
+
+fn vec_of_len<T>(element: Enumeration<T>, len: usize) -> Enumeration<Vec<T>>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    (0..len).fold(Enumeration::just(Vec::new()), |values, _| {
+        values.zip(element.clone()).map(|(mut values, value)| {
+            values.push(value);
+            values
+        })
+    })
+}
@@ -0,0 +1,197 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+use proptest::strategy::{NewTree, Strategy, ValueTree};
+use proptest::test_runner::{Reason, TestRunner};
+
+type Predicate<T> = Arc<dyn Fn(&T) -> bool + Send + Sync>;
+
+/// A predicate applied to every node generated by a recursive strategy, set with
+/// [`Recursive::prop_filter_subtree`](crate::Recursive::prop_filter_subtree).
+pub(crate) struct SubtreeFilter<T> {
+    predicate: Predicate<T>,
+    whence: Reason,
+    retries: u32,
+    budget: Arc<Budget>,
+}
+
+/// The retries left for the value being generated, shared by all of its nodes.
+#[derive(Debug)]
+struct Budget {
+    remaining: AtomicU32,
+    /// Set when a node gives up because no retries are left, so that the rejection it returns can
+    /// be told apart from rejections by other strategies as it is passed up to the outermost node.
+    exhausted: AtomicBool,
+}
+
+impl Budget {
+    fn new(retries: u32) -> Self {
+        Budget {
+            remaining: AtomicU32::new(retries),
+            exhausted: AtomicBool::new(false),
+        }
+    }
+
+    /// Uses up one retry, returning false if none are left.
+    fn take(&self) -> bool {
+        self.remaining
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |remaining| {
+                remaining.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    fn reset(&self, retries: u32) {
+        self.remaining.store(retries, Ordering::Relaxed);
+        self.exhausted.store(false, Ordering::Relaxed);
+    }
+}
+
+impl<T: fmt::Debug> SubtreeFilter<T> {
+    pub(crate) fn new<F>(whence: Reason, retries: u32, predicate: F) -> Self
+    where
+        F: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        SubtreeFilter {
+            predicate: Arc::new(predicate),
+            whence,
+            retries,
+            budget: Arc::new(Budget::new(retries)),
+        }
+    }
+
+    /// Returns a copy of this filter with a budget of its own, to be shared by the nodes of a
+    /// single value.
+    pub(crate) fn for_value(&self) -> Self {
+        SubtreeFilter {
+            budget: Arc::new(Budget::new(self.retries)),
+            ..self.clone()
+        }
+    }
+
+    /// Wraps the strategy for a node. A node which fails the predicate is retried while the
+    /// budget shared by the whole value allows. Once it is used up, the generation of every node
+    /// fails up to the outermost one, which counts a local rejection and starts again with a full
+    /// budget.
+    pub(crate) fn wrap<S>(&self, inner: S, outermost: bool) -> Filtered<S>
+    where
+        S: Strategy<Value = T>,
+    {
+        Filtered {
+            inner,
+            filter: self.clone(),
+            outermost,
+        }
+    }
+}
+
+impl<T> Clone for SubtreeFilter<T> {
+    fn clone(&self) -> Self {
+        SubtreeFilter {
+            predicate: Arc::clone(&self.predicate),
+            whence: self.whence.clone(),
+            retries: self.retries,
+            budget: Arc::clone(&self.budget),
+        }
+    }
+}
+
+impl<T> fmt::Debug for SubtreeFilter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SubtreeFilter")
+            .field("whence", &self.whence)
+            .field("retries", &self.retries)
+            .finish()
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Filtered<S: Strategy> {
+    inner: S,
+    filter: SubtreeFilter<S::Value>,
+    outermost: bool,
+}
+
+impl<S: Strategy> Strategy for Filtered<S>
+where
+    S::Tree: 'static,
+{
+    type Tree = Box<dyn ValueTree<Value = S::Value>>;
+    type Value = S::Value;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let budget = &self.filter.budget;
+        loop {
+            match self.inner.new_tree(runner) {
+                Ok(tree) if (self.filter.predicate)(&tree.current()) => {
+                    return Ok(Box::new(FilteredTree {
+                        tree,
+                        predicate: Arc::clone(&self.filter.predicate),
+                    }))
+                }
+                Ok(_) => (),
+                // A nested node ran out of retries, so give up on this node too.
+                Err(reason) if budget.exhausted.load(Ordering::Relaxed) => {
+                    if !self.outermost {
+                        return Err(reason);
+                    }
+                }
+                Err(reason) => return Err(reason),
+            }
+
+            if !budget.take() {
+                if !self.outermost {
+                    budget.exhausted.store(true, Ordering::Relaxed);
+                    return Err(self.filter.whence.clone());
+                }
+                runner.reject_local(self.filter.whence.clone())?;
+                budget.reset(self.filter.retries);
+            }
+        }
+    }
+}
+
+struct FilteredTree<T: ValueTree> {
+    tree: T,
+    predicate: Predicate<T::Value>,
+}
+
+impl<T: ValueTree> FilteredTree<T> {
+    /// Undoes shrinking steps until the value passes the predicate again. The initial value always
+    /// passes, so this terminates.
+    fn ensure_acceptable(&mut self) {
+        while !(self.predicate)(&self.tree.current()) {
+            if !self.tree.complicate() {
+                panic!("unable to complicate filtered subtree back into an acceptable value");
+            }
+        }
+    }
+}
+
+impl<T: ValueTree> ValueTree for FilteredTree<T> {
+    type Value = T::Value;
+
+    fn current(&self) -> T::Value {
+        self.tree.current()
+    }
+
+    fn simplify(&mut self) -> bool {
+        if self.tree.simplify() {
+            self.ensure_acceptable();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn complicate(&mut self) -> bool {
+        if self.tree.complicate() {
+            self.ensure_acceptable();
+            true
+        } else {
+            false
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Support for driving strategies from a fuzzer. Requires the `fuzz` feature.
+//!
+//! Rather than sampling values from a random number generator, the functions in this module use
+//! the bytes provided by a fuzzer as the source of randomness, so the same recursive strategies
+//! can be used for both property tests and fuzz targets. Every choice made while generating a
+//! value consumes input bytes, which lets coverage-guided fuzzers explore the structure of
+//! generated values.
+//!
+//! # Examples
+//!
+//! With `cargo-fuzz`, the raw input can be used directly, as the body of
+//! `fuzz_target!(|data: &[u8]| { ... })`:
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! # use proptest::strategy::SBoxedStrategy;
+//! # use proptest_recurse::{StrategyExt, StrategySet};
+//! #
+//! # #[derive(Clone, Debug)]
+//! # enum Expr {
+//! #     Lit(u8),
+//! #     Neg(Box<Expr>),
+//! # }
+//! #
+//! # fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+//! #     any::<u8>()
+//! #         .prop_map(Expr::Lit)
+//! #         .prop_mutually_recursive(4, 16, 1, set, |set| {
+//! #             set.get(arb_expr).prop_map(|e| Expr::Neg(Box::new(e))).sboxed()
+//! #         })
+//! # }
+//! #
+//! # fn check(_: &Expr) {}
+//! #
+//! fn fuzz(data: &[u8]) {
+//!     if let Some(expr) = proptest_recurse::fuzz::generate(&arb_expr(&mut Default::default()), data) {
+//!         check(&expr);
+//!     }
+//! }
+//! # fuzz(&[3, 1, 4, 1, 5, 9, 2, 6]);
+//! ```
+//!
+//! When combining with the `arbitrary` crate, use [`arbitrary`] to take the bytes for the value
+//! from an `Unstructured`, leaving the rest for other fields:
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! # use proptest::strategy::SBoxedStrategy;
+//! use arbitrary::{Arbitrary, Unstructured};
+//! use proptest_recurse::{StrategyExt, StrategySet};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Expr {
+//!     Lit(u8),
+//!     Neg(Box<Expr>),
+//! }
+//!
+//! fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+//!     any::<u8>()
+//!         .prop_map(Expr::Lit)
+//!         .prop_mutually_recursive(4, 16, 1, set, |set| {
+//!             set.get(arb_expr).prop_map(|e| Expr::Neg(Box::new(e))).sboxed()
+//!         })
+//! }
+//!
+//! #[derive(Debug)]
+//! struct Input {
+//!     name: String,
+//!     expr: Expr,
+//! }
+//!
+//! impl<'a> Arbitrary<'a> for Input {
+//!     fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+//!         let expr = proptest_recurse::fuzz::arbitrary(&arb_expr(&mut Default::default()), u)?;
+//!         let name = String::arbitrary(u)?;
+//!         Ok(Input { name, expr })
+//!     }
+//! }
+//!
+//! let mut u = Unstructured::new(&[3, 1, 4, 1, 5, 9, 2, 6]);
+//! let _ = Input::arbitrary(&mut u).unwrap();
+//! ```
+
+use arbitrary::Unstructured;
+use proptest::strategy::{Strategy, ValueTree};
+use proptest::test_runner::{Config, RngAlgorithm, TestRng, TestRunner};
+
+/// Returns a test runner whose random number generator returns the bytes of `data`, followed by
+/// zeros once `data` is exhausted.
+pub fn runner(data: &[u8]) -> TestRunner {
+    TestRunner::new_with_rng(
+        Config::default(),
+        TestRng::from_seed(RngAlgorithm::PassThrough, data),
+    )
+}
+
+/// Generates a value from `strategy` using `data` as the source of randomness. The same input
+/// always produces the same value.
+///
+/// Returns `None` if the strategy rejected too many values, for example due to filters.
+pub fn generate<S: Strategy>(strategy: &S, data: &[u8]) -> Option<S::Value> {
+    strategy
+        .new_tree(&mut runner(data))
+        .ok()
+        .map(|tree| tree.current())
+}
+
+/// Generates a value from `strategy`, using bytes taken from `u` as the source of randomness.
+///
+/// The number of bytes used is itself read from `u`, as `arbitrary` does for collections, so
+/// other values can be read from `u` afterwards. The same input always produces the same value.
+///
+/// Returns [`arbitrary::Error::IncorrectFormat`] if the strategy rejected too many values, for
+/// example due to filters.
+pub fn arbitrary<S: Strategy>(strategy: &S, u: &mut Unstructured) -> arbitrary::Result<S::Value> {
+    let len = u.arbitrary_len::<u8>()?;
+    let data = u.bytes(len)?;
+    generate(strategy, data).ok_or(arbitrary::Error::IncorrectFormat)
+}
@@ -0,0 +1,113 @@
+use alloc::boxed::Box;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Bound, RangeBounds};
+
+use proptest::strategy::{NewTree, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+/// Restricts a branch alternative to the levels of recursion where it is legal, for use with
+/// [`StrategySet::gated_oneof`](crate::StrategySet::gated_oneof).
+///
+/// Levels are numbered from the outermost inwards, so a node generated at level `n` has at most
+/// `n` ancestors of the same type, and the remaining depth is the number of levels below it. By
+/// default, every level is allowed.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_recurse::DepthGate;
+///
+/// // Only at the root.
+/// let module = DepthGate::root();
+/// // Only where there are at least two levels of recursion left.
+/// let lambda = DepthGate::any().remaining(2..);
+/// # let _ = (module, lambda);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DepthGate {
+    levels: (u32, u32),
+    remaining: (u32, u32),
+}
+
+impl DepthGate {
+    /// Returns a gate allowing every level.
+    pub fn any() -> Self {
+        DepthGate {
+            levels: (0, u32::MAX),
+            remaining: (0, u32::MAX),
+        }
+    }
+
+    /// Returns a gate allowing only the outermost level, so the alternative is never nested
+    /// inside another value of the same type.
+    pub fn root() -> Self {
+        DepthGate::any().levels(0..=0)
+    }
+
+    /// Restricts the gate to the given range of levels.
+    pub fn levels<R: RangeBounds<u32>>(mut self, range: R) -> Self {
+        self.levels = bounds(range);
+        self
+    }
+
+    /// Restricts the gate to levels with the given range of remaining depth.
+    pub fn remaining<R: RangeBounds<u32>>(mut self, range: R) -> Self {
+        self.remaining = bounds(range);
+        self
+    }
+
+    pub(crate) fn allows(&self, level: u32, depth: u32) -> bool {
+        let remaining = depth.saturating_sub(level);
+        (self.levels.0..=self.levels.1).contains(&level)
+            && (self.remaining.0..=self.remaining.1).contains(&remaining)
+    }
+}
+
+impl Default for DepthGate {
+    fn default() -> Self {
+        DepthGate::any()
+    }
+}
+
+fn bounds<R: RangeBounds<u32>>(range: R) -> (u32, u32) {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start,
+        Bound::Excluded(&start) => start.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end,
+        // An empty range, such as `0..0`, allows no levels.
+        Bound::Excluded(&0) => return (1, 0),
+        Bound::Excluded(&end) => end - 1,
+        Bound::Unbounded => u32::MAX,
+    };
+    (start, end)
+}
+
+/// Strategy returned by [`StrategySet::gated_oneof`](crate::StrategySet::gated_oneof) when no
+/// alternatives are allowed. The level it is returned for is never recursed into, so it is never
+/// used to generate values.
+pub(crate) struct Excluded<T>(PhantomData<fn() -> T>);
+
+impl<T> Excluded<T> {
+    pub(crate) fn new() -> Self {
+        Excluded(PhantomData)
+    }
+}
+
+impl<T> fmt::Debug for Excluded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Excluded")
+    }
+}
+
+impl<T: fmt::Debug> Strategy for Excluded<T> {
+    type Tree = Box<dyn ValueTree<Value = T>>;
+    type Value = T;
+
+    fn new_tree(&self, _: &mut TestRunner) -> NewTree<Self> {
+        Err("no alternatives are allowed at this level".into())
+    }
+}
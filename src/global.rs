@@ -0,0 +1,175 @@
+//! A process-wide strategy set.
+//!
+//! Attribute-based test frameworks such as
+//! [`test-strategy`](https://crates.io/crates/test-strategy) make it awkward to thread a
+//! `StrategySet` through every strategy. The functions in this module use a single, lazily
+//! populated set shared by the whole process instead, so strategies are only built once.
+//!
+//! # Examples
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! # use proptest::strategy::SBoxedStrategy;
+//! # use proptest_recurse::{StrategyExt, StrategySet};
+//! use test_strategy::{proptest, Arbitrary};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Expr {
+//!     Lit(i32),
+//!     Neg(Box<Expr>),
+//! }
+//!
+//! impl Expr {
+//!     fn eval(&self) -> i32 {
+//!         match self {
+//!             Expr::Lit(value) => *value,
+//!             Expr::Neg(expr) => expr.eval().wrapping_neg(),
+//!         }
+//!     }
+//! }
+//!
+//! fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+//!     any::<i32>().prop_map(Expr::Lit).prop_mutually_recursive(4, 16, 1, set, |set| {
+//!         set.get(arb_expr).prop_map(|expr| Expr::Neg(Box::new(expr))).sboxed()
+//!     })
+//! }
+//!
+//! #[proptest]
+//! fn evaluates(#[strategy(proptest_recurse::global::of(arb_expr))] expr: Expr) {
+//!     expr.eval();
+//! }
+//!
+//! #[derive(Debug, Arbitrary)]
+//! struct Assignment {
+//!     name: String,
+//!     #[strategy(proptest_recurse::global::of(arb_expr))]
+//!     value: Expr,
+//! }
+//! # let _ = any::<Assignment>();
+//! ```
+//!
+//! Factories can also be registered by type with [`register_global!`](crate::register_global),
+//! so that strategies defined in different modules can find each other through
+//! [`StrategySet::get_registered`] without naming each other's factories.
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! # use proptest::strategy::{Just, SBoxedStrategy};
+//! use proptest_recurse::{global_set, register_global, StrategyExt, StrategySet};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Expr {
+//!     Lit(i32),
+//!     Block(Vec<Stmt>),
+//! }
+//!
+//! #[derive(Clone, Debug)]
+//! enum Stmt {
+//!     Expr(Expr),
+//! }
+//!
+//! fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+//!     any::<i32>().prop_map(Expr::Lit).prop_mutually_recursive(3, 16, 2, set, |set| {
+//!         proptest::collection::vec(set.get_registered::<Stmt>(), 0..2)
+//!             .prop_map(Expr::Block)
+//!             .sboxed()
+//!     })
+//! }
+//!
+//! fn arb_stmt(set: &mut StrategySet) -> SBoxedStrategy<Stmt> {
+//!     set.get_registered::<Expr>().prop_map(Stmt::Expr).sboxed()
+//! }
+//!
+//! register_global!(arb_expr, arb_stmt);
+//!
+//! let expr = global_set().get_registered::<Expr>();
+//! # let _ = expr;
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+use std::vec::Vec;
+
+use proptest::strategy::SBoxedStrategy;
+
+use crate::StrategySet;
+
+static SET: Mutex<Option<StrategySet>> = Mutex::new(None);
+
+static REGISTRY: Mutex<BTreeMap<TypeId, Registration>> = Mutex::new(BTreeMap::new());
+
+/// The factory registered for a type.
+#[derive(Clone)]
+struct Registration {
+    /// The `Factory<T>` for the type.
+    factory: Arc<dyn Any + Send + Sync>,
+    /// Adds the strategy for the type to the global set.
+    populate: fn(),
+}
+
+type Factory<T> = fn(&mut StrategySet) -> SBoxedStrategy<T>;
+
+/// Returns a clone of the global set, including strategies for every type with a registered
+/// factory.
+pub fn set() -> StrategySet {
+    let registrations: Vec<Registration> = REGISTRY.lock().unwrap().values().cloned().collect();
+    for registration in registrations {
+        (registration.populate)();
+    }
+    snapshot()
+}
+
+fn snapshot() -> StrategySet {
+    SET.lock().unwrap().clone().unwrap_or_default()
+}
+
+/// Registers `factory` as the factory for `T`, replacing any factory previously registered for
+/// it. Usually called through [`register_global!`](crate::register_global).
+///
+/// Registering is idempotent, so it is fine to do it at the start of every test. The strategy
+/// for `T` is built the first time it is needed, after which the global set keeps it even if a
+/// different factory is registered.
+pub fn register<T>(factory: Factory<T>)
+where
+    T: Any,
+{
+    REGISTRY.lock().unwrap().insert(
+        TypeId::of::<T>(),
+        Registration {
+            factory: Arc::new(factory),
+            populate: populate::<T>,
+        },
+    );
+}
+
+/// Returns the factory registered for `T`, if any.
+pub(crate) fn factory<T: Any>() -> Option<Factory<T>> {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .get(&TypeId::of::<T>())
+        .and_then(|registration| registration.factory.downcast_ref::<Factory<T>>())
+        .copied()
+}
+
+fn populate<T: Any>() {
+    if let Some(factory) = factory::<T>() {
+        let _ = of(factory);
+    }
+}
+
+/// Returns the strategy for `T` from the global set. If it does not exist, it is created using
+/// `factory` and added to the global set.
+pub fn of<T, F>(factory: F) -> SBoxedStrategy<T>
+where
+    T: Any,
+    F: FnOnce(&mut StrategySet) -> SBoxedStrategy<T>,
+{
+    // The factory may itself use the global set, so it must be called without holding the lock.
+    let mut set = snapshot();
+    let strategy = set.get(factory);
+    let mut global = SET.lock().unwrap();
+    global.get_or_insert_with(StrategySet::default).merge(set);
+    strategy
+}
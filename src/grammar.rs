@@ -0,0 +1,257 @@
+//! A small grammar DSL for describing families of recursive types.
+//!
+//! Each type is a nonterminal described by a [`Rule`], consisting of weighted terminal
+//! (non-recursive) alternatives and weighted productions which may refer to other nonterminals.
+//! A [`Grammar`] collects the rules and compiles them into a [`StrategySet`].
+//!
+//! # Examples
+//!
+//! ```
+//! # use proptest::collection::vec;
+//! # use proptest::prelude::*;
+//! # use proptest::strategy::Just;
+//! use proptest_recurse::grammar::{Grammar, Rule};
+//!
+//! #[derive(Clone, Debug)]
+//! enum First {
+//!     Zero,
+//!     Second(Vec<Second>),
+//! }
+//!
+//! #[derive(Clone, Debug)]
+//! enum Second {
+//!     Zero,
+//!     First(Box<First>),
+//! }
+//!
+//! let grammar = Grammar::new()
+//!     .rule(
+//!         Rule::new(5, 32, 8)
+//!             .terminal(1, Just(First::Zero))
+//!             .production(1, |g| vec(g.get::<Second>(), 0..8).prop_map(First::Second)),
+//!     )
+//!     .rule(
+//!         Rule::new(3, 32, 1)
+//!             .terminal(1, Just(Second::Zero))
+//!             .production(1, |g| g.get::<First>().prop_map(|f| Second::First(Box::new(f)))),
+//!     );
+//!
+//! let mut set = grammar.compile();
+//! let first = grammar.get::<First>(&mut set);
+//! # let _ = first;
+//! ```
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::{type_name, Any, TypeId};
+use core::fmt;
+
+use proptest::strategy::{SBoxedStrategy, Strategy, Union};
+
+use crate::map::Map;
+use crate::{StrategyExt, StrategySet};
+
+type Production<T> = Arc<dyn Fn(&mut Derivation) -> SBoxedStrategy<T> + Send + Sync>;
+
+/// The alternatives for a single nonterminal type `T`.
+pub struct Rule<T> {
+    depth: u32,
+    desired_size: u32,
+    expected_branch_size: u32,
+    terminals: Vec<(u32, SBoxedStrategy<T>)>,
+    productions: Vec<(u32, Production<T>)>,
+}
+
+impl<T> fmt::Debug for Rule<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Rule")
+            .field("type", &type_name::<T>())
+            .field("depth", &self.depth)
+            .field("desired_size", &self.desired_size)
+            .field("expected_branch_size", &self.expected_branch_size)
+            .field("terminals", &self.terminals.len())
+            .field("productions", &self.productions.len())
+            .finish()
+    }
+}
+
+impl<T: fmt::Debug + 'static> Rule<T> {
+    /// Creates a rule with no alternatives. The parameters have the same meaning as for
+    /// [`prop_mutually_recursive`](crate::StrategyExt::prop_mutually_recursive).
+    pub fn new(depth: u32, desired_size: u32, expected_branch_size: u32) -> Self {
+        Rule {
+            depth,
+            desired_size,
+            expected_branch_size,
+            terminals: Vec::new(),
+            productions: Vec::new(),
+        }
+    }
+
+    /// Adds a non-recursive alternative with the given weight.
+    pub fn terminal<S>(mut self, weight: u32, strategy: S) -> Self
+    where
+        S: Strategy<Value = T> + Send + Sync + 'static,
+    {
+        self.terminals.push((weight, strategy.sboxed()));
+        self
+    }
+
+    /// Adds a recursive alternative with the given weight. The function may refer to other
+    /// nonterminals, including `T` itself, through the [`Derivation`] argument.
+    pub fn production<S, F>(mut self, weight: u32, f: F) -> Self
+    where
+        S: Strategy<Value = T> + Send + Sync + 'static,
+        F: Fn(&mut Derivation) -> S + Send + Sync + 'static,
+    {
+        self.productions
+            .push((weight, Arc::new(move |derivation| f(derivation).sboxed())));
+        self
+    }
+}
+
+struct Entry {
+    rule: Arc<dyn Any + Send + Sync>,
+    register: fn(&Grammar, &mut StrategySet),
+}
+
+/// A collection of [`Rule`]s, one per type. This type is cheap to clone.
+#[derive(Clone, Default)]
+pub struct Grammar {
+    rules: Map<TypeId, Arc<Entry>>,
+}
+
+impl fmt::Debug for Grammar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Grammar")
+            .field("rules", &self.rules.len())
+            .finish()
+    }
+}
+
+impl Grammar {
+    /// Creates an empty grammar.
+    pub fn new() -> Self {
+        Grammar::default()
+    }
+
+    /// Adds the rule for `T`, replacing any existing rule for that type.
+    pub fn rule<T>(mut self, rule: Rule<T>) -> Self
+    where
+        T: fmt::Debug + Send + Sync + 'static,
+    {
+        self.rules.insert(
+            TypeId::of::<T>(),
+            Arc::new(Entry {
+                rule: Arc::new(rule),
+                register: register::<T>,
+            }),
+        );
+        self
+    }
+
+    /// Returns a strategy for `T`, creating it from the rule for `T` if `set` does not already
+    /// contain one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grammar has no rule for `T`, or that rule has no terminal alternatives.
+    pub fn get<T>(&self, set: &mut StrategySet) -> SBoxedStrategy<T>
+    where
+        T: fmt::Debug + Send + Sync + 'static,
+    {
+        set.get::<T, _>(|set| self.build(set))
+    }
+
+    /// Returns a `StrategySet` containing a strategy for every rule in the grammar.
+    pub fn compile(&self) -> StrategySet {
+        let mut set = StrategySet::default();
+        for entry in self.rules.values() {
+            (entry.register)(self, &mut set);
+        }
+        set
+    }
+
+    fn build<T>(&self, set: &mut StrategySet) -> SBoxedStrategy<T>
+    where
+        T: fmt::Debug + Send + Sync + 'static,
+    {
+        let rule = self
+            .rules
+            .get(&TypeId::of::<T>())
+            .and_then(|entry| Arc::clone(&entry.rule).downcast::<Rule<T>>().ok())
+            .unwrap_or_else(|| panic!("grammar has no rule for `{}`", type_name::<T>()));
+        assert!(
+            !rule.terminals.is_empty(),
+            "rule for `{}` has no terminal alternatives",
+            type_name::<T>()
+        );
+
+        let base = Union::new_weighted(rule.terminals.clone());
+        if rule.productions.is_empty() {
+            return base.sboxed();
+        }
+
+        let grammar = self.clone();
+        base.prop_mutually_recursive(
+            rule.depth,
+            rule.desired_size,
+            rule.expected_branch_size,
+            set,
+            move |set| {
+                let mut derivation = Derivation {
+                    set,
+                    grammar: &grammar,
+                };
+                let alternatives = rule
+                    .productions
+                    .iter()
+                    .map(|(weight, production)| (*weight, production(&mut derivation)))
+                    .collect();
+                Union::new_weighted(alternatives).sboxed()
+            },
+        )
+    }
+}
+
+fn register<T>(grammar: &Grammar, set: &mut StrategySet)
+where
+    T: fmt::Debug + Send + Sync + 'static,
+{
+    let _ = grammar.get::<T>(set);
+}
+
+/// Provides access to the strategies for nonterminals while building a production.
+pub struct Derivation<'a> {
+    set: &'a mut StrategySet,
+    grammar: &'a Grammar,
+}
+
+impl fmt::Debug for Derivation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Derivation")
+            .field("set", &self.set)
+            .field("grammar", &self.grammar)
+            .finish()
+    }
+}
+
+impl Derivation<'_> {
+    /// Returns a strategy for the nonterminal `T`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grammar has no rule for `T` and no strategy for `T` was registered in the
+    /// underlying set.
+    pub fn get<T>(&mut self) -> SBoxedStrategy<T>
+    where
+        T: fmt::Debug + Send + Sync + 'static,
+    {
+        self.grammar.get::<T>(self.set)
+    }
+
+    /// Returns the underlying set, for use with hand-written strategy factories.
+    pub fn set(&mut self) -> &mut StrategySet {
+        self.set
+    }
+}
@@ -0,0 +1,114 @@
+#[cfg(feature = "std")]
+use alloc::string::String;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+#[cfg(feature = "std")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "std")]
+use crate::activity::Activity;
+use crate::RecursiveParams;
+
+/// The limits of a recursive strategy whose value is currently being generated, set with
+/// [`RecursiveParams::max_nodes`] and [`RecursiveParams::timeout`].
+#[cfg(feature = "std")]
+struct Guard {
+    /// Describes the strategy for panic messages.
+    description: String,
+    nodes: u64,
+    max_nodes: Option<u64>,
+    start: Instant,
+    timeout: Option<Duration>,
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// The guards of the values currently being generated, outermost first.
+    static GUARDS: RefCell<Vec<Guard>> = const { RefCell::new(Vec::new()) };
+}
+
+#[cfg(feature = "std")]
+static GUARDED: Activity = Activity::new();
+
+/// Pops the innermost guard when dropped, so that a panic raised while generating doesn't leave
+/// it in place for the next test run on this thread.
+#[cfg(feature = "std")]
+struct PopOnDrop;
+
+#[cfg(feature = "std")]
+impl Drop for PopOnDrop {
+    fn drop(&mut self) {
+        GUARDS.with(|guards| guards.borrow_mut().pop());
+    }
+}
+
+/// Generates a value of the type `type_name` with `f`, enforcing the limits in `params` on the
+/// nodes built while it runs.
+#[cfg(feature = "std")]
+pub(crate) fn guarded<R>(
+    type_name: &'static str,
+    params: &RecursiveParams,
+    f: impl FnOnce() -> R,
+) -> R {
+    if params.max_nodes.is_none() && params.timeout.is_none() {
+        return f();
+    }
+
+    let _active = GUARDED.enter();
+    GUARDS.with(|guards| {
+        guards.borrow_mut().push(Guard {
+            description: crate::entry::describe(type_name, params),
+            nodes: 0,
+            max_nodes: params.max_nodes,
+            start: Instant::now(),
+            timeout: params.timeout,
+        })
+    });
+    let _pop = PopOnDrop;
+    f()
+}
+
+/// Records that a node is being built, panicking if this exceeds the limits of any value being
+/// generated.
+#[cfg(feature = "std")]
+pub(crate) fn node() {
+    if !GUARDED.is_active() {
+        return;
+    }
+
+    let exceeded = GUARDS.with(|guards| {
+        let mut guards = guards.borrow_mut();
+        guards.iter_mut().find_map(|guard| {
+            guard.nodes += 1;
+            match (guard.max_nodes, guard.timeout) {
+                (Some(max_nodes), _) if guard.nodes > max_nodes => Some(format!(
+                    "generating a value of `{}` built more than {} nodes",
+                    guard.description, max_nodes
+                )),
+                (_, Some(timeout)) if guard.start.elapsed() > timeout => Some(format!(
+                    "generating a value of `{}` took longer than {:?}",
+                    guard.description, timeout
+                )),
+                _ => None,
+            }
+        })
+    });
+    if let Some(message) = exceeded {
+        panic!(
+            "{}; check the parameters of its recursive strategy and the sizes of any collections \
+             in its branch function",
+            message
+        );
+    }
+}
+
+// Without `std` there is nowhere to track the nodes built, so the limits are not enforced.
+#[cfg(not(feature = "std"))]
+pub(crate) fn guarded<R>(_: &'static str, _: &RecursiveParams, f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn node() {}
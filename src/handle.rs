@@ -0,0 +1,42 @@
+use core::fmt;
+
+use proptest::strategy::SBoxedStrategy;
+
+/// The strategy for `T` in a [`StrategySet`](crate::StrategySet), resolved once by
+/// [`StrategySet::handle`](crate::StrategySet::handle).
+///
+/// Retrieving a strategy from a set looks up its type and downcasts it on every call. A handle
+/// does this once up front, so branch closures which refer to the same strategy many times can
+/// use [`strategy`](TypedHandle::strategy) instead, which cannot fail and only increments a
+/// reference count. Like the strategies it is created from, a handle is not affected by later
+/// changes to the set.
+pub struct TypedHandle<T> {
+    strategy: SBoxedStrategy<T>,
+}
+
+impl<T> TypedHandle<T> {
+    pub(crate) fn new(strategy: SBoxedStrategy<T>) -> Self {
+        TypedHandle { strategy }
+    }
+
+    /// Returns the resolved strategy.
+    pub fn strategy(&self) -> SBoxedStrategy<T> {
+        self.strategy.clone()
+    }
+}
+
+impl<T> Clone for TypedHandle<T> {
+    fn clone(&self) -> Self {
+        TypedHandle {
+            strategy: self.strategy.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for TypedHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TypedHandle")
+            .field(&core::any::type_name::<T>())
+            .finish()
+    }
+}
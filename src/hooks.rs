@@ -0,0 +1,184 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::{self, Any, TypeId};
+use core::fmt;
+
+use proptest::strategy::{SBoxedStrategy, Strategy};
+
+use crate::entry::Entry;
+use crate::map::Map;
+
+type Hook<T> = Arc<dyn Fn(SBoxedStrategy<T>) -> SBoxedStrategy<T> + Send + Sync>;
+
+/// [`Hooks::apply_all`] for a particular type, stored by entries whose value type is only known to
+/// be `Any`.
+pub(crate) type ApplyAll<T> = fn(&Hooks, SBoxedStrategy<T>) -> SBoxedStrategy<T>;
+
+/// Transforms applied to strategies when they are retrieved from a set.
+#[derive(Clone, Default)]
+pub(crate) struct Hooks {
+    by_type: Map<TypeId, Arc<dyn Any + Send + Sync>>,
+    all: Vec<Hook<AnyValue>>,
+}
+
+impl Hooks {
+    /// Adds a transform for strategies of `T`, to be applied after any existing ones.
+    pub(crate) fn insert<T, F>(&mut self, hook: F)
+    where
+        T: Any,
+        F: Fn(SBoxedStrategy<T>) -> SBoxedStrategy<T> + Send + Sync + 'static,
+    {
+        let hook: Hook<T> = match self.get::<T>() {
+            Some(existing) => Arc::new(move |strategy| hook(existing(strategy))),
+            None => Arc::new(hook),
+        };
+        self.by_type.insert(TypeId::of::<T>(), Arc::new(hook));
+    }
+
+    /// Adds a transform for strategies of every type, to be applied after any existing ones.
+    pub(crate) fn insert_all<F>(&mut self, hook: F)
+    where
+        F: Fn(AnyStrategy) -> AnyStrategy + Send + Sync + 'static,
+    {
+        self.all.push(Arc::new(hook));
+    }
+
+    /// Applies the transforms for `T` to `strategy`, followed by the transforms for all types.
+    pub(crate) fn apply<T>(&self, strategy: SBoxedStrategy<T>) -> SBoxedStrategy<T>
+    where
+        T: Any + fmt::Debug,
+    {
+        self.apply_all(self.apply_typed(strategy))
+    }
+
+    /// Applies the transforms for `T` to `strategy`, which was retrieved from `entry`, followed by
+    /// the transforms for all types if `entry` knows how to apply them.
+    pub(crate) fn apply_entry<T: Any>(
+        &self,
+        strategy: SBoxedStrategy<T>,
+        entry: &Entry,
+    ) -> SBoxedStrategy<T> {
+        let strategy = self.apply_typed(strategy);
+        match entry.apply_all::<T>() {
+            Some(apply_all) => apply_all(self, strategy),
+            None => strategy,
+        }
+    }
+
+    /// Applies the transforms for `T` to `strategy`.
+    fn apply_typed<T: Any>(&self, strategy: SBoxedStrategy<T>) -> SBoxedStrategy<T> {
+        match self.get::<T>() {
+            Some(hook) => hook(strategy),
+            None => strategy,
+        }
+    }
+
+    /// Applies the transforms for all types to `strategy`. Since their values are erased to
+    /// [`AnyValue`], this requires the `Debug` implementation of `T`.
+    pub(crate) fn apply_all<T>(&self, strategy: SBoxedStrategy<T>) -> SBoxedStrategy<T>
+    where
+        T: Any + fmt::Debug,
+    {
+        if self.all.is_empty() {
+            return strategy;
+        }
+
+        let erased = self.all.iter().fold(
+            strategy.prop_map(AnyValue::new).sboxed(),
+            |strategy, hook| hook(strategy),
+        );
+        erased
+            .prop_map(|value| {
+                value.downcast().unwrap_or_else(|value| {
+                    panic!(
+                        "transform passed to `map_all` changed the value type from `{}` to `{}`",
+                        any::type_name::<T>(),
+                        value.type_name()
+                    )
+                })
+            })
+            .sboxed()
+    }
+
+    fn get<T: Any>(&self) -> Option<Hook<T>> {
+        self.by_type
+            .get(&TypeId::of::<T>())
+            .and_then(|hook| hook.downcast_ref::<Hook<T>>())
+            .cloned()
+    }
+}
+
+impl fmt::Debug for Hooks {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Hooks")
+            .field("by_type", &self.by_type)
+            .field("all", &self.all.len())
+            .finish()
+    }
+}
+
+/// A strategy whose value type is erased, as passed to the transforms registered with
+/// [`StrategySet::map_all`](crate::StrategySet::map_all).
+pub type AnyStrategy = SBoxedStrategy<AnyValue>;
+
+/// A value of any type generated by an [`AnyStrategy`].
+pub struct AnyValue {
+    value: Box<dyn DynValue>,
+    type_name: &'static str,
+}
+
+trait DynValue: fmt::Debug {
+    fn as_any(&self) -> &dyn Any;
+    fn into_any(self: Box<Self>) -> Box<dyn Any>;
+}
+
+impl<T: Any + fmt::Debug> DynValue for T {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+}
+
+impl AnyValue {
+    /// Wraps `value`, erasing its type.
+    pub fn new<T: Any + fmt::Debug>(value: T) -> Self {
+        AnyValue {
+            value: Box::new(value),
+            type_name: any::type_name::<T>(),
+        }
+    }
+
+    /// Returns the name of the type of the value.
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+
+    /// Returns `true` if the value has type `T`.
+    pub fn is<T: Any>(&self) -> bool {
+        (*self.value).as_any().is::<T>()
+    }
+
+    /// Returns a reference to the value if it has type `T`.
+    pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+        (*self.value).as_any().downcast_ref()
+    }
+
+    /// Returns the value if it has type `T`, or `self` otherwise.
+    pub fn downcast<T: Any>(self) -> Result<T, Self> {
+        if self.is::<T>() {
+            Ok(*self.value.into_any().downcast().unwrap())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+impl fmt::Debug for AnyValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
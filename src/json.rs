@@ -0,0 +1,64 @@
+//! Strategies for arbitrary JSON documents. Requires the `json` feature.
+//!
+//! [`arb_json`] is a strategy factory for `serde_json::Value`, so it can be registered in a
+//! [`StrategySet`] alongside other types, or used on its own. [`arb_json_with`] allows the size of
+//! documents to be controlled.
+//!
+//! # Examples
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! use proptest_recurse::json::{arb_json, arb_json_with};
+//! use proptest_recurse::{RecursiveParams, StrategySet};
+//!
+//! proptest! {
+//!     fn round_trip(json in arb_json(&mut StrategySet::default())) {
+//!         let text = serde_json::to_string(&json).unwrap();
+//!         prop_assert!(serde_json::from_str::<serde_json::Value>(&text).is_ok());
+//!     }
+//! }
+//! # round_trip();
+//!
+//! let large = arb_json_with(RecursiveParams::new(8, 256, 4), &mut StrategySet::default());
+//! # let _ = large;
+//! ```
+
+use proptest::collection::{btree_map, vec};
+use proptest::prelude::*;
+use proptest::strategy::SBoxedStrategy;
+use serde_json::{Number, Value};
+
+use crate::{RecursiveParams, StrategyExt, StrategySet};
+
+/// Returns a strategy for JSON documents nested at most 4 levels deep.
+///
+/// Strings and object keys are short and may contain any characters. Numbers are integers or
+/// finite floats.
+pub fn arb_json(set: &mut StrategySet) -> SBoxedStrategy<Value> {
+    arb_json_with(RecursiveParams::new(4, 64, 4), set)
+}
+
+/// Like [`arb_json`], but with the given parameters for the nesting of arrays and objects.
+pub fn arb_json_with(params: RecursiveParams, set: &mut StrategySet) -> SBoxedStrategy<Value> {
+    let number = prop_oneof![
+        any::<i64>().prop_map(Number::from),
+        any::<u64>().prop_map(Number::from),
+        (prop::num::f64::NORMAL | prop::num::f64::ZERO)
+            .prop_map(|value| Number::from_f64(value).expect("value is finite")),
+    ];
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        number.prop_map(Value::Number),
+        ".{0,8}".prop_map(Value::String),
+    ];
+    leaf.prop_mutually_recursive_with(params, set, |set| {
+        let json = set.get::<Value, _>(arb_json);
+        prop_oneof![
+            vec(json.clone(), 0..4).prop_map(Value::Array),
+            btree_map(".{0,4}", json, 0..4)
+                .prop_map(|entries| Value::Object(entries.into_iter().collect())),
+        ]
+        .sboxed()
+    })
+}
@@ -1,5 +1,6 @@
 #![deny(missing_docs)]
 #![deny(missing_debug_implementations)]
+#![no_std]
 
 //! This crate provides a helper struct for defining mutually recursive strategies with
 //! [`proptest`](https://crates.io/crates/proptest). The `prop_recursive` combinator is useful for
@@ -107,38 +108,809 @@
 //!     fn create(_ in arb_first(&mut Default::default())) {}
 //! }
 //! ```
+//!
+//! # `no_std` support
+//!
+//! The `std` feature is enabled by default. Without it, this crate only depends on `alloc`, and
+//! [`StrategySet`], [`Recursive`] and the other core strategies are still available. Strategies
+//! shared between threads are then locked with a spin lock, so the `spin` feature must be enabled
+//! instead:
+//!
+//! ```toml
+//! proptest-recurse = { version = "0.5", default-features = false, features = ["spin"] }
+//! ```
+//!
+//! Features which rely on thread-local or process-wide state are only available with `std`: the
+//! [`global`], [`replay`], [`testutil`] and [`zipper`] modules, [`with_depth`], [`diagnosed`] and
+//! [`mutated_pair`]. Without `std`, generating, shrinking and dropping a value also recurses on
+//! the stack once per level, so very deep values may need a thread with a larger stack.
+
+#[macro_use]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
+pub mod collection;
+pub mod context;
+pub mod cycle;
+pub mod enumerate;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+#[cfg(feature = "std")]
+pub mod global;
+pub mod grammar;
+#[cfg(feature = "json")]
+pub mod json;
+#[cfg(feature = "rayon")]
+pub mod parallel;
+#[cfg(feature = "std")]
+pub mod replay;
+pub mod size;
+pub mod state_machine;
+#[cfg(feature = "std")]
+pub mod testutil;
+pub mod tree;
+#[cfg(feature = "std")]
+pub mod zipper;
+
+#[cfg(feature = "std")]
+mod activity;
+mod balance;
+mod correlated;
+mod depth;
+mod entry;
+mod filter;
+mod gate;
+mod guard;
+mod handle;
+mod hooks;
+mod macros;
+mod map;
+mod mutate;
+mod observer;
+mod params;
 mod recursive;
+mod shape;
+mod shared;
+mod simplify;
+mod stack;
+mod sync;
+mod tower;
 
-use std::any::{Any, TypeId};
-use std::sync::Arc;
+/// Without `std`, values can't be recorded or replayed, so recursive strategies always make their
+/// own decisions.
+#[cfg(not(feature = "std"))]
+mod replay {
+    use proptest::test_runner::{Reason, TestRunner};
 
-use im::HashMap;
-use proptest::strategy::{SBoxedStrategy, Strategy};
+    pub(crate) fn replaying() -> bool {
+        false
+    }
 
-use crate::recursive::Recursive;
+    pub(crate) fn with_runner<R>(
+        runner: &mut TestRunner,
+        f: impl FnOnce(&mut TestRunner) -> R,
+    ) -> R {
+        f(runner)
+    }
+
+    pub(crate) fn decide<F>(
+        runner: &mut TestRunner,
+        choose: F,
+        _: impl FnOnce(usize) -> u32,
+        _: impl FnOnce(u32) -> Option<usize>,
+    ) -> Result<Option<usize>, Reason>
+    where
+        F: FnOnce(&mut TestRunner) -> Result<Option<usize>, Reason>,
+    {
+        choose(runner)
+    }
+}
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::{Any, TypeId};
+use core::convert::Infallible;
+use core::fmt;
+
+use proptest::arbitrary::{any, Arbitrary};
+use proptest::prop_oneof;
+use proptest::strategy::{float_to_weight, SBoxedStrategy, Strategy, Union};
+
+pub use crate::depth::{Diagnosed, WithDepth};
+pub use crate::entry::TypeMismatch;
+pub use crate::gate::DepthGate;
+#[cfg(feature = "std")]
+pub use crate::global::set as global_set;
+pub use crate::handle::TypedHandle;
+pub use crate::hooks::{AnyStrategy, AnyValue};
+pub use crate::observer::Observer;
+pub use crate::params::RecursiveParams;
+pub use crate::recursive::Recursive;
+pub use crate::shape::Shape;
+pub use crate::simplify::Simplify;
+pub use crate::size::TreeSize;
+
+#[cfg(feature = "derive")]
+pub use proptest_recurse_derive::TreeSize;
+
+use crate::balance::Balanced;
+use crate::correlated::{Budget, Correlated};
+#[cfg(feature = "std")]
+use crate::depth::Measure;
+use crate::entry::{Entry, Report};
+use crate::gate::Excluded;
+use crate::hooks::Hooks;
+#[cfg(feature = "std")]
+use crate::mutate::MutatedPair;
+use crate::shared::{Memo, Scoped, Shared};
+
+use crate::map::Map;
+
+#[doc(hidden)]
+pub extern crate alloc as __alloc;
+#[doc(hidden)]
+pub use proptest as __proptest;
 
 /// A collection of strategies that depend on each other. This type is cheap to clone.
-#[derive(Clone, Default, Debug)]
+///
+/// The `Debug` output lists the types with registered strategies, along with the parameters of
+/// those created by recursive strategies, for example `StrategySet { Expr (depth=5, size=32),
+/// Stmt, .. }`.
+#[derive(Clone, Default)]
 pub struct StrategySet {
-    inner: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
+    inner: Map<TypeId, Entry>,
+    /// The strategies for minimal values created by [`get_weighted`](StrategySet::get_weighted).
+    minimal: Map<TypeId, Entry>,
+    back_refs: Map<TypeId, u32>,
+    levels: Map<TypeId, (u32, u32)>,
+    /// The shape of the recursive strategy for each type in `levels`.
+    shapes: Map<TypeId, Shape>,
+    hooks: Hooks,
+    budget: Budget,
+    max_depth: Option<u32>,
+    /// Set by [`gated_oneof`](StrategySet::gated_oneof) when it excludes every alternative, so
+    /// that the current level is not recursed into.
+    pruned: bool,
+    /// Where recursive strategies created from this set are reported, if it was passed to a
+    /// running factory.
+    report: Option<Arc<Report>>,
 }
 
 impl StrategySet {
     /// Returns a strategy for `T`. If a strategy does not exist, it is created and inserted using
     /// `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the registered strategy for `T` has a different value type. See
+    /// [`try_get`](StrategySet::try_get) for a non-panicking version.
     pub fn get<T, F>(&mut self, f: F) -> SBoxedStrategy<T>
     where
         T: Any,
         F: FnOnce(&mut Self) -> SBoxedStrategy<T>,
     {
-        let mut this = self.clone();
+        self.try_get::<T, TypeMismatch, _>(|set| Ok(f(set)))
+            .unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Like [`get`](StrategySet::get), but for factories which can fail, such as those which
+    /// depend on external resources. If `f` returns an error, it is returned from this method and
+    /// nothing is inserted.
+    ///
+    /// Instead of panicking if the registered strategy for `T` has a different value type, a
+    /// [`TypeMismatch`] error is returned. Use `TypeMismatch` as the error type for factories
+    /// which cannot otherwise fail.
+    pub fn try_get<T, E, F>(&mut self, f: F) -> Result<SBoxedStrategy<T>, E>
+    where
+        T: Any,
+        E: From<TypeMismatch>,
+        F: FnOnce(&mut Self) -> Result<SBoxedStrategy<T>, E>,
+    {
+        if let Some(entry) = self.inner.get(&TypeId::of::<T>()) {
+            return Ok(self.hooks.apply_entry(entry.downcast()?, entry));
+        }
+
+        let (strategy, entry) = Entry::from_factory(self, f)?;
+        let strategy = self.hooks.apply_entry(strategy, &entry);
+        self.inner.insert(TypeId::of::<T>(), entry);
+        Ok(strategy)
+    }
+
+    /// Returns the strategy for `T`, creating it with the factory registered for `T` with
+    /// [`register_global!`] if necessary. See the [`global`] module for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no strategy for `T` in this set and no factory has been registered for
+    /// it.
+    #[cfg(feature = "std")]
+    pub fn get_registered<T>(&mut self) -> SBoxedStrategy<T>
+    where
+        T: Any,
+    {
+        self.get(|set| match global::factory::<T>() {
+            Some(factory) => factory(set),
+            None => panic!(
+                "no factory registered for `{}`",
+                core::any::type_name::<T>()
+            ),
+        })
+    }
+
+    /// Returns a strategy for the wrapper type `C`, built from the strategy for its element type.
+    /// The element strategy is looked up in this set, and created using `f` if necessary.
+    ///
+    /// This avoids writing a separate factory for each of `Vec<T>`, `Option<T>`, `Box<T>` and so
+    /// on. The wrapper strategy is built on every call rather than stored in the set, so that it
+    /// always uses the element strategy for the current level of recursion.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use proptest::prelude::*;
+    /// # use proptest::strategy::{Just, SBoxedStrategy};
+    /// use proptest_recurse::{StrategyExt, StrategySet};
+    ///
+    /// #[derive(Clone, Debug)]
+    /// enum Expr {
+    ///     Lit(i32),
+    ///     Call(Box<Expr>, Vec<Expr>),
+    ///     Block(Option<Box<Expr>>),
+    /// }
+    ///
+    /// fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    ///     any::<i32>().prop_map(Expr::Lit).prop_mutually_recursive(4, 32, 4, set, |set| {
+    ///         prop_oneof![
+    ///             (
+    ///                 set.get_family::<Box<Expr>, _>(arb_expr),
+    ///                 set.get_family::<Vec<Expr>, _>(arb_expr),
+    ///             )
+    ///                 .prop_map(|(callee, args)| Expr::Call(callee, args)),
+    ///             set.get_family::<Option<Box<Expr>>, _>(|set| {
+    ///                 set.get_family::<Box<Expr>, _>(arb_expr)
+    ///             })
+    ///             .prop_map(Expr::Block),
+    ///         ]
+    ///         .sboxed()
+    ///     })
+    /// }
+    /// # let _ = arb_expr(&mut StrategySet::default());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the registered strategy for the element type has a different value type.
+    pub fn get_family<C, F>(&mut self, f: F) -> SBoxedStrategy<C>
+    where
+        C: collection::Family,
+        F: FnOnce(&mut Self) -> SBoxedStrategy<C::Element>,
+    {
+        let element = self.get(f);
+        C::strategy(self, element)
+    }
+
+    /// Like [`get`](StrategySet::get), but only recurses into `T` with probability `weight`.
+    ///
+    /// Otherwise, a minimal value of `T` is generated, using the strategy created by `f` with
+    /// every recursive strategy limited to a depth of zero. This allows references to different
+    /// types to recurse with different probabilities, such as making a `First` containing a
+    /// non-trivial `Second` rarer than a `First` containing another `First`.
+    ///
+    /// Both strategies are cached in this set, so as with `get`, `f` is only called the first time
+    /// `T` is retrieved.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `weight` is not between 0 and 1.
+    pub fn get_weighted<T, F>(&mut self, weight: f64, f: F) -> SBoxedStrategy<T>
+    where
+        T: Any + fmt::Debug,
+        F: Fn(&mut Self) -> SBoxedStrategy<T>,
+    {
+        assert!(
+            (0.0..=1.0).contains(&weight),
+            "invalid edge weight: {}",
+            weight
+        );
+
+        let full = self.get(&f);
+        if weight >= 1.0 {
+            return full;
+        }
+
+        let leaf = match self.minimal.get(&TypeId::of::<T>()) {
+            Some(entry) => entry.expect(),
+            None => {
+                let mut leaf_set = self.clone();
+                leaf_set.inner.clear();
+                leaf_set.max_depth = Some(0);
+                let (leaf, entry) =
+                    Entry::from_factory(&leaf_set, |set| Ok::<_, Infallible>(f(set)))
+                        .unwrap_or_else(|never| match never {});
+                self.minimal.insert(TypeId::of::<T>(), entry);
+                leaf
+            }
+        };
+        let leaf = self.hooks.apply(leaf);
+        if weight <= 0.0 {
+            return leaf;
+        }
+
+        let (weight_full, weight_leaf) = float_to_weight(weight);
+        prop_oneof![weight_leaf => leaf, weight_full => full].sboxed()
+    }
+
+    /// Returns the strategy for `T` if one has been registered, without creating one.
+    ///
+    /// This is useful for optional dependencies, where a factory uses the strategy provided by its
+    /// caller if there is one, and otherwise falls back to a simpler strategy.
+    pub fn get_opt<T: Any>(&self) -> Option<SBoxedStrategy<T>> {
         self.inner
-            .entry(TypeId::of::<T>())
-            .or_insert_with(|| Arc::new(f(&mut this)))
-            .downcast_ref::<SBoxedStrategy<T>>()
-            .unwrap()
-            .clone()
+            .get(&TypeId::of::<T>())
+            .map(|entry| self.hooks.apply_entry(entry.expect(), entry))
+    }
+
+    /// Returns the strategy for only the non-recursive values of `T`, if `T` is registered and its
+    /// leaves are known.
+    ///
+    /// For a type registered with a recursive strategy, this is the base strategy passed to
+    /// [`prop_mutually_recursive`](StrategyExt::prop_mutually_recursive) or one of its variants.
+    /// Any transforms registered with [`map_type`](StrategySet::map_type) are applied.
+    ///
+    /// Returns `None` for a type whose factory didn't create a recursive strategy for it, since
+    /// its strategy may still recurse by other means, such as `prop_recursive`. Callers which
+    /// know the registered strategy only generates leaves can fall back to
+    /// [`get`](StrategySet::get) instead.
+    ///
+    /// Within branch functions, the leaves of the type being recursed on are always known.
+    /// Elsewhere, leaves are only known if the factory for the type created its recursive strategy
+    /// from the set it was passed.
+    ///
+    /// # Examples
+    ///
+    /// Leaves are useful for positions which must not recurse, such as the operands of literal
+    /// forms:
+    ///
+    /// ```
+    /// # use proptest::collection::vec;
+    /// # use proptest::prelude::*;
+    /// # use proptest::strategy::{Just, SBoxedStrategy};
+    /// use proptest_recurse::{StrategyExt, StrategySet};
+    ///
+    /// #[derive(Clone, Debug)]
+    /// enum Expr {
+    ///     Num(i32),
+    ///     Add(Box<Expr>, Box<Expr>),
+    ///     List(Vec<Expr>),
+    /// }
+    ///
+    /// fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    ///     any::<i32>()
+    ///         .prop_map(Expr::Num)
+    ///         .prop_mutually_recursive(4, 32, 2, set, |set| {
+    ///             let expr = set.get(arb_expr);
+    ///             // List literals only contain numbers.
+    ///             let leaf = set.get_leaf::<Expr>().unwrap();
+    ///             prop_oneof![
+    ///                 (expr.clone(), expr).prop_map(|(l, r)| Expr::Add(Box::new(l), Box::new(r))),
+    ///                 vec(leaf, 0..4).prop_map(Expr::List),
+    ///             ]
+    ///             .sboxed()
+    ///         })
+    /// }
+    /// # let _ = arb_expr(&mut StrategySet::default());
+    /// ```
+    pub fn get_leaf<T: Any + fmt::Debug>(&self) -> Option<SBoxedStrategy<T>> {
+        self.inner
+            .get(&TypeId::of::<T>())
+            .and_then(Entry::leaf)
+            .map(|leaf| self.hooks.apply(leaf))
+    }
+
+    /// Like [`prop_mutually_recursive_with`](StrategyExt::prop_mutually_recursive_with), but the
+    /// base strategy is created by calling `base` with a copy of this set. This allows the leaves
+    /// of one type to be built from the leaves of others, using
+    /// [`get_leaf`](StrategySet::get_leaf).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use proptest::prelude::*;
+    /// # use proptest::strategy::SBoxedStrategy;
+    /// use proptest_recurse::{RecursiveParams, StrategyExt, StrategySet};
+    ///
+    /// #[derive(Clone, Debug)]
+    /// enum Pattern {
+    ///     Wildcard,
+    ///     Tuple(Vec<Pattern>),
+    /// }
+    ///
+    /// #[derive(Clone, Debug)]
+    /// enum Expr {
+    ///     Unit,
+    ///     Match(Box<Expr>, Pattern),
+    /// }
+    ///
+    /// fn arb_pattern(set: &mut StrategySet) -> SBoxedStrategy<Pattern> {
+    ///     Just(Pattern::Wildcard).prop_mutually_recursive(3, 16, 2, set, |set| {
+    ///         prop::collection::vec(set.get(arb_pattern), 0..3)
+    ///             .prop_map(Pattern::Tuple)
+    ///             .sboxed()
+    ///     })
+    /// }
+    ///
+    /// fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    ///     set.recursive_with_base(
+    ///         RecursiveParams::new(4, 32, 1),
+    ///         |set| {
+    ///             // Leaves of `Expr` only contain leaves of `Pattern`.
+    ///             set.get(arb_pattern);
+    ///             let pattern = set
+    ///                 .get_leaf::<Pattern>()
+    ///                 .unwrap_or_else(|| Just(Pattern::Wildcard).sboxed());
+    ///             prop_oneof![
+    ///                 Just(Expr::Unit),
+    ///                 pattern.prop_map(|pattern| Expr::Match(Box::new(Expr::Unit), pattern)),
+    ///             ]
+    ///             .sboxed()
+    ///         },
+    ///         |set| {
+    ///             (set.get(arb_expr), set.get(arb_pattern))
+    ///                 .prop_map(|(expr, pattern)| Expr::Match(Box::new(expr), pattern))
+    ///                 .sboxed()
+    ///         },
+    ///     )
+    /// }
+    /// # let _ = arb_expr(&mut StrategySet::default());
+    /// ```
+    pub fn recursive_with_base<T, B, F>(
+        &self,
+        params: RecursiveParams,
+        base: B,
+        branch: F,
+    ) -> SBoxedStrategy<T>
+    where
+        T: Any + fmt::Debug,
+        B: FnOnce(&mut StrategySet) -> SBoxedStrategy<T>,
+        F: Fn(&mut StrategySet) -> SBoxedStrategy<T> + Send + Sync + 'static,
+    {
+        base(&mut self.clone()).prop_mutually_recursive_with(params, self, branch)
+    }
+
+    /// Resolves the registered strategy for `T`, returning a handle to it.
+    ///
+    /// This is an alternative to [`get`](StrategySet::get) for branch closures which retrieve the
+    /// same strategy repeatedly: the lookup, downcast and any transforms registered with
+    /// [`map_type`](StrategySet::map_type) happen once here, rather than on every retrieval.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use proptest::prelude::*;
+    /// # use proptest::strategy::SBoxedStrategy;
+    /// use proptest_recurse::{StrategyExt, StrategySet};
+    ///
+    /// #[derive(Clone, Debug)]
+    /// enum Expr {
+    ///     Lit(i32),
+    ///     Add(Box<Expr>, Box<Expr>),
+    ///     If(Box<Expr>, Box<Expr>, Box<Expr>),
+    /// }
+    ///
+    /// fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    ///     any::<i32>().prop_map(Expr::Lit).prop_mutually_recursive(4, 32, 4, set, |set| {
+    ///         let expr = set.handle::<Expr>();
+    ///         prop_oneof![
+    ///             (expr.strategy(), expr.strategy())
+    ///                 .prop_map(|(l, r)| Expr::Add(Box::new(l), Box::new(r))),
+    ///             (expr.strategy(), expr.strategy(), expr.strategy()).prop_map(|(c, t, e)| {
+    ///                 Expr::If(Box::new(c), Box::new(t), Box::new(e))
+    ///             }),
+    ///         ]
+    ///         .sboxed()
+    ///     })
+    /// }
+    /// # let _ = arb_expr(&mut StrategySet::default());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if no strategy is registered for `T`, or if the registered strategy has a different
+    /// value type.
+    pub fn handle<T: Any + fmt::Debug>(&self) -> TypedHandle<T> {
+        match self.get_opt() {
+            Some(strategy) => TypedHandle::new(strategy),
+            None => panic!(
+                "no strategy registered for `{}`",
+                core::any::type_name::<T>()
+            ),
+        }
+    }
+
+    /// Returns the strategy for `T` if one has been registered, and otherwise `any::<T>()`.
+    ///
+    /// This avoids registering strategies for leaf types such as integers and strings, which
+    /// don't need custom strategies, while still allowing callers to override them. As with
+    /// [`get`](StrategySet::get), any transforms registered for `T` with
+    /// [`map_type`](StrategySet::map_type) are applied. Nothing is inserted into the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use proptest::prelude::*;
+    /// # use proptest::strategy::Just;
+    /// use proptest_recurse::StrategySet;
+    ///
+    /// let mut set = StrategySet::default();
+    /// let any_u8 = set.get_or_any::<u8>();
+    ///
+    /// let _ = set.get::<u8, _>(|_| Just(7).sboxed());
+    /// let seven = set.get_or_any::<u8>();
+    /// # let _ = (any_u8, seven);
+    /// ```
+    pub fn get_or_any<T>(&self) -> SBoxedStrategy<T>
+    where
+        T: Arbitrary + Any,
+        T::Strategy: Send + Sync + 'static,
+    {
+        self.get_opt()
+            .unwrap_or_else(|| self.hooks.apply(any::<T>().sboxed()))
+    }
+
+    /// Removes the strategy for `T` from this set, returning it if it was registered. The returned
+    /// strategy does not have any transforms registered with
+    /// [`map_type`](StrategySet::map_type) applied.
+    ///
+    /// Sets are persistent, so this only affects this set. Clones of the set, and strategies
+    /// created from it, keep using the old strategy. In particular, strategies for other types
+    /// which depend on `T` and were created before it was removed should be removed as well if
+    /// they are to pick up its replacement.
+    pub fn remove<T: Any>(&mut self) -> Option<SBoxedStrategy<T>> {
+        self.minimal.remove(&TypeId::of::<T>());
+        self.inner
+            .remove(&TypeId::of::<T>())
+            .as_ref()
+            .map(Entry::expect)
+    }
+
+    /// Removes all strategies from this set. As with [`remove`](StrategySet::remove), clones of
+    /// the set and strategies already created from it are unaffected.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+        self.minimal.clear();
+    }
+
+    /// Registers a transform which is applied to the strategy for `T` whenever it is retrieved
+    /// from this set, including from inside factories and branch functions. Transforms registered
+    /// for the same type are applied in the order they were registered.
+    ///
+    /// This allows invariants such as normalization or interning to be enforced on every value of
+    /// `T`, including nested ones, without editing each factory. Transforms are only applied to
+    /// strategies retrieved using methods such as [`get`](StrategySet::get), so they should be
+    /// registered before creating any strategies, and the top-level strategy should be retrieved
+    /// from the set rather than by calling its factory directly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use proptest::prelude::*;
+    /// # use proptest::strategy::SBoxedStrategy;
+    /// use proptest_recurse::StrategySet;
+    ///
+    /// let mut set = StrategySet::default();
+    /// set.map_type::<String, _>(|strategy| strategy.prop_map(|s| s.to_lowercase()).sboxed());
+    /// let names = set.get(|_| any::<String>().sboxed());
+    /// # let _ = names;
+    /// ```
+    pub fn map_type<T, F>(&mut self, f: F)
+    where
+        T: Any,
+        F: Fn(SBoxedStrategy<T>) -> SBoxedStrategy<T> + Send + Sync + 'static,
+    {
+        self.hooks.insert(f);
+    }
+
+    /// Makes the strategy for `T` retrieved from this set generate one of `examples` a quarter of
+    /// the time, instead of always generating values from scratch.
+    ///
+    /// Seeding generation with real-world examples helps reach the interesting regions of
+    /// programs such as parsers and optimizers, which synthetic values rarely hit. Since this is
+    /// implemented with [`map_type`](StrategySet::map_type), the same rules apply to when it takes
+    /// effect. In particular, examples are also used for nested values of `T`, so they are spliced
+    /// into generated values as well as being generated whole. Examples shrink towards earlier
+    /// examples, and then to generated values. Calling this method with no examples does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use proptest::prelude::*;
+    /// use proptest_recurse::StrategySet;
+    ///
+    /// let mut set = StrategySet::default();
+    /// set.with_corpus(vec!["SELECT * FROM users".to_owned(), "DELETE FROM users".to_owned()]);
+    /// let queries = set.get(|_| "[A-Z ]{0,20}".sboxed());
+    /// # let _ = queries;
+    /// ```
+    pub fn with_corpus<T, I>(&mut self, examples: I)
+    where
+        T: Any + Clone + fmt::Debug + Send + Sync,
+        I: IntoIterator<Item = T>,
+    {
+        let examples: Arc<Vec<T>> = Arc::new(examples.into_iter().collect());
+        if examples.is_empty() {
+            return;
+        }
+
+        self.map_type::<T, _>(move |strategy| {
+            let examples = Arc::clone(&examples);
+            let example = (0..examples.len()).prop_map(move |index| examples[index].clone());
+            prop_oneof![3 => strategy, 1 => example].sboxed()
+        });
+    }
+
+    /// Registers a transform which is applied to the strategies for every type whenever they are
+    /// retrieved from this set, after any transforms registered with
+    /// [`map_type`](StrategySet::map_type). See `map_type` for details of when transforms are
+    /// applied.
+    ///
+    /// Since the transform is shared between types, it receives an [`AnyStrategy`] whose values
+    /// have their type erased. This is useful for instrumentation, such as logging or counting
+    /// generated values. The transform must not change the type of the values.
+    ///
+    /// Erasing the value type requires its `Debug` implementation, which the set only knows for
+    /// types with a recursive strategy created from the set, or from the set passed to their
+    /// factory. Strategies for other types, such as those returned by factories for leaf types,
+    /// are retrieved without the transform.
+    ///
+    /// # Panics
+    ///
+    /// Generating values from a strategy panics if the transform changed the type of its values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use proptest::prelude::*;
+    /// use proptest_recurse::StrategySet;
+    ///
+    /// let mut set = StrategySet::default();
+    /// set.map_all(|strategy| {
+    ///     strategy
+    ///         .prop_map(|value| {
+    ///             println!("generated {}: {:?}", value.type_name(), value);
+    ///             value
+    ///         })
+    ///         .sboxed()
+    /// });
+    /// ```
+    pub fn map_all<F>(&mut self, f: F)
+    where
+        F: Fn(AnyStrategy) -> AnyStrategy + Send + Sync + 'static,
+    {
+        self.hooks.insert_all(f);
+    }
+
+    /// Returns a strategy for pairs of related values which share a single size budget.
+    ///
+    /// For each generated pair, the budget is split randomly between the two values, and the size
+    /// parameters of every recursive strategy created while calling `first` or `second` are scaled
+    /// down accordingly. This avoids both values maxing out their size at once. Strategies which
+    /// are already registered in this set are reused unchanged. The factories are called at most
+    /// once for each way of splitting the budget.
+    ///
+    /// The two values are generated independently, and never share subtrees. To reuse subtrees
+    /// within each value, create its strategy with
+    /// [`prop_mutually_recursive_shared`](StrategyExt::prop_mutually_recursive_shared).
+    pub fn correlated<A, B, FA, FB>(&self, first: FA, second: FB) -> SBoxedStrategy<(A, B)>
+    where
+        A: fmt::Debug + 'static,
+        B: fmt::Debug + 'static,
+        FA: Fn(&mut Self) -> SBoxedStrategy<A> + Send + Sync + 'static,
+        FB: Fn(&mut Self) -> SBoxedStrategy<B> + Send + Sync + 'static,
+    {
+        Correlated::new(self.clone(), Arc::new(first), Arc::new(second)).sboxed()
+    }
+
+    /// Adds the entries of `other` which are not already in this set.
+    #[cfg(feature = "std")]
+    pub(crate) fn merge(&mut self, other: StrategySet) {
+        self.inner.union(&other.inner);
+    }
+
+    /// Combines weighted branch alternatives for `T`, as with `prop_oneof!`, keeping only those
+    /// whose [`DepthGate`] allows the level currently being generated.
+    ///
+    /// This should be called from the branch function of the recursive strategy for `T`, and
+    /// allows structural rules such as "`Module` only at the root" to be expressed directly. If
+    /// every alternative is excluded at a level, that level is never recursed into, and nodes
+    /// generated there fall through to the next level instead. Elsewhere, every alternative is
+    /// allowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use proptest::prelude::*;
+    /// # use proptest::strategy::SBoxedStrategy;
+    /// use proptest_recurse::{DepthGate, StrategyExt, StrategySet};
+    ///
+    /// #[derive(Clone, Debug)]
+    /// enum Item {
+    ///     Const(i32),
+    ///     Module(Vec<Item>),
+    ///     Lambda(Box<Item>),
+    /// }
+    ///
+    /// fn arb_item(set: &mut StrategySet) -> SBoxedStrategy<Item> {
+    ///     any::<i32>().prop_map(Item::Const).prop_mutually_recursive(4, 32, 4, set, |set| {
+    ///         let item = set.get(arb_item);
+    ///         set.gated_oneof(vec![
+    ///             (
+    ///                 1,
+    ///                 DepthGate::root(),
+    ///                 proptest::collection::vec(item.clone(), 0..4)
+    ///                     .prop_map(Item::Module)
+    ///                     .sboxed(),
+    ///             ),
+    ///             (
+    ///                 1,
+    ///                 DepthGate::any().levels(1..),
+    ///                 item.prop_map(|body| Item::Lambda(Box::new(body))).sboxed(),
+    ///             ),
+    ///         ])
+    ///     })
+    /// }
+    /// # let _ = arb_item(&mut StrategySet::default());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the allowed alternatives has a weight of zero.
+    pub fn gated_oneof<T>(
+        &mut self,
+        alternatives: Vec<(u32, DepthGate, SBoxedStrategy<T>)>,
+    ) -> SBoxedStrategy<T>
+    where
+        T: Any + fmt::Debug,
+    {
+        let level = self.levels.get(&TypeId::of::<T>()).copied();
+        let allowed: Vec<(u32, SBoxedStrategy<T>)> = alternatives
+            .into_iter()
+            .filter(|(_, gate, _)| level.is_none_or(|(level, depth)| gate.allows(level, depth)))
+            .map(|(weight, _, strategy)| (weight, strategy))
+            .collect();
+        if allowed.is_empty() {
+            self.pruned = true;
+            return Excluded::new().sboxed();
+        }
+        Union::new_weighted(allowed).sboxed()
+    }
+
+    /// Returns a strategy for back-references to ancestors of type `T`, for generating cyclic
+    /// structures. See the [`cycle`] module for details.
+    ///
+    /// Inside the branch function of a recursive strategy for `T`, the generated indices range up
+    /// to the maximum number of ancestors a node at that level can have. Elsewhere, the strategy
+    /// always refers to the innermost ancestor.
+    pub fn back_ref<T: 'static>(&self) -> SBoxedStrategy<cycle::BackRef<T>> {
+        let max = self.back_refs.get(&TypeId::of::<T>()).copied().unwrap_or(0);
+        (0..=max).prop_map(cycle::BackRef::new).sboxed()
+    }
+}
+
+impl fmt::Debug for StrategySet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut entries: Vec<String> = self
+            .inner
+            .values()
+            .map(|entry| format!("{:?}", entry))
+            .collect();
+        entries.sort();
+        f.write_str("StrategySet { ")?;
+        for entry in entries {
+            write!(f, "{}, ", entry)?;
+        }
+        f.write_str(".. }")
     }
 }
 
@@ -159,6 +931,85 @@ pub trait StrategyExt: Strategy {
     where
         Self::Value: Any,
         F: Fn(&mut StrategySet) -> SBoxedStrategy<Self::Value> + Send + Sync + 'static;
+
+    /// Like [`prop_mutually_recursive`](StrategyExt::prop_mutually_recursive), but takes its size
+    /// parameters as a [`RecursiveParams`], allowing alternative generation modes to be selected.
+    fn prop_mutually_recursive_with<F>(
+        self,
+        params: RecursiveParams,
+        set: &StrategySet,
+        recurse: F,
+    ) -> SBoxedStrategy<Self::Value>
+    where
+        Self::Value: Any,
+        F: Fn(&mut StrategySet) -> SBoxedStrategy<Self::Value> + Send + Sync + 'static;
+
+    /// Like [`prop_mutually_recursive_with`](StrategyExt::prop_mutually_recursive_with), but
+    /// nested values of this type are pooled and reused with probability `sharing_probability`
+    /// instead of always being generated from scratch. Values are only reused in positions at
+    /// least as high as the one they were generated for, so the depth limit is still respected.
+    ///
+    /// Reused values are cloned, so to generate values with genuinely shared subtrees (i.e. DAGs)
+    /// the value type should be a smart pointer such as `Arc<T>`. Values are only reused within
+    /// a single generated value, and reused values do not shrink.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sharing_probability` is not between 0 and 1.
+    fn prop_mutually_recursive_shared<F>(
+        self,
+        params: RecursiveParams,
+        sharing_probability: f64,
+        set: &StrategySet,
+        recurse: F,
+    ) -> SBoxedStrategy<Self::Value>
+    where
+        Self::Value: Any + Clone + Send,
+        F: Fn(&mut StrategySet) -> SBoxedStrategy<Self::Value> + Send + Sync + 'static;
+
+    /// Like [`prop_mutually_recursive_with`](StrategyExt::prop_mutually_recursive_with), but small
+    /// subtrees of this type are memoized. Leaves, and nodes generated at levels with at most
+    /// `max_height` levels of recursion below them, are cached and reused with probability
+    /// `reuse_probability` instead of being generated from scratch.
+    ///
+    /// This trades some independence between subtrees for speed, which can make a large
+    /// difference for values with thousands of nodes. Since looking up the cache has a cost of its
+    /// own, memoizing only the smallest subtrees may not pay off. The cache is emptied before each
+    /// value is generated, so subtrees are only reused within a single value, which still only
+    /// depends on the runner's seed. Reused subtrees do not shrink. A `max_height` of 0 only
+    /// memoizes leaves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `reuse_probability` is not between 0 and 1.
+    fn prop_mutually_recursive_memoized<F>(
+        self,
+        params: RecursiveParams,
+        max_height: u32,
+        reuse_probability: f64,
+        set: &StrategySet,
+        recurse: F,
+    ) -> SBoxedStrategy<Self::Value>
+    where
+        Self::Value: Any + Clone + Send,
+        F: Fn(&mut StrategySet) -> SBoxedStrategy<Self::Value> + Send + Sync + 'static;
+
+    /// Like [`prop_mutually_recursive`](StrategyExt::prop_mutually_recursive), but returns the
+    /// concrete [`Recursive`] strategy instead of boxing it. Nested levels are still boxed, but
+    /// callers that don't need to store the result in a `StrategySet` avoid the outermost dynamic
+    /// dispatch.
+    fn prop_mutually_recursive_unboxed<F>(
+        self,
+        depth: u32,
+        desired_size: u32,
+        expected_branch_size: u32,
+        set: &StrategySet,
+        recurse: F,
+    ) -> Recursive<Self, F>
+    where
+        Self: Sized,
+        Self::Value: Any,
+        F: Fn(&mut StrategySet) -> SBoxedStrategy<Self::Value> + Send + Sync + 'static;
 }
 
 impl<T: Strategy + Send + Sync + 'static> StrategyExt for T {
@@ -174,22 +1025,346 @@ impl<T: Strategy + Send + Sync + 'static> StrategyExt for T {
         Self::Value: Any,
         F: Fn(&mut StrategySet) -> SBoxedStrategy<Self::Value> + Send + Sync + 'static,
     {
-        let set = set.inner.clone();
+        self.prop_mutually_recursive_unboxed(depth, desired_size, expected_branch_size, set, branch)
+            .sboxed()
+    }
+
+    fn prop_mutually_recursive_with<F>(
+        self,
+        params: RecursiveParams,
+        set: &StrategySet,
+        branch: F,
+    ) -> SBoxedStrategy<Self::Value>
+    where
+        Self::Value: Any,
+        F: Fn(&mut StrategySet) -> SBoxedStrategy<Self::Value> + Send + Sync + 'static,
+    {
+        Recursive::new(self, params, set, branch).sboxed()
+    }
+
+    fn prop_mutually_recursive_shared<F>(
+        self,
+        params: RecursiveParams,
+        sharing_probability: f64,
+        set: &StrategySet,
+        branch: F,
+    ) -> SBoxedStrategy<Self::Value>
+    where
+        Self::Value: Any + Clone + Send,
+        F: Fn(&mut StrategySet) -> SBoxedStrategy<Self::Value> + Send + Sync + 'static,
+    {
+        let memo = Memo::new("sharing probability", sharing_probability);
+        let pools = Arc::clone(&memo);
+        let strategy = self.prop_mutually_recursive_with(params, set, move |set| {
+            let (level, depth) = set.levels[&TypeId::of::<Self::Value>()];
+            // Nested nodes are generated below this level, so they have at most this many levels
+            // of recursion below them. Pooling them by height ensures a reused value is never
+            // deeper than the position it fills.
+            let height = depth.saturating_sub(level + 1);
+            // Fetch the nested strategy without applying transforms, since they will be applied
+            // when the branch function retrieves it.
+            let entry = &set.inner[&TypeId::of::<Self::Value>()];
+            let nested = Shared::new(entry.expect::<Self::Value>(), memo.pool(height)).sboxed();
+            let entry = entry.with_strategy(nested);
+            set.inner.insert(TypeId::of::<Self::Value>(), entry);
+            branch(set)
+        });
+        Scoped::new(strategy, pools).sboxed()
+    }
+
+    fn prop_mutually_recursive_memoized<F>(
+        self,
+        params: RecursiveParams,
+        max_height: u32,
+        reuse_probability: f64,
+        set: &StrategySet,
+        branch: F,
+    ) -> SBoxedStrategy<Self::Value>
+    where
+        Self::Value: Any + Clone + Send,
+        F: Fn(&mut StrategySet) -> SBoxedStrategy<Self::Value> + Send + Sync + 'static,
+    {
+        let memo = Memo::new("reuse probability", reuse_probability);
+        let pools = Arc::clone(&memo);
+        let base = Shared::new(self.sboxed(), memo.pool(0));
+        let strategy = base.prop_mutually_recursive_with(params, set, move |set| {
+            let (level, depth) = set.levels[&TypeId::of::<Self::Value>()];
+            // Nodes at this level have at most this many levels of recursion below them.
+            let height = depth - level;
+            let strategy = branch(set);
+            if height <= max_height {
+                Shared::new(strategy, memo.pool(height)).sboxed()
+            } else {
+                strategy
+            }
+        });
+        Scoped::new(strategy, pools).sboxed()
+    }
+
+    fn prop_mutually_recursive_unboxed<F>(
+        self,
+        depth: u32,
+        desired_size: u32,
+        expected_branch_size: u32,
+        set: &StrategySet,
+        branch: F,
+    ) -> Recursive<Self, F>
+    where
+        Self::Value: Any,
+        F: Fn(&mut StrategySet) -> SBoxedStrategy<Self::Value> + Send + Sync + 'static,
+    {
         Recursive::new(
-            self.sboxed(),
-            depth,
-            desired_size,
-            expected_branch_size,
-            move |nested| {
-                branch(&mut StrategySet {
-                    inner: set.update(TypeId::of::<Self::Value>(), Arc::new(nested)),
-                })
-            },
+            self,
+            RecursiveParams::new(depth, desired_size, expected_branch_size),
+            set,
+            branch,
         )
-        .sboxed()
     }
 }
 
+/// Returns the strategy for `T` created by `factory` using a new, empty `StrategySet`.
+///
+/// This is intended for use in places which can only contain an expression, such as the
+/// `#[proptest(strategy = "...")]` attribute of `proptest-derive`. To share one set between
+/// several fields, declare it as the parameters of the derived type and use [`from_shared_set`].
+///
+/// # Examples
+///
+/// ```
+/// # use proptest::collection::vec;
+/// # use proptest::prelude::*;
+/// # use proptest::strategy::SBoxedStrategy;
+/// use proptest_derive::Arbitrary;
+/// use proptest_recurse::{from_set, from_shared_set, StrategySet};
+/// # use proptest_recurse::StrategyExt;
+/// #
+/// # #[derive(Clone, Debug)]
+/// # enum Expr {
+/// #     Lit(i32),
+/// #     Block(Vec<Stmt>),
+/// # }
+/// #
+/// # #[derive(Clone, Debug)]
+/// # enum Stmt {
+/// #     Expr(Expr),
+/// # }
+/// #
+/// # fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+/// #     any::<i32>().prop_map(Expr::Lit).prop_mutually_recursive(3, 16, 2, set, |set| {
+/// #         vec(set.get(arb_stmt), 0..3).prop_map(Expr::Block).sboxed()
+/// #     })
+/// # }
+/// #
+/// # fn arb_stmt(set: &mut StrategySet) -> SBoxedStrategy<Stmt> {
+/// #     set.get(arb_expr).prop_map(Stmt::Expr).sboxed()
+/// # }
+///
+/// #[derive(Debug, Arbitrary)]
+/// struct Function {
+///     #[proptest(strategy = "from_set(arb_expr)")]
+///     body: Expr,
+/// }
+///
+/// #[derive(Debug, Arbitrary)]
+/// #[proptest(params(StrategySet))]
+/// struct Module {
+///     #[proptest(strategy = "from_shared_set(&params, arb_expr)")]
+///     init: Expr,
+///     #[proptest(strategy = "vec(from_shared_set(&params, arb_stmt), 0..4)")]
+///     body: Vec<Stmt>,
+/// }
+/// # let _ = (any::<Function>(), any::<Module>());
+/// ```
+pub fn from_set<T, F>(factory: F) -> SBoxedStrategy<T>
+where
+    T: Any,
+    F: FnOnce(&mut StrategySet) -> SBoxedStrategy<T>,
+{
+    StrategySet::default().get(factory)
+}
+
+/// Returns the strategy for `T` registered in `set`, creating it with `factory` if necessary.
+///
+/// Unlike [`StrategySet::get`], this takes the set by shared reference, so it can be used with the
+/// parameters of a type deriving `Arbitrary`. See [`from_set`] for an example.
+pub fn from_shared_set<T, F>(set: &StrategySet, factory: F) -> SBoxedStrategy<T>
+where
+    T: Any,
+    F: FnOnce(&mut StrategySet) -> SBoxedStrategy<T>,
+{
+    set.clone().get(factory)
+}
+
+/// Wraps `strategy` so that values whose cost, as computed by `cost`, exceeds `max_cost` are
+/// discarded and generated again.
+///
+/// This is useful as a safety net for properties which are too slow to run on occasional huge
+/// values. Discarded values count as local rejections, so if too many values are over the bound
+/// the test fails with a "too many local rejects" error rather than looping forever. Shrinking
+/// never produces values over the bound either.
+pub fn size_bounded<S, F>(strategy: S, max_cost: u64, cost: F) -> SBoxedStrategy<S::Value>
+where
+    S: Strategy + Send + Sync + 'static,
+    F: Fn(&S::Value) -> u64 + Send + Sync + 'static,
+{
+    strategy
+        .prop_filter(
+            format!("value exceeds maximum cost of {}", max_cost),
+            move |value| cost(value) <= max_cost,
+        )
+        .sboxed()
+}
+
+/// Wraps `strategy` so that each value is generated together with its recursion depth and node
+/// count, as a [`WithDepth`].
+///
+/// The depth and node count are tracked while the value is built, so no walker over the value's
+/// type is needed. Every node generated by a recursive strategy from this crate is counted,
+/// including nodes of other types in a mutually recursive family. Other strategies contribute
+/// nothing, and neither do subtrees reused by
+/// [`prop_mutually_recursive_shared`](StrategyExt::prop_mutually_recursive_shared) or
+/// [`prop_mutually_recursive_memoized`](StrategyExt::prop_mutually_recursive_memoized).
+///
+/// # Examples
+///
+/// ```
+/// # use proptest::prelude::*;
+/// # use proptest::strategy::ValueTree;
+/// # use proptest::test_runner::TestRunner;
+/// use proptest_recurse::tree::recursive_tree;
+/// use proptest_recurse::{with_depth, RecursiveParams};
+///
+/// let trees = with_depth(recursive_tree(any::<u8>(), 1..4, RecursiveParams::new(4, 32, 2)));
+/// let tree = trees.new_tree(&mut TestRunner::default()).unwrap().current();
+/// assert_eq!(tree.depth, tree.value.depth());
+/// ```
+#[cfg(feature = "std")]
+pub fn with_depth<S>(strategy: S) -> SBoxedStrategy<WithDepth<S::Value>>
+where
+    S: Strategy + Send + Sync + 'static,
+{
+    Measure(strategy).sboxed()
+}
+
+/// Wraps `strategy` so that failure output shows the recursion depth and node count of each value,
+/// as a [`Diagnosed`].
+///
+/// When a property fails, proptest prints the minimal failing value using its `Debug` output.
+/// With this wrapper, that output is followed by the depth and node count of the value, measured
+/// as for [`with_depth`], so it is easy to see whether a counterexample is deep or wide. The
+/// wrapped value dereferences to the original value, so properties usually need no changes.
+///
+/// # Examples
+///
+/// ```
+/// # use proptest::prelude::*;
+/// # use proptest::strategy::SBoxedStrategy;
+/// use proptest_recurse::tree::{recursive_tree, Tree};
+/// use proptest_recurse::{diagnosed, RecursiveParams};
+///
+/// fn arb_tree() -> SBoxedStrategy<Tree<u8>> {
+///     recursive_tree(any::<u8>(), 1..4, RecursiveParams::new(4, 32, 2))
+/// }
+///
+/// proptest! {
+///     # #![proptest_config(ProptestConfig::with_cases(16))]
+///     fn small(tree in diagnosed(arb_tree())) {
+///         // On failure, prints something like
+///         // `minimal failing input: tree = Node([Leaf(0), Leaf(0)]) (depth 1, 3 nodes)`.
+///         prop_assert!(tree.depth() <= 4);
+///     }
+/// }
+/// # small();
+/// ```
+#[cfg(feature = "std")]
+pub fn diagnosed<S>(strategy: S) -> SBoxedStrategy<Diagnosed<S::Value>>
+where
+    S: Strategy + Send + Sync + 'static,
+{
+    Measure(strategy).prop_map(Diagnosed::from).sboxed()
+}
+
+/// Wraps `strategy` so that each value is generated together with a copy in which one randomly
+/// chosen subtree has been regenerated, for use in metamorphic tests.
+///
+/// The subtree is chosen uniformly from all nodes generated by recursive strategies from this
+/// crate, including leaves, and is regenerated from the same level of the same strategy, so the
+/// mutated value respects the same depth limits and uses the same
+/// [`StrategySet`]. The regenerated subtree may happen to equal the original one. If the value
+/// contains no such nodes, both values are the same.
+///
+/// Shrinking the pair shrinks the original value; the mutation is applied to the same node,
+/// counting in the order nodes are built, of each shrunk value.
+///
+/// # Examples
+///
+/// ```
+/// # use proptest::prelude::*;
+/// # use proptest::strategy::ValueTree;
+/// # use proptest::test_runner::TestRunner;
+/// use proptest_recurse::tree::recursive_tree;
+/// use proptest_recurse::{mutated_pair, RecursiveParams};
+///
+/// let pairs = mutated_pair(recursive_tree(any::<u8>(), 1..4, RecursiveParams::new(4, 32, 2)));
+/// let (original, mutated) = pairs.new_tree(&mut TestRunner::default()).unwrap().current();
+/// # let _ = (original, mutated);
+/// ```
+#[cfg(feature = "std")]
+pub fn mutated_pair<S>(strategy: S) -> SBoxedStrategy<(S::Value, S::Value)>
+where
+    S: Strategy + Send + Sync + 'static,
+{
+    MutatedPair(strategy).sboxed()
+}
+
+/// Like `Union::new_weighted`, but adapts the weights as values are generated so that rarely
+/// generated alternatives become more likely.
+///
+/// The strategy counts how often each alternative is generated, and each time it generates a
+/// value, multiplies the weight of alternatives which are underrepresented by 16. An alternative is
+/// underrepresented if it has been generated less often than the average of its share of the
+/// total weight and an equal share of all values. This improves the coverage of wide enums whose
+/// variants have very different weights, or whose weights are hard to tune. The counts are shared
+/// between clones of the strategy, so they accumulate over a whole test run, and include values
+/// generated while shrinking.
+///
+/// # Reproducibility
+///
+/// Since the weights depend on the values generated before, the value generated for a given seed
+/// depends on which values the strategy has already generated. A failing case can therefore not
+/// be reproduced from its seed alone: replaying a seed saved in a regression file, or passed to
+/// `TestRunner::new_with_rng`, generates it with the weights of a fresh strategy instead. Use
+/// `Union::new_weighted` when failures must be reproducible from their seeds.
+///
+/// Note that only the choices made by this strategy are balanced. A variant which is rare because
+/// the strategy itself is rarely used, such as when it is nested behind other low-probability
+/// choices, needs those choices to be balanced too.
+///
+/// # Panics
+///
+/// Panics if `alternatives` is empty or any weight is zero.
+///
+/// # Examples
+///
+/// ```
+/// # use proptest::prelude::*;
+/// # use proptest::strategy::Just;
+/// use proptest_recurse::balanced_oneof;
+///
+/// let ops = balanced_oneof(vec![
+///     (100, Just("add").sboxed()),
+///     (10, Just("mul").sboxed()),
+///     (1, Just("pow").sboxed()),
+/// ]);
+/// # let _ = ops;
+/// ```
+pub fn balanced_oneof<T>(alternatives: Vec<(u32, SBoxedStrategy<T>)>) -> SBoxedStrategy<T>
+where
+    T: fmt::Debug + 'static,
+{
+    Balanced::new(alternatives).sboxed()
+}
+
 #[test]
 fn strategy_set_send_sync() {
     fn send<T: Send>() {}
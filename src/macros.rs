@@ -0,0 +1,327 @@
+/// Defines a family of mutually recursive strategies.
+///
+/// The first item names a function which returns a [`StrategySet`](crate::StrategySet) populated
+/// with every strategy in the family. Each following entry expands to a factory function
+/// `fn name(set: &mut StrategySet) -> SBoxedStrategy<Type>` built with
+/// [`prop_mutually_recursive`](crate::StrategyExt::prop_mutually_recursive). The `branch` expression
+/// is boxed automatically, and `params` are the `depth`, `desired_size` and `expected_branch_size`
+/// arguments, in that order.
+///
+/// # Examples
+///
+/// ```
+/// # use proptest::collection::vec;
+/// # use proptest::prelude::*;
+/// # use proptest::strategy::Just;
+/// use proptest_recurse::strategy_set;
+///
+/// #[derive(Clone, Debug)]
+/// enum First {
+///     Zero,
+///     Second(Vec<Second>),
+/// }
+///
+/// #[derive(Clone, Debug)]
+/// enum Second {
+///     Zero,
+///     First(First),
+/// }
+///
+/// strategy_set! {
+///     fn strategies();
+///
+///     fn arb_first: First => {
+///         base: Just(First::Zero),
+///         branch(set) => vec(set.get::<Second, _>(arb_second), 0..8).prop_map(First::Second),
+///         params: (5, 32, 8),
+///     }
+///
+///     fn arb_second: Second => {
+///         base: Just(Second::Zero),
+///         branch(set) => set.get::<First, _>(arb_first).prop_map(Second::First),
+///         params: (3, 32, 1),
+///     }
+/// }
+///
+/// let mut set = strategies();
+/// let first = arb_first(&mut set);
+/// # let _ = first;
+/// ```
+#[macro_export]
+macro_rules! strategy_set {
+    (
+        $(#[$set_attr:meta])*
+        $set_vis:vis fn $set_name:ident();
+
+        $(
+            $(#[$attr:meta])*
+            $vis:vis fn $name:ident: $ty:ty => {
+                base: $base:expr,
+                branch($set:ident) => $branch:expr,
+                params: ($depth:expr, $desired_size:expr, $expected_branch_size:expr) $(,)?
+            }
+        )*
+    ) => {
+        $(#[$set_attr])*
+        $set_vis fn $set_name() -> $crate::StrategySet {
+            let mut set = $crate::StrategySet::default();
+            $(
+                let _ = set.get::<$ty, _>($name);
+            )*
+            set
+        }
+
+        $(
+            $(#[$attr])*
+            $vis fn $name(
+                set: &mut $crate::StrategySet,
+            ) -> $crate::__proptest::strategy::SBoxedStrategy<$ty> {
+                $crate::StrategyExt::prop_mutually_recursive(
+                    $base,
+                    $depth,
+                    $desired_size,
+                    $expected_branch_size,
+                    set,
+                    |$set: &mut $crate::StrategySet| {
+                        $crate::__proptest::strategy::Strategy::sboxed($branch)
+                    },
+                )
+            }
+        )*
+    };
+}
+
+/// Defines a single recursive strategy factory, in the style of `prop_compose!`.
+///
+/// Expands to a function `fn name(set: &mut StrategySet, args...) -> SBoxedStrategy<Type>` built
+/// with [`prop_mutually_recursive_with`](crate::StrategyExt::prop_mutually_recursive_with). The
+/// `branch` expression is boxed automatically and may refer to the function's arguments, which
+/// are moved into it and so must be `Send + Sync + 'static` if they are used. `params` is either a
+/// [`RecursiveParams`](crate::RecursiveParams) or a tuple of the `depth`, `desired_size` and
+/// `expected_branch_size` arguments, in that order.
+///
+/// Note that a [`StrategySet`](crate::StrategySet) holds one strategy per type, so when a factory
+/// with arguments is called through [`StrategySet::get`](crate::StrategySet::get), the arguments
+/// of the first call are used for every value of that type generated from the set.
+///
+/// # Examples
+///
+/// ```
+/// # use proptest::collection::vec;
+/// # use proptest::prelude::*;
+/// # use proptest::strategy::Just;
+/// use proptest_recurse::{prop_mutual, RecursiveParams, StrategySet};
+///
+/// #[derive(Clone, Debug)]
+/// enum First {
+///     Zero,
+///     Second(Vec<Second>),
+/// }
+///
+/// #[derive(Clone, Debug)]
+/// enum Second {
+///     Zero,
+///     First(First),
+/// }
+///
+/// prop_mutual! {
+///     fn arb_first(max_len: usize) -> First {
+///         base: Just(First::Zero),
+///         branch(set) => vec(set.get::<Second, _>(arb_second), 0..max_len).prop_map(First::Second),
+///         params: (5, 32, 8),
+///     }
+/// }
+///
+/// prop_mutual! {
+///     fn arb_second() -> Second {
+///         base: Just(Second::Zero),
+///         branch(set) => set.get::<First, _>(|set| arb_first(set, 8)).prop_map(Second::First),
+///         params: RecursiveParams::new(3, 32, 1).target_size(1..=32),
+///     }
+/// }
+///
+/// let first = arb_first(&mut StrategySet::default(), 4);
+/// # let _ = first;
+/// ```
+#[macro_export]
+macro_rules! prop_mutual {
+    (
+        $(#[$attr:meta])*
+        $vis:vis fn $name:ident($($arg:ident: $arg_ty:ty),* $(,)?) -> $ty:ty {
+            base: $base:expr,
+            branch($set:ident) => $branch:expr,
+            params: $($params:tt)+
+        }
+    ) => {
+        $(#[$attr])*
+        $vis fn $name(
+            set: &mut $crate::StrategySet,
+            $($arg: $arg_ty),*
+        ) -> $crate::__proptest::strategy::SBoxedStrategy<$ty> {
+            $crate::StrategyExt::prop_mutually_recursive_with(
+                $base,
+                $crate::prop_mutual!(@params $($params)+),
+                set,
+                move |$set: &mut $crate::StrategySet| {
+                    $crate::__proptest::strategy::Strategy::sboxed($branch)
+                },
+            )
+        }
+    };
+    (@params ($depth:expr, $desired_size:expr, $expected_branch_size:expr) $(,)?) => {
+        $crate::RecursiveParams::new($depth, $desired_size, $expected_branch_size)
+    };
+    (@params $params:expr $(,)?) => {
+        $params
+    };
+}
+
+/// Combines strategies for several concrete types into a strategy for a boxed trait object.
+///
+/// Each strategy's values are boxed and coerced to the given trait object type, then combined as
+/// with `prop_oneof!`, optionally with weights. The result is an `SBoxedStrategy`, so it can be
+/// registered in a [`StrategySet`](crate::StrategySet) keyed by the trait object type like any other
+/// strategy.
+///
+/// # Examples
+///
+/// ```
+/// # use std::fmt::Debug;
+/// # use proptest::prelude::*;
+/// # use proptest::strategy::Just;
+/// use proptest_recurse::{dyn_oneof, StrategyExt, StrategySet};
+///
+/// trait Node: Debug {}
+///
+/// #[derive(Clone, Debug)]
+/// struct Lit(i32);
+/// impl Node for Lit {}
+///
+/// #[derive(Debug)]
+/// struct Neg(Box<dyn Node>);
+/// impl Node for Neg {}
+///
+/// fn arb_node(set: &mut StrategySet) -> SBoxedStrategy<Box<dyn Node>> {
+///     dyn_oneof![Box<dyn Node>; any::<i32>().prop_map(Lit)].prop_mutually_recursive(
+///         4,
+///         16,
+///         1,
+///         set,
+///         |set| {
+///             dyn_oneof![Box<dyn Node>;
+///                 3 => any::<i32>().prop_map(Lit),
+///                 1 => set.get::<Box<dyn Node>, _>(arb_node).prop_map(Neg),
+///             ]
+///         },
+///     )
+/// }
+/// # let _ = arb_node(&mut StrategySet::default());
+/// ```
+#[macro_export]
+macro_rules! dyn_oneof {
+    ($ty:ty; $($weight:expr => $strategy:expr),+ $(,)?) => {
+        $crate::__proptest::strategy::Strategy::sboxed(
+            $crate::__proptest::strategy::Union::new_weighted($crate::__alloc::vec![
+                $(
+                    (
+                        $weight,
+                        $crate::__proptest::strategy::Strategy::sboxed(
+                            $crate::__proptest::strategy::Strategy::prop_map(
+                                $strategy,
+                                |value| -> $ty { $crate::__alloc::boxed::Box::new(value) },
+                            ),
+                        ),
+                    ),
+                )+
+            ]),
+        )
+    };
+    ($ty:ty; $($strategy:expr),+ $(,)?) => {
+        $crate::dyn_oneof!($ty; $(1 => $strategy),+)
+    };
+}
+
+/// Builds a strategy for an enum whose variants each wrap a type registered in a
+/// [`StrategySet`](crate::StrategySet).
+///
+/// Each entry names a tuple variant and the factory for its payload type, which is looked up in
+/// the set with [`get`](crate::StrategySet::get) and mapped into the variant. The variants are
+/// combined as with `prop_oneof!`, optionally with weights, and the result is an
+/// `SBoxedStrategy`.
+///
+/// # Examples
+///
+/// ```
+/// # use proptest::prelude::*;
+/// # use proptest::strategy::{Just, SBoxedStrategy};
+/// use proptest_recurse::{recursive_oneof, StrategySet};
+///
+/// #[derive(Clone, Debug)]
+/// struct Expr;
+///
+/// #[derive(Clone, Debug)]
+/// struct Stmt;
+///
+/// #[derive(Clone, Debug)]
+/// enum Node {
+///     Expr(Expr),
+///     Stmt(Stmt),
+/// }
+///
+/// fn arb_expr(_: &mut StrategySet) -> SBoxedStrategy<Expr> {
+///     Just(Expr).sboxed()
+/// }
+///
+/// fn arb_stmt(_: &mut StrategySet) -> SBoxedStrategy<Stmt> {
+///     Just(Stmt).sboxed()
+/// }
+///
+/// fn arb_node(set: &mut StrategySet) -> SBoxedStrategy<Node> {
+///     recursive_oneof![set;
+///         3 => Node::Expr(arb_expr),
+///         1 => Node::Stmt(arb_stmt),
+///     ]
+/// }
+/// # let _ = arb_node(&mut StrategySet::default());
+/// ```
+#[macro_export]
+macro_rules! recursive_oneof {
+    ($set:expr; $($weight:expr => $($variant:ident)::+ ($factory:expr)),+ $(,)?) => {{
+        let set: &mut $crate::StrategySet = $set;
+        $crate::__proptest::strategy::Strategy::sboxed(
+            $crate::__proptest::strategy::Union::new_weighted($crate::__alloc::vec![
+                $(
+                    (
+                        $weight,
+                        $crate::__proptest::strategy::Strategy::sboxed(
+                            $crate::__proptest::strategy::Strategy::prop_map(
+                                set.get($factory),
+                                $($variant)::+,
+                            ),
+                        ),
+                    ),
+                )+
+            ]),
+        )
+    }};
+    ($set:expr; $($($variant:ident)::+ ($factory:expr)),+ $(,)?) => {
+        $crate::recursive_oneof!($set; $(1 => $($variant)::+ ($factory)),+)
+    };
+}
+
+/// Registers factories in the global registry, so that the strategies they create can be found
+/// by type with [`StrategySet::get_registered`](crate::StrategySet::get_registered).
+///
+/// Each argument is a factory function `fn(&mut StrategySet) -> SBoxedStrategy<T>`, and is
+/// registered for its value type `T` with [`global::register`](crate::global::register). Since
+/// registering is idempotent, the macro can be invoked wherever is convenient before the
+/// strategies are used, such as at the start of each test. See the [`global`](crate::global)
+/// module for an example.
+#[macro_export]
+macro_rules! register_global {
+    ($($factory:expr),* $(,)?) => {
+        $(
+            $crate::global::register($factory);
+        )*
+    };
+}
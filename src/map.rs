@@ -0,0 +1,153 @@
+//! A small copy-on-write map used to store the entries of a [`StrategySet`](crate::StrategySet).
+//!
+//! Sets are cloned every time a recursive strategy is created and typically hold a handful of
+//! entries, so the entries are kept in a shared vector sorted by key. Cloning only increments a
+//! reference count, lookups are a binary search over contiguous memory, and the vector is copied
+//! on the first write to a shared map.
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Index;
+
+pub(crate) struct Map<K, V> {
+    entries: Arc<Vec<(K, V)>>,
+}
+
+impl<K: Ord + Clone, V: Clone> Map<K, V> {
+    fn find(&self, key: &K) -> Result<usize, usize> {
+        self.entries.binary_search_by(|(probe, _)| probe.cmp(key))
+    }
+
+    pub(crate) fn get(&self, key: &K) -> Option<&V> {
+        match self.find(key) {
+            Ok(index) => Some(&self.entries[index].1),
+            Err(_) => None,
+        }
+    }
+
+    /// Inserts a value, returning the previous value for `key`, if any.
+    pub(crate) fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.find(&key) {
+            Ok(index) => Some(core::mem::replace(
+                &mut Arc::make_mut(&mut self.entries)[index].1,
+                value,
+            )),
+            Err(index) => {
+                Arc::make_mut(&mut self.entries).insert(index, (key, value));
+                None
+            }
+        }
+    }
+
+    /// Removes the value for `key`. The entries are not copied if `key` is not present.
+    pub(crate) fn remove(&mut self, key: &K) -> Option<V> {
+        let index = self.find(key).ok()?;
+        Some(Arc::make_mut(&mut self.entries).remove(index).1)
+    }
+
+    /// Removes the values for which `keep` returns false. The entries are not copied if every
+    /// value is kept.
+    pub(crate) fn retain(&mut self, mut keep: impl FnMut(&V) -> bool) {
+        if !self.entries.iter().all(|(_, value)| keep(value)) {
+            Arc::make_mut(&mut self.entries).retain(|(_, value)| keep(value));
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        if !self.entries.is_empty() {
+            self.entries = Arc::default();
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, value)| value)
+    }
+
+    /// Adds the entries of `other` whose keys are not already in this map.
+    #[cfg(feature = "std")]
+    pub(crate) fn union(&mut self, other: &Self) {
+        for (key, value) in other.entries.iter() {
+            if let Err(index) = self.find(key) {
+                Arc::make_mut(&mut self.entries).insert(index, (key.clone(), value.clone()));
+            }
+        }
+    }
+}
+
+impl<K, V> Clone for Map<K, V> {
+    fn clone(&self) -> Self {
+        Map {
+            entries: self.entries.clone(),
+        }
+    }
+}
+
+impl<K, V> Default for Map<K, V> {
+    fn default() -> Self {
+        Map {
+            entries: Arc::default(),
+        }
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Index<&K> for Map<K, V> {
+    type Output = V;
+
+    fn index(&self, key: &K) -> &V {
+        self.get(key).expect("key not found in map")
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for Map<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map()
+            .entries(self.entries.iter().map(|(key, value)| (key, value)))
+            .finish()
+    }
+}
+
+#[test]
+fn copy_on_write() {
+    use alloc::string::String;
+
+    let mut map = Map::default();
+    assert_eq!(map.insert(2, 'b'), None);
+    assert_eq!(map.insert(1, 'a'), None);
+    assert_eq!(map.insert(3, 'c'), None);
+    assert_eq!(map.values().collect::<String>(), "abc");
+
+    let snapshot = map.clone();
+    assert_eq!(map.insert(2, 'x'), Some('b'));
+    assert_eq!(map.remove(&1), Some('a'));
+    assert_eq!(map.remove(&1), None);
+    assert_eq!(map.values().collect::<String>(), "xc");
+    assert_eq!(snapshot.values().collect::<String>(), "abc");
+    assert_eq!(snapshot[&2], 'b');
+    assert_eq!(snapshot.get(&4), None);
+
+    map.retain(|&value| value != 'c');
+    assert_eq!(map.values().collect::<String>(), "x");
+
+    map.clear();
+    assert_eq!(map.len(), 0);
+    assert_eq!(snapshot.len(), 3);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn union_keeps_existing_entries() {
+    use alloc::string::String;
+
+    let mut map = Map::default();
+    map.insert(1, 'a');
+    let mut other = Map::default();
+    other.insert(1, 'x');
+    other.insert(2, 'b');
+    map.union(&other);
+    assert_eq!(map.values().collect::<String>(), "ab");
+}
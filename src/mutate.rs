@@ -0,0 +1,141 @@
+#[cfg(feature = "std")]
+use core::cell::RefCell;
+
+#[cfg(feature = "std")]
+use proptest::prelude::*;
+use proptest::strategy::SBoxedStrategy;
+#[cfg(feature = "std")]
+use proptest::strategy::{NewTree, ValueTree};
+#[cfg(feature = "std")]
+use proptest::test_runner::TestRunner;
+
+#[cfg(feature = "std")]
+use crate::activity::Activity;
+#[cfg(feature = "std")]
+use crate::depth;
+
+#[cfg(feature = "std")]
+struct Mutation {
+    /// The number of nodes to build before the one to regenerate.
+    remaining: u64,
+    runner: TestRunner,
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    /// The pending mutation of the value currently being built by `current`, if any.
+    static MUTATION: RefCell<Option<Mutation>> = const { RefCell::new(None) };
+}
+
+#[cfg(feature = "std")]
+static MUTATING: Activity = Activity::new();
+
+/// Builds the value of a node with `current`, unless it is the node chosen by a pending mutation,
+/// in which case a new value is generated from `regenerate` instead.
+#[cfg(feature = "std")]
+pub(crate) fn node<T: core::fmt::Debug>(
+    regenerate: &SBoxedStrategy<T>,
+    current: impl FnOnce() -> T,
+) -> T {
+    if !MUTATING.is_active() {
+        return current();
+    }
+
+    let runner = MUTATION.with(|mutation| {
+        let mut mutation = mutation.borrow_mut();
+        match mutation.as_mut() {
+            Some(pending) if pending.remaining == 0 => mutation.take().map(|done| done.runner),
+            Some(pending) => {
+                pending.remaining -= 1;
+                None
+            }
+            None => None,
+        }
+    });
+
+    if let Some(mut runner) = runner {
+        if let Ok(tree) = regenerate.new_tree(&mut runner) {
+            return tree.current();
+        }
+    }
+    current()
+}
+
+// Without `std` there is no pending mutation to apply, so `mutated_pair` is unavailable.
+#[cfg(not(feature = "std"))]
+pub(crate) fn node<T: core::fmt::Debug>(_: &SBoxedStrategy<T>, current: impl FnOnce() -> T) -> T {
+    current()
+}
+
+/// Strategy returned by [`mutated_pair`](crate::mutated_pair).
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub(crate) struct MutatedPair<S>(pub(crate) S);
+
+#[cfg(feature = "std")]
+impl<S: Strategy> Strategy for MutatedPair<S> {
+    type Tree = MutatedPairTree<S::Tree>;
+    type Value = (S::Value, S::Value);
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let tree = self.0.new_tree(runner)?;
+        let choice = any::<u64>().new_tree(runner)?.current();
+        Ok(MutatedPairTree {
+            tree,
+            choice,
+            runner: TestRunner::new_with_rng(runner.config().clone(), runner.new_rng()),
+        })
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) struct MutatedPairTree<T> {
+    tree: T,
+    /// Selects the node to regenerate, modulo the number of nodes in the current value.
+    choice: u64,
+    /// The runner used to regenerate the chosen node. It is cloned for each call to `current`, so
+    /// the same node always gets the same new value.
+    runner: TestRunner,
+}
+
+/// Clears the pending mutation when dropped, whether or not it was applied, so that a panic while
+/// building the mutated value doesn't leave it pending for the next value built on this thread.
+#[cfg(feature = "std")]
+struct ClearOnDrop;
+
+#[cfg(feature = "std")]
+impl Drop for ClearOnDrop {
+    fn drop(&mut self) {
+        MUTATION.with(|mutation| mutation.borrow_mut().take());
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: ValueTree> ValueTree for MutatedPairTree<T> {
+    type Value = (T::Value, T::Value);
+
+    fn current(&self) -> Self::Value {
+        let (original, node_count) = depth::count_nodes(|| self.tree.current());
+        if node_count == 0 {
+            return (original, self.tree.current());
+        }
+
+        let _active = MUTATING.enter();
+        MUTATION.with(|mutation| {
+            *mutation.borrow_mut() = Some(Mutation {
+                remaining: self.choice % node_count,
+                runner: self.runner.clone(),
+            })
+        });
+        let _clear = ClearOnDrop;
+        (original, self.tree.current())
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.tree.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.tree.complicate()
+    }
+}
@@ -0,0 +1,99 @@
+use alloc::sync::Arc;
+use core::fmt;
+
+use proptest::strategy::{NewTree, Strategy};
+use proptest::test_runner::TestRunner;
+
+/// Callbacks for events during the generation of values by a recursive strategy, for use in
+/// logging, coverage accounting or tuning tools. Set using
+/// [`RecursiveParams::observer`](crate::RecursiveParams::observer).
+///
+/// Levels are numbered from the outermost level, 0, inwards. All methods do nothing by default.
+pub trait Observer: Send + Sync {
+    /// Called when a node is generated using the branch function at `level`.
+    fn on_branch(&self, level: u32) {
+        let _ = level;
+    }
+
+    /// Called when a node is generated using the base strategy.
+    fn on_leaf(&self) {}
+
+    /// Called when a node is generated using the base strategy because there were no levels left
+    /// to recurse into. This is always followed by a call to [`on_leaf`](Observer::on_leaf).
+    fn on_depth_exhausted(&self) {}
+}
+
+impl<O: Observer + ?Sized> Observer for Arc<O> {
+    fn on_branch(&self, level: u32) {
+        (**self).on_branch(level)
+    }
+
+    fn on_leaf(&self) {
+        (**self).on_leaf()
+    }
+
+    fn on_depth_exhausted(&self) {
+        (**self).on_depth_exhausted()
+    }
+}
+
+/// A shared observer, compared by identity.
+#[derive(Clone)]
+pub(crate) struct ObserverRef(pub(crate) Arc<dyn Observer>);
+
+impl fmt::Debug for ObserverRef {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("<observer>")
+    }
+}
+
+impl PartialEq for ObserverRef {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Event {
+    Branch(u32),
+    Leaf,
+}
+
+/// Wraps a strategy, notifying an observer each time a value tree is created.
+pub(crate) struct Notify<S> {
+    inner: S,
+    observer: ObserverRef,
+    event: Event,
+}
+
+impl<S> Notify<S> {
+    pub(crate) fn new(inner: S, observer: ObserverRef, event: Event) -> Self {
+        Notify {
+            inner,
+            observer,
+            event,
+        }
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for Notify<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Notify")
+            .field("inner", &self.inner)
+            .field("event", &self.event)
+            .finish()
+    }
+}
+
+impl<S: Strategy> Strategy for Notify<S> {
+    type Tree = S::Tree;
+    type Value = S::Value;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        match self.event {
+            Event::Branch(level) => self.observer.0.on_branch(level),
+            Event::Leaf => self.observer.0.on_leaf(),
+        }
+        self.inner.new_tree(runner)
+    }
+}
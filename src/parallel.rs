@@ -0,0 +1,264 @@
+//! Generating sibling subtrees in parallel. Requires the `rayon` feature.
+//!
+//! For stress tests which generate very large values, most of the time is spent generating the
+//! subtrees of the outermost nodes, which are independent of each other. [`recursive_vec`] is a
+//! drop-in replacement for [`collection::recursive_vec`](crate::collection::recursive_vec) which
+//! generates the elements of vectors at the outermost levels on the rayon thread pool.
+//!
+//! Each element is generated from its own random number generator, split from the runner's in
+//! order, so the same seed always produces the same value regardless of the number of threads.
+//! Elements are generated without their value trees; if the value is shrunk, the value tree for
+//! an element is generated again from its random number generator on the current thread the first
+//! time it is needed. This requires the element strategy to be deterministic, so it should not be
+//! combined with [`prop_mutually_recursive_shared`](crate::StrategyExt::prop_mutually_recursive_shared)
+//! or [`prop_mutually_recursive_memoized`](crate::StrategyExt::prop_mutually_recursive_memoized).
+//!
+//! Siblings are generated independently of each other, so vectors of types whose recursive
+//! strategy has a [`Shape`](crate::Shape) other than the default are always generated on the
+//! current thread.
+//!
+//! Features which observe the nodes of a value as it is built on the current thread, such as
+//! [`with_depth`](crate::with_depth), [`zipper`](crate::zipper), [`replay`](crate::replay) and the
+//! limits set by [`RecursiveParams::max_nodes`](crate::RecursiveParams::max_nodes), don't see
+//! the elements generated in parallel.
+//!
+//! # Examples
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! # use proptest::strategy::{Just, SBoxedStrategy};
+//! use proptest_recurse::parallel::recursive_vec;
+//! use proptest_recurse::{StrategyExt, StrategySet};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Tree {
+//!     Leaf,
+//!     Node(Vec<Tree>),
+//! }
+//!
+//! fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+//!     Just(Tree::Leaf).prop_mutually_recursive(8, 4096, 8, set, |set| {
+//!         recursive_vec(set, arb_tree, 0..16).prop_map(Tree::Node).sboxed()
+//!     })
+//! }
+//! # let _ = arb_tree(&mut StrategySet::default());
+//! ```
+
+use std::any::{Any, TypeId};
+use std::boxed::Box;
+use std::fmt;
+use std::sync::Arc;
+use std::vec::Vec;
+
+use proptest::collection::SizeRange;
+use proptest::strategy::{NewTree, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::{Config, Reason, TestRng, TestRunner};
+use rayon::prelude::*;
+
+use crate::{collection, StrategySet};
+
+/// The number of outermost levels whose vectors are generated in parallel. Below these, subtrees
+/// are small enough that the cost of spreading them between threads outweighs the benefit.
+const PARALLEL_LEVELS: u32 = 2;
+
+/// Returns a strategy for vectors of `T`, whose length is in `size` scaled by the remaining depth
+/// of `T`, as with [`collection::recursive_vec`]. At the outermost levels of the recursive
+/// strategy for `T`, or outside it, the elements are generated in parallel, unless the strategy
+/// for `T` makes siblings depend on each other's choices with a [`Shape`](crate::Shape).
+pub fn recursive_vec<T, F>(
+    set: &mut StrategySet,
+    factory: F,
+    size: impl Into<SizeRange>,
+) -> SBoxedStrategy<Vec<T>>
+where
+    T: Any + Clone + Send + Sync + fmt::Debug,
+    F: FnOnce(&mut StrategySet) -> SBoxedStrategy<T>,
+{
+    let outermost = match set.levels.get(&TypeId::of::<T>()) {
+        Some(&(level, _)) => level < PARALLEL_LEVELS,
+        None => true,
+    };
+    // Elements generated in parallel can't see the choices made by the elements before them.
+    let independent = set
+        .shapes
+        .get(&TypeId::of::<T>())
+        .is_none_or(|shape| shape.is_independent());
+    if !outermost || !independent {
+        return collection::recursive_vec(set, factory, size);
+    }
+
+    let size = collection::scale::<T>(set, size.into());
+    ParVec {
+        element: set.get(factory),
+        size,
+    }
+    .sboxed()
+}
+
+#[derive(Debug)]
+struct ParVec<T> {
+    element: SBoxedStrategy<T>,
+    size: SizeRange,
+}
+
+impl<T> Strategy for ParVec<T>
+where
+    T: Clone + Send + Sync + fmt::Debug + 'static,
+{
+    type Tree = ParVecTree<T>;
+    type Value = Vec<T>;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let (start, end) = self.size.start_end_incl();
+        let len = (start..=end).new_tree(runner)?.current();
+        let rngs: Vec<TestRng> = (0..len).map(|_| runner.new_rng()).collect();
+
+        let config = Arc::new(runner.config().clone());
+        let values = rngs
+            .par_iter()
+            .map(|rng| -> Result<T, Reason> {
+                let mut runner = TestRunner::new_with_rng((*config).clone(), rng.clone());
+                Ok(self.element.new_tree(&mut runner)?.current())
+            })
+            .collect::<Result<Vec<T>, Reason>>()?;
+
+        Ok(ParVecTree {
+            element: self.element.clone(),
+            config,
+            elements: rngs
+                .into_iter()
+                .zip(values)
+                .map(|(rng, value)| Element {
+                    rng,
+                    value,
+                    tree: None,
+                })
+                .collect(),
+            included: vec![true; len],
+            min_len: start,
+            shrink: Shrink::Delete(0),
+            prev_shrink: None,
+        })
+    }
+}
+
+struct Element<T> {
+    rng: TestRng,
+    /// The value generated in parallel, used until the value tree is needed.
+    value: T,
+    tree: Option<Box<dyn ValueTree<Value = T>>>,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Shrink {
+    Delete(usize),
+    Element(usize),
+}
+
+/// Value tree for a vector whose elements were generated in parallel. Shrinks in the same way as
+/// the value trees of `proptest::collection::vec`, by deleting elements and then shrinking each
+/// remaining element in turn.
+struct ParVecTree<T> {
+    element: SBoxedStrategy<T>,
+    config: Arc<Config>,
+    elements: Vec<Element<T>>,
+    included: Vec<bool>,
+    min_len: usize,
+    shrink: Shrink,
+    prev_shrink: Option<Shrink>,
+}
+
+impl<T: fmt::Debug> ParVecTree<T> {
+    /// Returns the value tree for the element at `index`, generating it again from the element's
+    /// random number generator if necessary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if generating the value tree again fails, which means the element strategy is not
+    /// deterministic.
+    fn tree(&mut self, index: usize) -> &mut Box<dyn ValueTree<Value = T>> {
+        let element = &mut self.elements[index];
+        let (strategy, config) = (&self.element, &self.config);
+        let rng = &element.rng;
+        element.tree.get_or_insert_with(|| {
+            let mut runner = TestRunner::new_with_rng((**config).clone(), rng.clone());
+            strategy.new_tree(&mut runner).unwrap_or_else(|reason| {
+                panic!(
+                    "generating an element of a parallel vector again failed ({}), although the \
+                     first attempt succeeded; the element strategy must be deterministic",
+                    reason
+                )
+            })
+        })
+    }
+}
+
+impl<T: Clone + fmt::Debug> ValueTree for ParVecTree<T> {
+    type Value = Vec<T>;
+
+    fn current(&self) -> Vec<T> {
+        self.elements
+            .iter()
+            .zip(&self.included)
+            .filter(|(_, &included)| included)
+            .map(|(element, _)| match &element.tree {
+                Some(tree) => tree.current(),
+                None => element.value.clone(),
+            })
+            .collect()
+    }
+
+    fn simplify(&mut self) -> bool {
+        if let Shrink::Delete(index) = self.shrink {
+            let len = self.included.iter().filter(|&&included| included).count();
+            if index >= self.elements.len() || len == self.min_len {
+                self.shrink = Shrink::Element(0);
+            } else {
+                self.included[index] = false;
+                self.prev_shrink = Some(self.shrink);
+                self.shrink = Shrink::Delete(index + 1);
+                return true;
+            }
+        }
+
+        while let Shrink::Element(index) = self.shrink {
+            if index >= self.elements.len() {
+                return false;
+            }
+            let simplified = self.included[index] && self.tree(index).simplify();
+            if simplified {
+                self.prev_shrink = Some(self.shrink);
+                return true;
+            }
+            self.shrink = Shrink::Element(index + 1);
+        }
+        false
+    }
+
+    fn complicate(&mut self) -> bool {
+        match self.prev_shrink {
+            None => false,
+            Some(Shrink::Delete(index)) => {
+                self.included[index] = true;
+                self.prev_shrink = None;
+                true
+            }
+            Some(Shrink::Element(index)) => {
+                let complicated = self.tree(index).complicate();
+                if !complicated {
+                    self.prev_shrink = None;
+                }
+                complicated
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for ParVecTree<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParVecTree")
+            .field("len", &self.elements.len())
+            .field("shrink", &self.shrink)
+            .finish()
+    }
+}
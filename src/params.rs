@@ -0,0 +1,158 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+#[cfg(feature = "std")]
+use std::time::Duration;
+
+use crate::observer::ObserverRef;
+use crate::{Observer, Shape};
+
+/// Parameters controlling the size of values generated by a recursive strategy.
+///
+/// The parameters apply only to values of the strategy they are passed to, not to other
+/// strategies in the same [`StrategySet`](crate::StrategySet).
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecursiveParams {
+    pub(crate) depth: u32,
+    pub(crate) desired_size: u32,
+    pub(crate) expected_branch_size: u32,
+    pub(crate) target_size: Option<RangeInclusive<u32>>,
+    pub(crate) shape: Shape,
+    pub(crate) observer: Option<ObserverRef>,
+    pub(crate) max_nodes: Option<u64>,
+    #[cfg(feature = "std")]
+    pub(crate) timeout: Option<Duration>,
+}
+
+impl RecursiveParams {
+    /// Creates parameters with the same meaning as the arguments to `prop_recursive`. By default
+    /// the probability of recursing at each level is chosen using the same heuristic as
+    /// `prop_recursive`, which makes `desired_size` only a loose upper bound.
+    pub fn new(depth: u32, desired_size: u32, expected_branch_size: u32) -> Self {
+        RecursiveParams {
+            depth,
+            desired_size,
+            expected_branch_size,
+            target_size: None,
+            shape: Shape::default(),
+            observer: None,
+            max_nodes: None,
+            #[cfg(feature = "std")]
+            timeout: None,
+        }
+    }
+
+    /// Switches to size-targeted generation. For each generated value, a target node count is
+    /// drawn uniformly from `range`, and the probability of recursing at each level is chosen so
+    /// that the expected number of nodes is close to that target. This gives a much more even
+    /// spread of sizes than the default heuristic, which rarely produces medium sized values.
+    ///
+    /// Use a range containing a single value to always target the same size. `desired_size` is
+    /// ignored in this mode.
+    pub fn target_size(mut self, range: RangeInclusive<u32>) -> Self {
+        self.target_size = Some(range);
+        self
+    }
+
+    /// Sets how the recursion budget is shared between sibling nodes. See [`Shape`] for details.
+    pub fn shape(mut self, shape: Shape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets an observer which is notified of events during the generation of values. Events are
+    /// reported when value trees are created, which may include while shrinking.
+    pub fn observer<O: Observer + 'static>(mut self, observer: O) -> Self {
+        self.observer = Some(ObserverRef(Arc::new(observer)));
+        self
+    }
+
+    /// Limits the number of nodes built while generating a single value, including the nodes of
+    /// other recursive strategies nested inside it.
+    ///
+    /// Mis-specified parameters, such as a large `expected_branch_size` combined with wide
+    /// collections in the branch function, can make generating one value take so long that the
+    /// test run appears to hang, or run out of memory. If this limit is exceeded, generation
+    /// panics with a message naming the type and parameters of this strategy instead. Only
+    /// enforced with the `std` feature.
+    pub fn max_nodes(mut self, max_nodes: u64) -> Self {
+        self.max_nodes = Some(max_nodes);
+        self
+    }
+
+    /// Limits the time taken to generate a single value. As with
+    /// [`max_nodes`](RecursiveParams::max_nodes), generation panics with a message naming the
+    /// type and parameters of this strategy if the limit is exceeded. The limit is checked each
+    /// time a node is built, so it may be overrun by a node which is slow to generate.
+    #[cfg(feature = "std")]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Returns these parameters scaled down to a fraction of their original size.
+    pub(crate) fn scale(&self, budget: f64) -> RecursiveParams {
+        if budget >= 1.0 {
+            return self.clone();
+        }
+
+        let scale = |value: u32| (f64::from(value) * budget).round() as u32;
+        RecursiveParams {
+            depth: scale(self.depth),
+            desired_size: scale(self.desired_size),
+            expected_branch_size: self.expected_branch_size,
+            target_size: self
+                .target_size
+                .as_ref()
+                .map(|range| scale(*range.start()).max(1)..=scale(*range.end()).max(1)),
+            shape: self.shape,
+            observer: self.observer.clone(),
+            max_nodes: self.max_nodes,
+            #[cfg(feature = "std")]
+            timeout: self.timeout,
+        }
+    }
+
+    /// Returns the probability of recursing at each level, from the outermost level inwards.
+    pub(crate) fn branch_probabilities(&self, target_size: Option<u32>) -> Vec<f64> {
+        match target_size {
+            None => self.heuristic_branch_probabilities(),
+            Some(target_size) => self.targeted_branch_probabilities(target_size),
+        }
+    }
+
+    fn heuristic_branch_probabilities(&self) -> Vec<f64> {
+        // copied from https://github.com/AltSysrq/proptest/blob/ee53956395492c8172a6d437cb0d2962f6077572/src/strategy/recursive.rs#L76
+
+        let mut branch_probabilities = Vec::new();
+        let mut k2 = u64::from(self.expected_branch_size) * 2;
+        for _ in 0..self.depth {
+            branch_probabilities.push(f64::from(self.desired_size) / k2 as f64);
+            k2 = k2.saturating_mul(u64::from(self.expected_branch_size) * 2);
+        }
+        branch_probabilities
+    }
+
+    fn targeted_branch_probabilities(&self, target_size: u32) -> Vec<f64> {
+        // Each level is given enough fuel for one node plus an equal share of the remaining fuel
+        // for each of its children. Working from the innermost level outwards, we pick the branch
+        // probability that brings the expected node count at that level up to its fuel.
+        let branch_size = f64::from(self.expected_branch_size.max(1));
+        let mut fuel = Vec::with_capacity(self.depth as usize);
+        let mut level_fuel = f64::from(target_size);
+        for _ in 0..self.depth {
+            fuel.push(level_fuel);
+            level_fuel = ((level_fuel - 1.0) / branch_size).max(1.0);
+        }
+
+        let mut branch_probabilities = vec![0.0; fuel.len()];
+        let mut expected_size = 1.0;
+        for (level, level_fuel) in fuel.iter().enumerate().rev() {
+            let growth = 1.0 + (branch_size - 1.0) * expected_size;
+            let probability = ((level_fuel - expected_size) / growth).max(0.0);
+            branch_probabilities[level] = probability;
+            expected_size += probability.min(0.9) * growth;
+        }
+        branch_probabilities
+    }
+}
@@ -1,90 +1,328 @@
-use std::fmt;
-use std::sync::Arc;
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::{Any, TypeId};
+use core::fmt;
 
-use proptest::strategy::{float_to_weight, NewTree, ValueTree};
+use proptest::prelude::*;
+use proptest::strategy::{NewTree, ValueTree};
 use proptest::test_runner::*;
-use proptest::{prelude::*, prop_oneof};
 
-pub(crate) struct Recursive<T> {
-    base: SBoxedStrategy<T>,
-    recurse: Arc<dyn Fn(SBoxedStrategy<T>) -> SBoxedStrategy<T> + Send + Sync>,
-    depth: u32,
-    desired_size: u32,
-    expected_branch_size: u32,
+use crate::entry::Entry;
+use crate::filter::SubtreeFilter;
+use crate::guard;
+use crate::replay;
+use crate::simplify::Simplifier;
+use crate::size::{SizeCheck, SizeStats};
+use crate::tower::Tower;
+use crate::{RecursiveParams, Simplify, StrategySet, TreeSize};
+
+/// Strategy returned by
+/// [`prop_mutually_recursive_unboxed`](crate::StrategyExt::prop_mutually_recursive_unboxed).
+///
+/// Unlike the strategy returned by
+/// [`prop_mutually_recursive`](crate::StrategyExt::prop_mutually_recursive), this is a concrete
+/// type, so no dynamic dispatch is needed to call `new_tree` on the outermost level.
+pub struct Recursive<S: Strategy, F> {
+    base: Arc<S>,
+    exhausted_leaf: Option<SBoxedStrategy<S::Value>>,
+    filter: Option<SubtreeFilter<S::Value>>,
+    simplifier: Option<Simplifier<S::Value>>,
+    size: SizeCheck<S::Value>,
+    branch: Arc<F>,
+    set: StrategySet,
+    params: RecursiveParams,
 }
 
-impl<T: fmt::Debug> fmt::Debug for Recursive<T> {
+impl<S: Strategy + fmt::Debug, F> fmt::Debug for Recursive<S, F> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Recursive")
             .field("base", &self.base)
-            .field("recurse", &"<function>")
-            .field("depth", &self.depth)
-            .field("desired_size", &self.desired_size)
-            .field("expected_branch_size", &self.expected_branch_size)
+            .field("exhausted_leaf", &self.exhausted_leaf)
+            .field("filter", &self.filter)
+            .field("simplifier", &self.simplifier)
+            .field("size", &self.size)
+            .field("branch", &"<function>")
+            .field("set", &self.set)
+            .field("params", &self.params)
             .finish()
     }
 }
 
-impl<T> Clone for Recursive<T> {
+impl<S: Strategy, F> Clone for Recursive<S, F> {
     fn clone(&self) -> Self {
         Recursive {
-            base: self.base.clone(),
-            recurse: Arc::clone(&self.recurse),
-            depth: self.depth,
-            desired_size: self.desired_size,
-            expected_branch_size: self.expected_branch_size,
+            base: Arc::clone(&self.base),
+            exhausted_leaf: self.exhausted_leaf.clone(),
+            filter: self.filter.clone(),
+            simplifier: self.simplifier,
+            size: self.size.clone(),
+            branch: Arc::clone(&self.branch),
+            set: self.set.clone(),
+            params: self.params.clone(),
         }
     }
 }
 
-impl<T: fmt::Debug + 'static> Recursive<T> {
-    pub(crate) fn new(
-        base: SBoxedStrategy<T>,
-        depth: u32,
-        desired_size: u32,
-        expected_branch_size: u32,
-        recurse: impl Fn(SBoxedStrategy<T>) -> SBoxedStrategy<T> + Send + Sync + 'static,
-    ) -> Self {
+impl<S, F> Recursive<S, F>
+where
+    S: Strategy + Send + Sync + 'static,
+    S::Value: Any,
+    F: Fn(&mut StrategySet) -> SBoxedStrategy<S::Value>,
+{
+    pub(crate) fn new(base: S, params: RecursiveParams, set: &StrategySet, branch: F) -> Self {
+        let base = Arc::new(base);
+        Entry::report(set, &params, || Arc::clone(&base).sboxed());
         Self {
-            base: base.sboxed(),
-            recurse: Arc::new(recurse),
-            depth,
-            desired_size,
-            expected_branch_size,
+            base,
+            exhausted_leaf: None,
+            filter: None,
+            simplifier: None,
+            size: SizeCheck::new(),
+            branch: Arc::new(branch),
+            set: set.clone(),
+            params,
         }
     }
-}
 
-impl<T: fmt::Debug + 'static> Strategy for Recursive<T> {
-    type Tree = Box<dyn ValueTree<Value = T>>;
-    type Value = T;
+    /// Sets a strategy used instead of the base strategy for nodes generated where the depth limit
+    /// prevents any further recursion.
+    ///
+    /// When the base strategy only produces trivial values, deep values otherwise end in a fringe
+    /// of identical leaves. The exhausted leaf strategy can instead produce richer nodes, such as
+    /// nodes with all of their non-recursive fields populated, so the deepest parts of values stay
+    /// interesting.
+    pub fn exhausted_leaf<L>(mut self, leaf: L) -> Self
+    where
+        L: Strategy<Value = S::Value> + Send + Sync + 'static,
+    {
+        self.exhausted_leaf = Some(leaf.sboxed());
+        self
+    }
 
-    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
-        // copied from https://github.com/AltSysrq/proptest/blob/ee53956395492c8172a6d437cb0d2962f6077572/src/strategy/recursive.rs#L76
+    /// Only generates nodes which satisfy `predicate`, at every level of this strategy.
+    ///
+    /// Unlike `prop_filter` applied to the whole value, a node which fails the predicate is
+    /// regenerated on its own, leaving the rest of the value intact. Up to `retries` nodes are
+    /// regenerated in total for each value, however deep it is. Once they are used up, a local
+    /// rejection is counted against the runner, as with `prop_filter`, and the whole value is
+    /// generated again. While shrinking, values are only simplified to ones which satisfy the
+    /// predicate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use proptest::prelude::*;
+    /// # use proptest::strategy::SBoxedStrategy;
+    /// use proptest_recurse::{StrategyExt, StrategySet};
+    ///
+    /// #[derive(Clone, Debug)]
+    /// enum Expr {
+    ///     Lit(i32),
+    ///     Div(Box<Expr>, Box<Expr>),
+    /// }
+    ///
+    /// impl Expr {
+    ///     fn eval(&self) -> Option<i32> {
+    ///         match self {
+    ///             Expr::Lit(value) => Some(*value),
+    ///             Expr::Div(l, r) => l.eval()?.checked_div(r.eval()?),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    ///     any::<i32>()
+    ///         .prop_map(Expr::Lit)
+    ///         .prop_mutually_recursive_unboxed(4, 32, 4, set, |set| {
+    ///             (set.get(arb_expr), set.get(arb_expr))
+    ///                 .prop_map(|(l, r)| Expr::Div(Box::new(l), Box::new(r)))
+    ///                 .sboxed()
+    ///         })
+    ///         .prop_filter_subtree("division by zero", 8, |expr| expr.eval().is_some())
+    ///         .sboxed()
+    /// }
+    /// # let _ = arb_expr(&mut StrategySet::default());
+    /// ```
+    pub fn prop_filter_subtree<R, P>(mut self, whence: R, retries: u32, predicate: P) -> Self
+    where
+        R: Into<Reason>,
+        P: Fn(&S::Value) -> bool + Send + Sync + 'static,
+    {
+        self.filter = Some(SubtreeFilter::new(whence.into(), retries, predicate));
+        self
+    }
+
+    /// Shrinks every node of this strategy with the simplifications returned by the [`Simplify`]
+    /// implementation of the value type, as well as by shrinking the random choices made while
+    /// generating it.
+    ///
+    /// Each node tries its simplifications before its own value tree is shrunk, so that large
+    /// structural simplifications, such as replacing an expression with one of its operands, are
+    /// found first, starting from the outermost node. If none of them still fail the test, the
+    /// node's value tree is shrunk, which simplifies the nodes nested inside it in the same way,
+    /// and once it can't be shrunk any further, the simplifications of its final value are tried.
+    /// Whenever a simplification still fails the test, the node continues from its own
+    /// simplifications, and its value tree is no longer used.
+    ///
+    /// Values produced by simplification are not built by this strategy, so they aren't seen by
+    /// [`with_depth`](crate::with_depth) or [`mutated_pair`](crate::mutated_pair).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use proptest::prelude::*;
+    /// # use proptest::strategy::SBoxedStrategy;
+    /// use proptest_recurse::{Simplify, StrategyExt, StrategySet};
+    ///
+    /// #[derive(Clone, Debug)]
+    /// enum Expr {
+    ///     Lit(i32),
+    ///     Add(Box<Expr>, Box<Expr>),
+    /// }
+    ///
+    /// impl Simplify for Expr {
+    ///     fn simplify(&self) -> Vec<Self> {
+    ///         match self {
+    ///             Expr::Lit(_) => vec![],
+    ///             Expr::Add(a, b) => vec![(**a).clone(), (**b).clone()],
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    ///     any::<i32>()
+    ///         .prop_map(Expr::Lit)
+    ///         .prop_mutually_recursive_unboxed(4, 16, 2, set, |set| {
+    ///             (set.get(arb_expr), set.get(arb_expr))
+    ///                 .prop_map(|(a, b)| Expr::Add(Box::new(a), Box::new(b)))
+    ///                 .sboxed()
+    ///         })
+    ///         .simplified()
+    ///         .sboxed()
+    /// }
+    /// # let _ = arb_expr(&mut StrategySet::default());
+    /// ```
+    pub fn simplified(mut self) -> Self
+    where
+        S::Value: Simplify + Clone,
+    {
+        self.simplifier = Some(Simplifier::new());
+        self
+    }
 
-        let mut branch_probabilities = Vec::new();
-        let mut k2 = u64::from(self.expected_branch_size) * 2;
-        for _ in 0..self.depth {
-            branch_probabilities.push(f64::from(self.desired_size) / k2 as f64);
-            k2 = k2.saturating_mul(u64::from(self.expected_branch_size) * 2);
+    /// Regenerates values with more than `max_size` nodes, as counted by [`TreeSize`].
+    ///
+    /// Unlike the statistical size parameters, this is a hard bound on the whole value, including
+    /// nodes of other types. As with `prop_filter`, discarded values count as local rejections
+    /// and shrinking never produces values over the bound. This is distinct from
+    /// [`RecursiveParams::max_nodes`], which stops runaway generation by panicking.
+    pub fn max_size(mut self, max_size: u64) -> Self
+    where
+        S::Value: TreeSize,
+    {
+        self.size.set_max_size(max_size);
+        self
+    }
+
+    /// Records the node count of every generated value in `stats`, as counted by [`TreeSize`].
+    /// Values produced while shrinking are not recorded.
+    pub fn track_size(mut self, stats: &SizeStats) -> Self
+    where
+        S::Value: TreeSize,
+    {
+        self.size.set_stats(stats);
+        self
+    }
+
+    fn recurse(
+        &self,
+        level: u32,
+        depth: u32,
+        filter: Option<&SubtreeFilter<S::Value>>,
+        nested: SBoxedStrategy<S::Value>,
+    ) -> Option<SBoxedStrategy<S::Value>> {
+        let mut set = self.set.clone();
+        set.pruned = false;
+        set.report = None;
+        // Strategies created by factories outside this level, such as those registered up front
+        // by `strategy_set!`, don't know the current level of this type. They are created again
+        // by `get` within the branch, so that nested levels share this type's depth budget.
+        set.inner.retain(|entry| !entry.is_factory());
+        let nested = match filter {
+            Some(filter) => filter.wrap(nested, false).sboxed(),
+            None => nested,
+        };
+        set.inner.insert(
+            TypeId::of::<S::Value>(),
+            Entry::new(nested)
+                .with_leaf(Arc::clone(&self.base).sboxed())
+                .with_debug::<S::Value>(),
+        );
+        // A node generated at this level has at most `level` ancestors of the same type.
+        set.back_refs.insert(TypeId::of::<S::Value>(), level);
+        set.levels.insert(TypeId::of::<S::Value>(), (level, depth));
+        set.shapes
+            .insert(TypeId::of::<S::Value>(), self.params.shape);
+        let branch = (self.branch)(&mut set);
+        if set.pruned {
+            None
+        } else {
+            Some(branch)
         }
+    }
+}
 
-        let mut strat = self.base.clone();
-        while let Some(branch_probability) = branch_probabilities.pop() {
-            let recursed = (self.recurse)(strat.clone());
-            let recursive_choice = recursed.sboxed();
-            let non_recursive_choice = strat;
-            // Clamp the maximum branch probability to 0.9 to ensure we can
-            // generate non-recursive cases reasonably often.
-            let branch_probability = branch_probability.min(0.9);
-            let (weight_branch, weight_leaf) = float_to_weight(branch_probability);
-            let branch = prop_oneof![
-                weight_leaf => non_recursive_choice,
-                weight_branch => recursive_choice,
-            ];
-            strat = branch.sboxed();
+impl<S, F> Strategy for Recursive<S, F>
+where
+    S: Strategy + Send + Sync + 'static,
+    S::Value: Any,
+    F: Fn(&mut StrategySet) -> SBoxedStrategy<S::Value>,
+{
+    type Tree = Box<dyn ValueTree<Value = S::Value>>;
+    type Value = S::Value;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let mut params = self.params.scale(self.set.budget.0);
+        if let Some(max_depth) = self.set.max_depth {
+            params.depth = params.depth.min(max_depth);
         }
+        // When replaying a recording, the branch probabilities are not used, so all levels are
+        // kept available.
+        let replaying = replay::replaying();
+        let target_size = match &params.target_size {
+            Some(range) if !replaying => Some(replay::with_runner(runner, |runner| {
+                Ok::<_, Reason>(range.clone().new_tree(runner)?.current())
+            })?),
+            _ => None,
+        };
+        let branch_probabilities: Vec<(u32, f64)> = params
+            .branch_probabilities(target_size)
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, branch_probability)| replaying || branch_probability > 0.0)
+            .map(|(level, branch_probability)| (level as u32, branch_probability))
+            .collect();
 
-        strat.new_tree(runner)
+        // The nodes of each value share one retry budget.
+        let filter = self.filter.as_ref().map(SubtreeFilter::for_value);
+        let tower = Tower::build(
+            Arc::clone(&self.base).sboxed(),
+            self.exhausted_leaf.clone(),
+            self.simplifier,
+            &params,
+            &branch_probabilities,
+            |level, nested| self.recurse(level, params.depth, filter.as_ref(), nested),
+        );
+        guard::guarded(
+            core::any::type_name::<S::Value>(),
+            &params,
+            || match &filter {
+                Some(filter) => self
+                    .size
+                    .new_tree(filter.wrap(tower.strategy(), true), runner),
+                None => self.size.new_tree(tower.strategy(), runner),
+            },
+        )
     }
 }
@@ -0,0 +1,348 @@
+//! Recording and replaying the recursion decisions made while generating a value.
+//!
+//! A seed persisted by proptest reproduces a failing value only as long as the strategy consumes
+//! random numbers in exactly the same way. For recursive strategies this is fragile: changing the
+//! depth or size parameters changes which levels flip a coin, which shifts every random choice
+//! after it, so a saved regression silently turns into an unrelated value.
+//!
+//! Values generated by a strategy wrapped with [`record`] come with a [`Recording`], which holds
+//! a seed for the random choices made by the non-recursive parts of the strategy and, separately,
+//! the level at which each node generated by a recursive strategy recursed, or that it was a leaf.
+//! Passing the recording to [`replay`] makes the same decisions again, in the order the nodes are
+//! generated, while all other choices use the same random numbers as before. This reproduces the
+//! original value exactly if the strategy is unchanged, including strategies which depend on the
+//! level, such as those built with the helpers in [`collection`](crate::collection), and keeps its
+//! structure when the parameters of recursive strategies change.
+//!
+//! Levels are recorded by their height, the number of levels inside them, so that a node still
+//! has as many levels left to recurse into if the depth limit is raised or lowered. Nodes recurse
+//! at the next level inwards if their recorded level is no longer available, or the outermost
+//! level available if the depth limit is now too low for it. Nodes which no longer have any
+//! levels to recurse into become leaves, and nodes beyond the end of the recording are leaves.
+//!
+//! Like proptest's seeds, the recording reproduces the value as it was generated, before
+//! shrinking.
+//!
+//! # Examples
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! # use proptest::strategy::ValueTree;
+//! # use proptest::test_runner::TestRunner;
+//! use proptest_recurse::replay::{record, replay, Recording};
+//! use proptest_recurse::tree::recursive_tree;
+//! use proptest_recurse::RecursiveParams;
+//!
+//! let trees = recursive_tree(any::<u8>(), 1..4, RecursiveParams::new(4, 32, 2));
+//! let (tree, recording) = record(trees.clone())
+//!     .new_tree(&mut TestRunner::default())
+//!     .unwrap()
+//!     .current();
+//!
+//! // The recording can be saved as a string, for example in a regression test.
+//! let recording: Recording = recording.to_string().parse().unwrap();
+//! let replayed = replay(trees, recording)
+//!     .new_tree(&mut TestRunner::default())
+//!     .unwrap()
+//!     .current();
+//! assert_eq!(tree, replayed);
+//! ```
+
+use std::boxed::Box;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::string::ToString;
+use std::vec::Vec;
+
+use proptest::prelude::*;
+use proptest::strategy::{NewTree, SBoxedStrategy, ValueTree};
+use proptest::test_runner::{Reason, RngAlgorithm, TestRng, TestRunner};
+
+use crate::activity::Activity;
+
+/// The decisions made while generating a value from a strategy wrapped with [`record`].
+///
+/// Recordings can be converted to and from strings with `to_string` and `parse`.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Recording {
+    seed: [u8; 32],
+    /// The height of the level each node recursed at, or `None` for a leaf.
+    decisions: Vec<Option<u32>>,
+}
+
+impl Recording {
+    /// Returns the number of nodes whose decision was recorded.
+    pub fn len(&self) -> usize {
+        self.decisions.len()
+    }
+
+    /// Returns `true` if no decisions were recorded, because the value contains no nodes
+    /// generated by recursive strategies.
+    pub fn is_empty(&self) -> bool {
+        self.decisions.is_empty()
+    }
+}
+
+impl fmt::Debug for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Recording").field(&self.to_string()).finish()
+    }
+}
+
+/// Formats the recording as the seed in hexadecimal, followed by a colon and a comma-separated
+/// list of the height of the level each node recursed at, or `-` for each leaf.
+impl fmt::Display for Recording {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in &self.seed {
+            write!(f, "{:02x}", byte)?;
+        }
+        f.write_str(":")?;
+        for (index, decision) in self.decisions.iter().enumerate() {
+            if index != 0 {
+                f.write_str(",")?;
+            }
+            match decision {
+                Some(level) => write!(f, "{}", level)?,
+                None => f.write_str("-")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Recording {
+    type Err = ParseRecordingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (seed_hex, decisions) = s.split_once(':').ok_or(ParseRecordingError(()))?;
+        if seed_hex.len() != 64 || !seed_hex.is_ascii() {
+            return Err(ParseRecordingError(()));
+        }
+
+        let mut seed = [0; 32];
+        for (byte, hex) in seed.iter_mut().zip(seed_hex.as_bytes().chunks(2)) {
+            let hex = std::str::from_utf8(hex).map_err(|_| ParseRecordingError(()))?;
+            *byte = u8::from_str_radix(hex, 16).map_err(|_| ParseRecordingError(()))?;
+        }
+        let decisions = if decisions.is_empty() {
+            Vec::new()
+        } else {
+            decisions
+                .split(',')
+                .map(|decision| match decision {
+                    "-" => Ok(None),
+                    level if level.bytes().all(|b| b.is_ascii_digit()) => {
+                        level.parse().map(Some).map_err(|_| ParseRecordingError(()))
+                    }
+                    _ => Err(ParseRecordingError(())),
+                })
+                .collect::<Result<_, _>>()?
+        };
+        Ok(Recording { seed, decisions })
+    }
+}
+
+/// The error returned when parsing an invalid [`Recording`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseRecordingError(());
+
+impl fmt::Display for ParseRecordingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid recording")
+    }
+}
+
+impl Error for ParseRecordingError {}
+
+/// Wraps `strategy` so that each value is generated together with a [`Recording`] of the
+/// decisions made while generating it.
+///
+/// The recursive strategies used by `strategy` flip their coins using a separate source of
+/// randomness from the rest of the strategy, so values differ from those generated by `strategy`
+/// alone with the same seed.
+pub fn record<S>(strategy: S) -> SBoxedStrategy<(S::Value, Recording)>
+where
+    S: Strategy + Send + Sync + 'static,
+{
+    Record(strategy).sboxed()
+}
+
+/// Wraps `strategy` so that it generates the value described by `recording`. See the
+/// [module documentation](self) for details.
+pub fn replay<S>(strategy: S, recording: Recording) -> SBoxedStrategy<S::Value>
+where
+    S: Strategy + Send + Sync + 'static,
+{
+    Replay {
+        inner: strategy,
+        recording,
+    }
+    .sboxed()
+}
+
+enum Mode {
+    Record {
+        runner: Box<TestRunner>,
+        decisions: Vec<Option<u32>>,
+    },
+    Replay {
+        decisions: VecDeque<Option<u32>>,
+    },
+}
+
+std::thread_local! {
+    /// The mode of the value tree currently being created by `new_tree`, if any.
+    static MODE: RefCell<Option<Mode>> = const { RefCell::new(None) };
+}
+
+static REPLAYING: Activity = Activity::new();
+
+/// Returns `true` if the value tree being created is replaying a recording, in which case
+/// recursive strategies make no random choices of their own.
+pub(crate) fn replaying() -> bool {
+    if !REPLAYING.is_active() {
+        return false;
+    }
+
+    MODE.with(|mode| matches!(*mode.borrow(), Some(Mode::Replay { .. })))
+}
+
+/// Calls `f` with the runner recursive strategies should use for their own random choices. This
+/// is `runner`, unless a recording is in progress.
+pub(crate) fn with_runner<R>(runner: &mut TestRunner, f: impl FnOnce(&mut TestRunner) -> R) -> R {
+    if !REPLAYING.is_active() {
+        return f(runner);
+    }
+
+    MODE.with(|mode| match &mut *mode.borrow_mut() {
+        Some(Mode::Record { runner, .. }) => f(runner),
+        _ => f(runner),
+    })
+}
+
+/// Decides which level a node recurses at, as an index into the levels of its strategy, or `None`
+/// if it is a leaf. When not replaying, `choose` makes the decision, and `height` gives the height
+/// to record for the chosen index. Otherwise, `replayed` gives the index to recurse at for the
+/// recorded height.
+pub(crate) fn decide<F>(
+    runner: &mut TestRunner,
+    choose: F,
+    height: impl FnOnce(usize) -> u32,
+    replayed: impl FnOnce(u32) -> Option<usize>,
+) -> Result<Option<usize>, Reason>
+where
+    F: FnOnce(&mut TestRunner) -> Result<Option<usize>, Reason>,
+{
+    if !REPLAYING.is_active() {
+        return choose(runner);
+    }
+
+    MODE.with(|mode| match &mut *mode.borrow_mut() {
+        None => choose(runner),
+        Some(Mode::Record { runner, decisions }) => {
+            let index = choose(runner)?;
+            decisions.push(index.map(height));
+            Ok(index)
+        }
+        Some(Mode::Replay { decisions }) => Ok(decisions.pop_front().flatten().and_then(replayed)),
+    })
+}
+
+/// Runs `f` with the given mode, returning the final mode.
+fn with_mode<R>(mode: Mode, f: impl FnOnce() -> R) -> (R, Mode) {
+    let _active = REPLAYING.enter();
+    let outer = MODE.with(|cell| cell.borrow_mut().replace(mode));
+    let _restore = RestoreOnDrop(outer);
+    let result = f();
+    let mode = MODE.with(|cell| cell.borrow_mut().take());
+    (result, mode.unwrap())
+}
+
+/// Restores the enclosing mode, if any, when dropped, so that a panic while creating a value tree
+/// doesn't leave a recording or replay in progress for the next value tree created on this thread.
+struct RestoreOnDrop(Option<Mode>);
+
+impl Drop for RestoreOnDrop {
+    fn drop(&mut self) {
+        let outer = self.0.take();
+        MODE.with(|cell| *cell.borrow_mut() = outer);
+    }
+}
+
+fn seeded_runner(config: &proptest::test_runner::Config, seed: &[u8; 32]) -> TestRunner {
+    TestRunner::new_with_rng(
+        config.clone(),
+        TestRng::from_seed(RngAlgorithm::ChaCha, seed),
+    )
+}
+
+#[derive(Debug)]
+struct Record<S>(S);
+
+impl<S: Strategy> Strategy for Record<S> {
+    type Tree = RecordTree<S::Tree>;
+    type Value = (S::Value, Recording);
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let seed = any::<[u8; 32]>().new_tree(runner)?.current();
+        let decision_seed = any::<[u8; 32]>().new_tree(runner)?.current();
+        let mode = Mode::Record {
+            runner: Box::new(seeded_runner(runner.config(), &decision_seed)),
+            decisions: Vec::new(),
+        };
+
+        let mut value_runner = seeded_runner(runner.config(), &seed);
+        let (tree, mode) = with_mode(mode, || self.0.new_tree(&mut value_runner));
+        let decisions = match mode {
+            Mode::Record { decisions, .. } => decisions,
+            Mode::Replay { .. } => unreachable!(),
+        };
+        Ok(RecordTree {
+            tree: tree?,
+            recording: Recording { seed, decisions },
+        })
+    }
+}
+
+struct RecordTree<T> {
+    tree: T,
+    recording: Recording,
+}
+
+impl<T: ValueTree> ValueTree for RecordTree<T> {
+    type Value = (T::Value, Recording);
+
+    fn current(&self) -> Self::Value {
+        (self.tree.current(), self.recording.clone())
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.tree.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.tree.complicate()
+    }
+}
+
+#[derive(Debug)]
+struct Replay<S> {
+    inner: S,
+    recording: Recording,
+}
+
+impl<S: Strategy> Strategy for Replay<S> {
+    type Tree = S::Tree;
+    type Value = S::Value;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let mode = Mode::Replay {
+            decisions: self.recording.decisions.iter().copied().collect(),
+        };
+        let mut value_runner = seeded_runner(runner.config(), &self.recording.seed);
+        with_mode(mode, || self.inner.new_tree(&mut value_runner)).0
+    }
+}
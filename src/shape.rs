@@ -0,0 +1,225 @@
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::sync::Mutex;
+
+use proptest::strategy::{NewTree, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::{Reason, TestRunner};
+
+use crate::stack::{self, Nested};
+use crate::{depth, mutate};
+
+/// Controls how the recursion budget is shared between sibling nodes.
+///
+/// Siblings here are nodes of the same type with the same nearest ancestor of that type. By
+/// default each sibling independently decides whether to recurse, which tends to produce trees
+/// that are neither very deep nor very bushy.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Shape {
+    /// At most one sibling recurses, producing deep, narrow, list-like trees.
+    Linear,
+    /// Siblings all make the same choice as the first sibling, producing wide, balanced trees.
+    Balanced,
+    /// Once one sibling has recursed, each of the others may only recurse with the given
+    /// probability. `Random(1.0)` is the default behaviour and `Random(0.0)` is equivalent to
+    /// [`Shape::Linear`].
+    Random(f64),
+}
+
+impl Default for Shape {
+    fn default() -> Self {
+        Shape::Random(1.0)
+    }
+}
+
+impl Shape {
+    pub(crate) fn is_independent(self) -> bool {
+        self == Shape::Random(1.0)
+    }
+
+    /// Decides whether a node recurses at a level with the given branch probability, taking into
+    /// account the choices made by its siblings.
+    pub(crate) fn choose(
+        self,
+        runner: &mut TestRunner,
+        stack: &SiblingStack,
+        branch_probability: f64,
+    ) -> Result<bool, Reason> {
+        let mut recurse = coin(runner, branch_probability)?;
+        if self.is_independent() {
+            return Ok(recurse);
+        }
+
+        let allow = match self {
+            Shape::Random(probability) => coin(runner, probability)?,
+            _ => true,
+        };
+        if let Some(siblings) = stack.lock().unwrap().last_mut() {
+            recurse = self.constrain(siblings, recurse, allow);
+            siblings.first.get_or_insert(recurse);
+            siblings.any_recursed |= recurse;
+        }
+        Ok(recurse)
+    }
+
+    fn constrain(self, siblings: &Siblings, recurse: bool, allow: bool) -> bool {
+        match self {
+            Shape::Linear => recurse && !siblings.any_recursed,
+            Shape::Balanced => siblings.first.unwrap_or(recurse),
+            Shape::Random(_) => recurse && (!siblings.any_recursed || allow),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct Siblings {
+    first: Option<bool>,
+    any_recursed: bool,
+}
+
+/// The sibling state of each node currently being generated, innermost last.
+pub(crate) type SiblingStack = Arc<Mutex<Vec<Siblings>>>;
+
+pub(crate) fn sibling_stack() -> SiblingStack {
+    Arc::default()
+}
+
+/// Wraps the recursive alternative of a level, tracking the choices made by its children.
+pub(crate) struct Parent<T> {
+    inner: SBoxedStrategy<T>,
+    stack: SiblingStack,
+}
+
+impl<T> Parent<T> {
+    pub(crate) fn new(inner: SBoxedStrategy<T>, stack: SiblingStack) -> Self {
+        Parent { inner, stack }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Parent<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Parent")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<T: fmt::Debug> Strategy for Parent<T> {
+    type Tree = Box<dyn ValueTree<Value = T>>;
+    type Value = T;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.stack.lock().unwrap().push(Siblings::default());
+        let result = self.inner.new_tree(runner);
+        self.stack.lock().unwrap().pop();
+        result
+    }
+}
+
+/// Returns `true` with the given probability.
+pub(crate) fn coin(runner: &mut TestRunner, probability: f64) -> Result<bool, Reason> {
+    if probability >= 1.0 {
+        return Ok(true);
+    }
+    if probability <= 0.0 {
+        return Ok(false);
+    }
+    Ok(proptest::bool::weighted(probability)
+        .new_tree(runner)?
+        .current())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum LevelState {
+    /// Using the recursive alternative.
+    Branch,
+    /// Just switched to the non-recursive alternative, which may be undone by `complicate`.
+    SwitchedToLeaf,
+    /// Using the non-recursive alternative.
+    Leaf,
+    /// Using the recursive alternative, after switching back from the non-recursive one.
+    LockedBranch,
+}
+
+/// Value tree for a level where the recursive alternative was chosen. Like the value tree for
+/// `prop_oneof`, it shrinks by switching to the non-recursive alternative, which is generated
+/// lazily.
+///
+/// Both alternatives may contain further levels, so every method which reaches into them,
+/// including dropping them, runs with [`stack::grow`].
+pub(crate) struct LevelTree<T> {
+    branch: Nested<Box<dyn ValueTree<Value = T>>>,
+    leaf: Nested<Option<Box<dyn ValueTree<Value = T>>>>,
+    pending_leaf: Option<(SBoxedStrategy<T>, TestRunner)>,
+    regenerate: SBoxedStrategy<T>,
+    state: LevelState,
+}
+
+impl<T> LevelTree<T> {
+    pub(crate) fn new(
+        branch: Box<dyn ValueTree<Value = T>>,
+        leaf: SBoxedStrategy<T>,
+        regenerate: SBoxedStrategy<T>,
+        runner: &mut TestRunner,
+    ) -> Self {
+        LevelTree {
+            branch: Nested::new(branch),
+            leaf: Nested::new(None),
+            pending_leaf: Some((
+                leaf,
+                TestRunner::new_with_rng(runner.config().clone(), runner.new_rng()),
+            )),
+            regenerate,
+            state: LevelState::Branch,
+        }
+    }
+}
+
+impl<T: fmt::Debug> ValueTree for LevelTree<T> {
+    type Value = T;
+
+    fn current(&self) -> T {
+        stack::grow(|| match self.state {
+            LevelState::Branch | LevelState::LockedBranch => {
+                mutate::node(&self.regenerate, || depth::branch(|| self.branch.current()))
+            }
+            LevelState::SwitchedToLeaf | LevelState::Leaf => self.leaf.as_ref().unwrap().current(),
+        })
+    }
+
+    fn simplify(&mut self) -> bool {
+        stack::grow(|| match self.state {
+            LevelState::Branch => {
+                if self.branch.simplify() {
+                    return true;
+                }
+                if let Some((strategy, mut runner)) = self.pending_leaf.take() {
+                    if let Ok(leaf) = strategy.new_tree(&mut runner) {
+                        *self.leaf = Some(leaf);
+                        self.state = LevelState::SwitchedToLeaf;
+                        return true;
+                    }
+                }
+                false
+            }
+            LevelState::LockedBranch => self.branch.simplify(),
+            LevelState::SwitchedToLeaf | LevelState::Leaf => {
+                self.state = LevelState::Leaf;
+                self.leaf.as_mut().unwrap().simplify()
+            }
+        })
+    }
+
+    fn complicate(&mut self) -> bool {
+        stack::grow(|| match self.state {
+            LevelState::SwitchedToLeaf => {
+                self.state = LevelState::LockedBranch;
+                true
+            }
+            LevelState::Branch | LevelState::LockedBranch => self.branch.complicate(),
+            LevelState::Leaf => self.leaf.as_mut().unwrap().complicate(),
+        })
+    }
+}
@@ -0,0 +1,154 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::sync::Mutex;
+
+use proptest::strategy::{Just, NewTree, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+/// The maximum number of values retained for reuse by a single pool.
+const MAX_POOL_SIZE: usize = 64;
+
+/// A pool of previously generated values of at most a given height.
+pub(crate) struct Pool<T> {
+    sharing_probability: f64,
+    values: Mutex<Vec<T>>,
+}
+
+impl<T> Pool<T> {
+    fn new(sharing_probability: f64) -> Arc<Self> {
+        Arc::new(Pool {
+            sharing_probability,
+            values: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+/// Pools of previously generated subtrees, keyed by the maximum height of the subtrees they hold.
+/// Keeping subtrees of different heights apart ensures reused subtrees never exceed the depth
+/// limit.
+pub(crate) struct Memo<T> {
+    reuse_probability: f64,
+    pools: Mutex<BTreeMap<u32, Arc<Pool<T>>>>,
+}
+
+impl<T> Memo<T> {
+    /// Creates an empty memo, checking that the probability passed as the parameter `name` is
+    /// valid.
+    pub(crate) fn new(name: &str, reuse_probability: f64) -> Arc<Self> {
+        assert!(
+            (0.0..=1.0).contains(&reuse_probability),
+            "{} must be between 0 and 1, got {}",
+            name,
+            reuse_probability
+        );
+        Arc::new(Memo {
+            reuse_probability,
+            pools: Mutex::new(BTreeMap::new()),
+        })
+    }
+
+    /// Returns the pool for subtrees of at most `height` levels.
+    pub(crate) fn pool(&self, height: u32) -> Arc<Pool<T>> {
+        Arc::clone(
+            self.pools
+                .lock()
+                .unwrap()
+                .entry(height)
+                .or_insert_with(|| Pool::new(self.reuse_probability)),
+        )
+    }
+
+    /// Empties every pool.
+    fn clear(&self) {
+        for pool in self.pools.lock().unwrap().values() {
+            pool.values.lock().unwrap().clear();
+        }
+    }
+}
+
+/// Strategy which empties the pools of a memo before generating each value, so that values are
+/// only reused within a single generated value. Otherwise, values would depend on the ones
+/// generated for earlier test cases, and couldn't be reproduced from the runner's seed alone.
+pub(crate) struct Scoped<T> {
+    inner: SBoxedStrategy<T>,
+    memo: Arc<Memo<T>>,
+}
+
+impl<T> Scoped<T> {
+    pub(crate) fn new(inner: SBoxedStrategy<T>, memo: Arc<Memo<T>>) -> Self {
+        Scoped { inner, memo }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Scoped<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Scoped")
+            .field("inner", &self.inner)
+            .field("reuse_probability", &self.memo.reuse_probability)
+            .finish()
+    }
+}
+
+impl<T: fmt::Debug> Strategy for Scoped<T> {
+    type Tree = Box<dyn ValueTree<Value = T>>;
+    type Value = T;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        self.memo.clear();
+        self.inner.new_tree(runner)
+    }
+}
+
+/// Strategy which either reuses a value from a pool or generates a new one and adds it to the
+/// pool.
+pub(crate) struct Shared<T> {
+    inner: SBoxedStrategy<T>,
+    pool: Arc<Pool<T>>,
+}
+
+impl<T> Shared<T> {
+    pub(crate) fn new(inner: SBoxedStrategy<T>, pool: Arc<Pool<T>>) -> Self {
+        Shared { inner, pool }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Shared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Shared")
+            .field("inner", &self.inner)
+            .field("sharing_probability", &self.pool.sharing_probability)
+            .finish()
+    }
+}
+
+impl<T: Clone + fmt::Debug + 'static> Strategy for Shared<T> {
+    type Tree = Box<dyn ValueTree<Value = T>>;
+    type Value = T;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let len = self.pool.values.lock().unwrap().len();
+        if len != 0
+            && proptest::bool::weighted(self.pool.sharing_probability)
+                .new_tree(runner)?
+                .current()
+        {
+            let index = (0..len).new_tree(runner)?.current();
+            let value = self.pool.values.lock().unwrap()[index].clone();
+            return Ok(Box::new(Just(value)));
+        }
+
+        let tree = self.inner.new_tree(runner)?;
+        let value = tree.current();
+        if len < MAX_POOL_SIZE {
+            self.pool.values.lock().unwrap().push(value);
+        } else {
+            let index = (0..len).new_tree(runner)?.current();
+            self.pool.values.lock().unwrap()[index] = value;
+        }
+        Ok(tree)
+    }
+}
@@ -0,0 +1,210 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::fmt;
+use core::mem;
+
+use proptest::strategy::ValueTree;
+
+/// Domain-specific shrinking for values, used by [`Recursive::simplified`](crate::Recursive::simplified).
+///
+/// Proptest shrinks values by shrinking the random choices made while generating them, which for
+/// recursive types rarely produces the simplifications a person would try first, such as
+/// replacing an expression with one of its operands. Implementing this trait supplies those
+/// simplifications directly.
+///
+/// # Examples
+///
+/// ```
+/// use proptest_recurse::Simplify;
+///
+/// #[derive(Clone, Debug)]
+/// enum Expr {
+///     Lit(i32),
+///     Add(Box<Expr>, Box<Expr>),
+/// }
+///
+/// impl Simplify for Expr {
+///     fn simplify(&self) -> Vec<Self> {
+///         match self {
+///             Expr::Lit(_) => vec![],
+///             Expr::Add(a, b) => vec![(**a).clone(), (**b).clone()],
+///         }
+///     }
+/// }
+/// ```
+pub trait Simplify: Sized {
+    /// Returns values which are simpler than this one, with the most promising first. Returns an
+    /// empty `Vec` if there are none.
+    ///
+    /// Each returned value must be strictly simpler than `self` by some measure, or shrinking may
+    /// not terminate.
+    fn simplify(&self) -> Vec<Self>;
+}
+
+/// The [`Simplify`] implementation of a recursive strategy's value type, stored as function
+/// pointers so the tower doesn't need the trait bounds.
+pub(crate) struct Simplifier<T> {
+    simplify: fn(&T) -> Vec<T>,
+    clone: fn(&T) -> T,
+}
+
+impl<T: Simplify + Clone> Simplifier<T> {
+    pub(crate) fn new() -> Self {
+        Simplifier {
+            simplify: T::simplify,
+            clone: T::clone,
+        }
+    }
+}
+
+impl<T> Simplifier<T> {
+    /// Wraps the value tree of a node so that it is also shrunk by simplification.
+    pub(crate) fn wrap(self, tree: Box<dyn ValueTree<Value = T>>) -> SimplifiedTree<T> {
+        SimplifiedTree {
+            tree,
+            simplifier: self,
+            phase: Phase::Start,
+        }
+    }
+}
+
+impl<T> Clone for Simplifier<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Simplifier<T> {}
+
+impl<T> fmt::Debug for Simplifier<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Simplifier")
+    }
+}
+
+enum Phase<T> {
+    /// Nothing has been shrunk yet.
+    Start,
+    /// Trying the simplifications of the node's original value, before shrinking its value tree.
+    Before(Candidates<T>),
+    /// Shrinking the value tree.
+    Tree,
+    /// Trying simplifications after the value tree can't be shrunk any further, or after one of
+    /// the simplifications tried before shrinking it was accepted.
+    After(Candidates<T>),
+}
+
+struct Candidates<V> {
+    /// The simplest value known to fail the test.
+    accepted: V,
+    /// The candidate currently being tested, if any.
+    trial: Option<V>,
+    /// The simplifications of `accepted`.
+    candidates: Vec<V>,
+    /// The index of the next candidate to try.
+    next: usize,
+}
+
+impl<V> Candidates<V> {
+    fn new(accepted: V, simplify: fn(&V) -> Vec<V>) -> Self {
+        Candidates {
+            candidates: simplify(&accepted),
+            accepted,
+            trial: None,
+            next: 0,
+        }
+    }
+
+    /// Accepts the previous trial if it was not rejected by `complicate`, since it still fails the
+    /// test, returning whether there was one.
+    fn accept_trial(&mut self, simplify: fn(&V) -> Vec<V>) -> bool {
+        match self.trial.take() {
+            Some(trial) => {
+                *self = Candidates::new(trial, simplify);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Starts testing the next candidate, returning whether there was one.
+    fn try_next(&mut self, clone: fn(&V) -> V) -> bool {
+        match self.candidates.get(self.next) {
+            Some(candidate) => {
+                self.trial = Some(clone(candidate));
+                self.next += 1;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Value tree for a node of a recursive strategy with a [`Simplifier`]. The simplifications of the
+/// node are tried first, so that large structural simplifications, such as replacing the node with
+/// one of its children, are found before the node's value tree shrinks its contents. If none are
+/// accepted, the value tree is shrunk, which shrinks the nested nodes in the same way, and once it
+/// can't be shrunk any further, the simplifications of its final value are tried.
+///
+/// Once a simplification has been accepted, the value tree is no longer used, so any further
+/// shrinking of the node must be done by `Simplify`.
+pub(crate) struct SimplifiedTree<T> {
+    tree: Box<dyn ValueTree<Value = T>>,
+    simplifier: Simplifier<T>,
+    phase: Phase<T>,
+}
+
+impl<T: fmt::Debug> ValueTree for SimplifiedTree<T> {
+    type Value = T;
+
+    fn current(&self) -> T {
+        match &self.phase {
+            Phase::Before(Candidates {
+                trial: Some(trial), ..
+            }) => (self.simplifier.clone)(trial),
+            Phase::After(state) => {
+                (self.simplifier.clone)(state.trial.as_ref().unwrap_or(&state.accepted))
+            }
+            _ => self.tree.current(),
+        }
+    }
+
+    fn simplify(&mut self) -> bool {
+        let Simplifier { simplify, clone } = self.simplifier;
+        loop {
+            self.phase = match mem::replace(&mut self.phase, Phase::Tree) {
+                Phase::Start => Phase::Before(Candidates::new(self.tree.current(), simplify)),
+                Phase::Before(mut state) => {
+                    if state.accept_trial(simplify) {
+                        Phase::After(state)
+                    } else if state.try_next(clone) {
+                        self.phase = Phase::Before(state);
+                        return true;
+                    } else {
+                        Phase::Tree
+                    }
+                }
+                Phase::Tree => {
+                    if self.tree.simplify() {
+                        return true;
+                    }
+                    Phase::After(Candidates::new(self.tree.current(), simplify))
+                }
+                Phase::After(mut state) => {
+                    state.accept_trial(simplify);
+                    let simplified = state.try_next(clone);
+                    self.phase = Phase::After(state);
+                    return simplified;
+                }
+            };
+        }
+    }
+
+    fn complicate(&mut self) -> bool {
+        match &mut self.phase {
+            Phase::Start => false,
+            Phase::Before(state) | Phase::After(state) => state.trial.take().is_some(),
+            Phase::Tree => self.tree.complicate(),
+        }
+    }
+}
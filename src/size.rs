@@ -0,0 +1,324 @@
+//! Accurate size accounting for recursive values.
+//!
+//! The size parameters of recursive strategies are only statistical targets, since the strategies
+//! can't see how many nodes a value actually contains. Types implementing [`TreeSize`] report
+//! their own node count, which lets [`Recursive::max_size`](crate::Recursive::max_size) enforce a
+//! hard bound and [`Recursive::track_size`](crate::Recursive::track_size) record the real
+//! distribution of sizes in a [`SizeStats`].
+//!
+//! With the `derive` feature enabled, `TreeSize` can be derived. The derived implementation counts
+//! one node for the value itself plus the node counts of all fields; fields can be excluded with
+//! `#[tree_size(skip)]`.
+
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::sync::Mutex;
+
+use proptest::strategy::{Strategy, ValueTree};
+use proptest::test_runner::{Reason, TestRunner};
+
+/// Types which can count the nodes they contain.
+pub trait TreeSize {
+    /// Returns the number of nodes in this value, including itself. Types which are not nodes,
+    /// such as containers and primitives, return the total node count of their contents.
+    fn node_count(&self) -> u64;
+}
+
+macro_rules! impl_leaf {
+    ($($ty:ty),*) => {
+        $(
+            impl TreeSize for $ty {
+                fn node_count(&self) -> u64 {
+                    0
+                }
+            }
+        )*
+    };
+}
+
+impl_leaf!(
+    (),
+    bool,
+    char,
+    u8,
+    u16,
+    u32,
+    u64,
+    u128,
+    usize,
+    i8,
+    i16,
+    i32,
+    i64,
+    i128,
+    isize,
+    f32,
+    f64,
+    String,
+    str
+);
+
+macro_rules! impl_pointer {
+    ($($ty:ident),*) => {
+        $(
+            impl<T: TreeSize + ?Sized> TreeSize for $ty<T> {
+                fn node_count(&self) -> u64 {
+                    (**self).node_count()
+                }
+            }
+        )*
+    };
+}
+
+impl_pointer!(Box, Rc, Arc);
+
+impl<T: TreeSize + ?Sized> TreeSize for &T {
+    fn node_count(&self) -> u64 {
+        (**self).node_count()
+    }
+}
+
+macro_rules! impl_collection {
+    ($($ty:ident<$($param:ident),*>),*) => {
+        $(
+            impl<$($param),*> TreeSize for $ty<$($param),*>
+            where
+                $($param: TreeSize),*
+            {
+                fn node_count(&self) -> u64 {
+                    self.iter().map(TreeSize::node_count).sum()
+                }
+            }
+        )*
+    };
+}
+
+impl_collection!(Vec<T>, VecDeque<T>, BTreeSet<T>, Option<T>);
+
+impl<T: TreeSize> TreeSize for [T] {
+    fn node_count(&self) -> u64 {
+        self.iter().map(TreeSize::node_count).sum()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: TreeSize, S> TreeSize for std::collections::HashSet<T, S> {
+    fn node_count(&self) -> u64 {
+        self.iter().map(TreeSize::node_count).sum()
+    }
+}
+
+impl<K: TreeSize, V: TreeSize> TreeSize for BTreeMap<K, V> {
+    fn node_count(&self) -> u64 {
+        self.iter()
+            .map(|(k, v)| k.node_count() + v.node_count())
+            .sum()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<K: TreeSize, V: TreeSize, S> TreeSize for std::collections::HashMap<K, V, S> {
+    fn node_count(&self) -> u64 {
+        self.iter()
+            .map(|(k, v)| k.node_count() + v.node_count())
+            .sum()
+    }
+}
+
+macro_rules! impl_tuple {
+    ($($param:ident),*) => {
+        impl<$($param: TreeSize),*> TreeSize for ($($param,)*) {
+            #[allow(non_snake_case)]
+            fn node_count(&self) -> u64 {
+                let ($($param,)*) = self;
+                0 $(+ $param.node_count())*
+            }
+        }
+    };
+}
+
+impl_tuple!(A);
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+
+/// Statistics about the node counts of generated values. This type is cheap to clone, and clones
+/// share the same statistics.
+#[derive(Clone, Debug, Default)]
+pub struct SizeStats {
+    inner: Arc<Mutex<Histogram>>,
+}
+
+#[derive(Debug, Default)]
+struct Histogram {
+    counts: BTreeMap<u64, u64>,
+}
+
+impl SizeStats {
+    /// Creates an empty set of statistics.
+    pub fn new() -> Self {
+        SizeStats::default()
+    }
+
+    pub(crate) fn record(&self, node_count: u64) {
+        *self
+            .inner
+            .lock()
+            .unwrap()
+            .counts
+            .entry(node_count)
+            .or_insert(0) += 1;
+    }
+
+    /// Returns the number of values recorded.
+    pub fn samples(&self) -> u64 {
+        self.inner.lock().unwrap().counts.values().sum()
+    }
+
+    /// Returns the smallest node count recorded.
+    pub fn min(&self) -> Option<u64> {
+        self.inner.lock().unwrap().counts.keys().next().copied()
+    }
+
+    /// Returns the largest node count recorded.
+    pub fn max(&self) -> Option<u64> {
+        self.inner
+            .lock()
+            .unwrap()
+            .counts
+            .keys()
+            .next_back()
+            .copied()
+    }
+
+    /// Returns the mean node count, or `None` if no values were recorded.
+    pub fn mean(&self) -> Option<f64> {
+        let histogram = self.inner.lock().unwrap();
+        let samples: u64 = histogram.counts.values().sum();
+        if samples == 0 {
+            return None;
+        }
+        let total: u64 = histogram
+            .counts
+            .iter()
+            .map(|(node_count, count)| node_count * count)
+            .sum();
+        Some(total as f64 / samples as f64)
+    }
+
+    /// Returns the number of values recorded for each node count.
+    pub fn histogram(&self) -> BTreeMap<u64, u64> {
+        self.inner.lock().unwrap().counts.clone()
+    }
+}
+
+/// The size accounting for the values of a recursive strategy, set with
+/// [`Recursive::max_size`](crate::Recursive::max_size) and
+/// [`Recursive::track_size`](crate::Recursive::track_size).
+pub(crate) struct SizeCheck<T> {
+    node_count: Option<fn(&T) -> u64>,
+    max_size: Option<(u64, Reason)>,
+    stats: Option<SizeStats>,
+}
+
+impl<T> SizeCheck<T> {
+    pub(crate) fn new() -> Self {
+        SizeCheck {
+            node_count: None,
+            max_size: None,
+            stats: None,
+        }
+    }
+}
+
+impl<T: TreeSize> SizeCheck<T> {
+    pub(crate) fn set_max_size(&mut self, max_size: u64) {
+        self.node_count = Some(T::node_count);
+        self.max_size = Some((
+            max_size,
+            format!("value exceeds maximum size of {} nodes", max_size).into(),
+        ));
+    }
+
+    pub(crate) fn set_stats(&mut self, stats: &SizeStats) {
+        self.node_count = Some(T::node_count);
+        self.stats = Some(stats.clone());
+    }
+}
+
+impl<T: fmt::Debug + 'static> SizeCheck<T> {
+    /// Creates a value tree from `strategy`, regenerating values over the maximum size and
+    /// recording the size of the accepted value.
+    pub(crate) fn new_tree<S>(
+        &self,
+        strategy: S,
+        runner: &mut TestRunner,
+    ) -> Result<Box<dyn ValueTree<Value = T>>, Reason>
+    where
+        S: Strategy<Tree = Box<dyn ValueTree<Value = T>>, Value = T>,
+    {
+        let node_count = match self.node_count {
+            Some(node_count) => node_count,
+            None => return strategy.new_tree(runner),
+        };
+        let tree: Box<dyn ValueTree<Value = T>> = match &self.max_size {
+            Some((max_size, whence)) => {
+                let max_size = *max_size;
+                Box::new(
+                    strategy
+                        .prop_filter(whence.clone(), move |value| node_count(value) <= max_size)
+                        .new_tree(runner)?,
+                )
+            }
+            None => strategy.new_tree(runner)?,
+        };
+        if let Some(stats) = &self.stats {
+            stats.record(node_count(&tree.current()));
+        }
+        Ok(tree)
+    }
+}
+
+impl<T> Clone for SizeCheck<T> {
+    fn clone(&self) -> Self {
+        SizeCheck {
+            node_count: self.node_count,
+            max_size: self.max_size.clone(),
+            stats: self.stats.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for SizeCheck<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SizeCheck")
+            .field(
+                "max_size",
+                &self.max_size.as_ref().map(|(max_size, _)| max_size),
+            )
+            .field("stats", &self.stats)
+            .finish()
+    }
+}
+
+impl fmt::Display for SizeStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.min(), self.max(), self.mean()) {
+            (Some(min), Some(max), Some(mean)) => write!(
+                f,
+                "{} samples, node count min {}, max {}, mean {:.1}",
+                self.samples(),
+                min,
+                max,
+                mean
+            ),
+            _ => write!(f, "no samples"),
+        }
+    }
+}
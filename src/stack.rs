@@ -0,0 +1,54 @@
+use core::ops::{Deref, DerefMut};
+
+/// The amount of stack space which must remain before recursing into the next node. Nodes are
+/// built and shrunk through the user's combinators, whose frames can be large in debug builds.
+#[cfg(feature = "std")]
+const RED_ZONE: usize = 256 * 1024;
+
+/// The size of each new stack segment allocated once the red zone is reached.
+#[cfg(feature = "std")]
+const SEGMENT_SIZE: usize = 4 * 1024 * 1024;
+
+/// Runs `f`, which recurses into a nested node, on a new heap-allocated stack segment if the
+/// current stack is close to overflowing, so that the depth of values isn't limited by the size
+/// of the thread's stack.
+#[cfg(feature = "std")]
+pub(crate) fn grow<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(RED_ZONE, SEGMENT_SIZE, f)
+}
+
+// Without `std` the stack can't be grown, so deep values are limited by the size of the stack.
+#[cfg(not(feature = "std"))]
+pub(crate) fn grow<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+/// Owns part of a value tree which may be nested arbitrarily deeply, and drops it with [`grow`].
+pub(crate) struct Nested<T>(Option<T>);
+
+impl<T> Nested<T> {
+    pub(crate) fn new(value: T) -> Self {
+        Nested(Some(value))
+    }
+}
+
+impl<T> Deref for Nested<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.0.as_ref().expect("value is only taken when dropped")
+    }
+}
+
+impl<T> DerefMut for Nested<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.0.as_mut().expect("value is only taken when dropped")
+    }
+}
+
+impl<T> Drop for Nested<T> {
+    fn drop(&mut self) {
+        let value = self.0.take();
+        grow(move || drop(value));
+    }
+}
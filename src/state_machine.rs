@@ -0,0 +1,262 @@
+//! Helpers for state machine tests.
+//!
+//! The transitions of a
+//! [`proptest-state-machine`](https://crates.io/crates/proptest-state-machine) reference state
+//! machine are generated from scratch at every step, and often need recursive arguments such as
+//! expressions or nested configuration. A [`Transitions`] caches the strategies for these
+//! arguments, and allows each transition to limit the depth of its arguments independently.
+//!
+//! With the `state-machine` feature, a type implementing `RecursiveStateMachine` can be used as a
+//! `ReferenceStateMachine` through the `Reference` adapter, whose `transitions` are given the
+//! machine's cache.
+//!
+//! # Examples
+//!
+//! ```
+//! # #[cfg(feature = "state-machine")]
+//! # mod example {
+//! use proptest::prelude::*;
+//! use proptest::strategy::{BoxedStrategy, SBoxedStrategy};
+//! use proptest_recurse::state_machine::{RecursiveStateMachine, Reference, Transitions};
+//! use proptest_recurse::{StrategyExt, StrategySet};
+//! use proptest_state_machine::{prop_state_machine, StateMachineTest};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Expr {
+//!     Num(i64),
+//!     Neg(Box<Expr>),
+//! }
+//!
+//! impl Expr {
+//!     fn eval(&self) -> i64 {
+//!         match self {
+//!             Expr::Num(value) => *value,
+//!             Expr::Neg(expr) => expr.eval().wrapping_neg(),
+//!         }
+//!     }
+//! }
+//!
+//! fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+//!     any::<i64>()
+//!         .prop_map(Expr::Num)
+//!         .prop_mutually_recursive(8, 64, 1, set, |set| {
+//!             set.get(arb_expr)
+//!                 .prop_map(|expr| Expr::Neg(Box::new(expr)))
+//!                 .sboxed()
+//!         })
+//! }
+//!
+//! #[derive(Clone, Debug)]
+//! enum Transition {
+//!     Push(Expr),
+//!     Pop,
+//! }
+//!
+//! struct Calculator;
+//!
+//! static TRANSITIONS: Transitions = Transitions::new();
+//!
+//! impl RecursiveStateMachine for Calculator {
+//!     type State = Vec<i64>;
+//!     type Transition = Transition;
+//!
+//!     fn cache() -> &'static Transitions {
+//!         &TRANSITIONS
+//!     }
+//!
+//!     fn init_state() -> BoxedStrategy<Self::State> {
+//!         Just(Vec::new()).boxed()
+//!     }
+//!
+//!     fn transitions(_: &Self::State, cache: &Transitions) -> SBoxedStrategy<Transition> {
+//!         prop_oneof![
+//!             cache.get(2, arb_expr).prop_map(Transition::Push),
+//!             Just(Transition::Pop),
+//!         ]
+//!         .sboxed()
+//!     }
+//!
+//!     fn apply(mut state: Self::State, transition: &Transition) -> Self::State {
+//!         match transition {
+//!             Transition::Push(expr) => state.push(expr.eval()),
+//!             Transition::Pop => {
+//!                 state.pop();
+//!             }
+//!         }
+//!         state
+//!     }
+//!
+//!     fn preconditions(state: &Self::State, transition: &Transition) -> bool {
+//!         !matches!(transition, Transition::Pop) || !state.is_empty()
+//!     }
+//! }
+//!
+//! struct CalculatorTest;
+//!
+//! impl StateMachineTest for CalculatorTest {
+//!     type SystemUnderTest = Vec<i64>;
+//!     type Reference = Reference<Calculator>;
+//!
+//!     fn init_test(_: &Vec<i64>) -> Vec<i64> {
+//!         Vec::new()
+//!     }
+//!
+//!     fn apply(mut stack: Vec<i64>, reference: &Vec<i64>, transition: Transition) -> Vec<i64> {
+//!         match transition {
+//!             Transition::Push(expr) => stack.push(expr.eval()),
+//!             Transition::Pop => {
+//!                 stack.pop();
+//!             }
+//!         }
+//!         assert_eq!(&stack, reference);
+//!         stack
+//!     }
+//! }
+//!
+//! prop_state_machine! {
+//!     # #![proptest_config(ProptestConfig::with_cases(16))]
+//!     fn calculator(sequential 1..20 => CalculatorTest);
+//! }
+//! # pub fn run() { calculator() }
+//! # }
+//! # #[cfg(feature = "state-machine")]
+//! # example::run();
+//! ```
+
+use crate::sync::Mutex;
+use alloc::collections::BTreeMap;
+use core::any::{Any, TypeId};
+#[cfg(feature = "state-machine")]
+use core::fmt;
+#[cfg(feature = "state-machine")]
+use core::marker::PhantomData;
+use core::mem;
+
+use proptest::strategy::SBoxedStrategy;
+#[cfg(feature = "state-machine")]
+use proptest::strategy::{BoxedStrategy, Strategy};
+
+use crate::entry::Entry;
+use crate::StrategySet;
+
+/// A cache of strategies for the arguments of state machine transitions, keyed by factory and
+/// depth limit. This can be stored in a `static`, since the transitions of a reference state
+/// machine are generated without access to any other shared state.
+#[derive(Debug, Default)]
+pub struct Transitions {
+    strategies: Mutex<BTreeMap<(TypeId, u32), Entry>>,
+}
+
+impl Transitions {
+    /// Creates an empty cache.
+    pub const fn new() -> Self {
+        Transitions {
+            strategies: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Returns a strategy for `T` created by `factory`, in which no recursive strategy exceeds a
+    /// depth of `max_depth`. Strategies are created at most once for each factory and depth limit,
+    /// so different factories for the same type can be used side by side.
+    ///
+    /// Factories are identified by their type, so a factory which captures state, such as a
+    /// closure referring to local variables, is called every time: two instances of its type may
+    /// create different strategies.
+    pub fn get<T, F>(&self, max_depth: u32, factory: F) -> SBoxedStrategy<T>
+    where
+        T: Any,
+        F: FnOnce(&mut StrategySet) -> SBoxedStrategy<T> + 'static,
+    {
+        let mut set = StrategySet {
+            max_depth: Some(max_depth),
+            ..StrategySet::default()
+        };
+        if mem::size_of::<F>() != 0 {
+            return set.get(factory);
+        }
+
+        let key = (TypeId::of::<F>(), max_depth);
+        if let Some(strategy) = self.strategies.lock().unwrap().get(&key) {
+            return strategy.expect();
+        }
+
+        let strategy = set.get(factory);
+        let mut strategies = self.strategies.lock().unwrap();
+        strategies
+            .entry(key)
+            .or_insert_with(|| Entry::new(strategy))
+            .expect()
+    }
+}
+
+/// A reference state machine whose transitions take arguments from a [`Transitions`] cache. Use
+/// it as a `ReferenceStateMachine` through [`Reference`].
+///
+/// The methods other than [`cache`](RecursiveStateMachine::cache) have the same meaning as those
+/// of `ReferenceStateMachine`.
+#[cfg(feature = "state-machine")]
+pub trait RecursiveStateMachine {
+    /// The reference state.
+    type State: Clone + fmt::Debug;
+    /// The transitions of the state machine.
+    type Transition: Clone + fmt::Debug;
+
+    /// Returns the cache passed to [`transitions`](RecursiveStateMachine::transitions), typically
+    /// a `static`.
+    fn cache() -> &'static Transitions;
+
+    /// Returns the strategy for the initial state.
+    fn init_state() -> BoxedStrategy<Self::State>;
+
+    /// Returns the strategy for the transitions from `state`, taking the strategies for their
+    /// arguments from `cache`.
+    fn transitions(state: &Self::State, cache: &Transitions) -> SBoxedStrategy<Self::Transition>;
+
+    /// Applies `transition` to `state`.
+    fn apply(state: Self::State, transition: &Self::Transition) -> Self::State;
+
+    /// Returns whether `transition` may be applied to `state`.
+    fn preconditions(state: &Self::State, transition: &Self::Transition) -> bool {
+        let _ = (state, transition);
+        true
+    }
+}
+
+/// Adapts a [`RecursiveStateMachine`] to a `ReferenceStateMachine`.
+#[cfg(feature = "state-machine")]
+pub struct Reference<M>(PhantomData<M>);
+
+#[cfg(feature = "state-machine")]
+impl<M> fmt::Debug for Reference<M> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("Reference")
+            .field(&core::any::type_name::<M>())
+            .finish()
+    }
+}
+
+#[cfg(feature = "state-machine")]
+impl<M> proptest_state_machine::ReferenceStateMachine for Reference<M>
+where
+    M: RecursiveStateMachine,
+    M::Transition: 'static,
+{
+    type State = M::State;
+    type Transition = M::Transition;
+
+    fn init_state() -> BoxedStrategy<Self::State> {
+        M::init_state()
+    }
+
+    fn transitions(state: &Self::State) -> BoxedStrategy<Self::Transition> {
+        M::transitions(state, M::cache()).boxed()
+    }
+
+    fn apply(state: Self::State, transition: &Self::Transition) -> Self::State {
+        M::apply(state, transition)
+    }
+
+    fn preconditions(state: &Self::State, transition: &Self::Transition) -> bool {
+        M::preconditions(state, transition)
+    }
+}
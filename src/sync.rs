@@ -0,0 +1,43 @@
+//! Locking for strategies shared between threads.
+//!
+//! With the `std` feature this is just `std::sync::Mutex`. Without it, the spin lock from the
+//! `spin` crate is used instead, wrapped to have the same interface, which requires the `spin`
+//! feature.
+
+#[cfg(feature = "std")]
+pub(crate) use std::sync::Mutex;
+
+#[cfg(all(not(feature = "std"), feature = "spin"))]
+pub(crate) use self::no_std::Mutex;
+
+#[cfg(not(any(feature = "std", feature = "spin")))]
+compile_error!("proptest-recurse requires either the `std` or the `spin` feature");
+
+#[cfg(all(not(feature = "std"), feature = "spin"))]
+mod no_std {
+    use core::convert::Infallible;
+    use core::fmt;
+
+    use spin::mutex::{SpinMutex, SpinMutexGuard};
+
+    #[derive(Default)]
+    pub(crate) struct Mutex<T>(SpinMutex<T>);
+
+    impl<T> Mutex<T> {
+        pub(crate) const fn new(value: T) -> Self {
+            Mutex(SpinMutex::new(value))
+        }
+
+        /// Locks the mutex, spinning until it is available. Returns a `Result` to match
+        /// `std::sync::Mutex::lock`, but never fails, since there is no poisoning.
+        pub(crate) fn lock(&self) -> Result<SpinMutexGuard<'_, T>, Infallible> {
+            Ok(self.0.lock())
+        }
+    }
+
+    impl<T> fmt::Debug for Mutex<T> {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.debug_struct("Mutex").finish_non_exhaustive()
+        }
+    }
+}
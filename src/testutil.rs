@@ -0,0 +1,151 @@
+//! Helpers for checking that strategies actually produce the values they were configured for.
+//!
+//! The parameters of recursive strategies only influence the shape of generated values
+//! statistically, and a mistake such as a branch function which rarely recurses can leave a
+//! strategy producing only shallow values without any test failing. The assertions in this module
+//! can be used in a test suite to catch such strategies early.
+//!
+//! [`sample_corpus`] generates example values outside of a `proptest!` block, for example to
+//! write golden files or seed a fuzzer.
+//!
+//! All functions use a deterministic test runner, so their results are reproducible.
+//!
+//! # Examples
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! use proptest_recurse::testutil::{assert_reaches_depth, depth_distribution};
+//! use proptest_recurse::tree::{recursive_tree, Tree};
+//! use proptest_recurse::RecursiveParams;
+//!
+//! let trees = recursive_tree(any::<u8>(), 1..4, RecursiveParams::new(4, 32, 2));
+//! assert_reaches_depth(&trees, 4, 1000, Tree::depth);
+//! println!("{:?}", depth_distribution(&trees, 1000, Tree::depth));
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::vec::Vec;
+
+use proptest::strategy::{Strategy, ValueTree};
+use proptest::test_runner::{Config, RngAlgorithm, TestRng, TestRunner};
+
+use crate::TreeSize;
+
+/// Generates `samples` values from `strategy`, and returns the number of values with each depth,
+/// as measured by `depth`.
+///
+/// # Panics
+///
+/// Panics if the strategy fails to generate a value, for example because it rejected too many
+/// values.
+pub fn depth_distribution<S, F>(strategy: &S, samples: u32, depth: F) -> BTreeMap<u32, u64>
+where
+    S: Strategy,
+    F: Fn(&S::Value) -> u32,
+{
+    distribution(strategy, samples, |value| depth(value))
+}
+
+/// Asserts that at least one of `samples` values generated from `strategy` has a depth of at least
+/// `min_depth`, as measured by `depth`.
+///
+/// # Panics
+///
+/// Panics if no value was deep enough, with a message including the depths that were generated.
+pub fn assert_reaches_depth<S, F>(strategy: &S, min_depth: u32, samples: u32, depth: F)
+where
+    S: Strategy,
+    F: Fn(&S::Value) -> u32,
+{
+    let distribution = depth_distribution(strategy, samples, depth);
+    assert_reaches("depth", min_depth, samples, &distribution);
+}
+
+/// Asserts that at least one of `samples` values generated from `strategy` has at least
+/// `min_nodes` nodes.
+///
+/// # Panics
+///
+/// Panics if no value was large enough, with a message including the node counts that were
+/// generated.
+pub fn assert_reaches_size<S>(strategy: &S, min_nodes: u64, samples: u32)
+where
+    S: Strategy,
+    S::Value: TreeSize,
+{
+    let distribution = distribution(strategy, samples, TreeSize::node_count);
+    assert_reaches("node count", min_nodes, samples, &distribution);
+}
+
+/// Generates `count` values from `strategy`, using a test runner seeded with `seed`. The same seed
+/// always produces the same values, as long as the strategy is unchanged.
+///
+/// # Examples
+///
+/// ```
+/// # use proptest::prelude::*;
+/// use proptest_recurse::testutil::sample_corpus;
+/// use proptest_recurse::tree::recursive_tree;
+/// use proptest_recurse::RecursiveParams;
+///
+/// let trees = recursive_tree(any::<u8>(), 1..4, RecursiveParams::new(4, 32, 2));
+/// for tree in sample_corpus(&trees, 10, 42) {
+///     println!("{:?}", tree);
+/// }
+/// ```
+///
+/// # Panics
+///
+/// Panics if the strategy fails to generate a value, for example because it rejected too many
+/// values.
+pub fn sample_corpus<S: Strategy>(strategy: &S, count: usize, seed: u64) -> Vec<S::Value> {
+    let mut rng_seed = [0; 32];
+    rng_seed[..8].copy_from_slice(&seed.to_le_bytes());
+    let mut runner = TestRunner::new_with_rng(
+        Config::default(),
+        TestRng::from_seed(RngAlgorithm::ChaCha, &rng_seed),
+    );
+    (0..count)
+        .map(|_| generate(strategy, &mut runner))
+        .collect()
+}
+
+fn distribution<S, K, F>(strategy: &S, samples: u32, key: F) -> BTreeMap<K, u64>
+where
+    S: Strategy,
+    K: Ord,
+    F: Fn(&S::Value) -> K,
+{
+    let mut runner = TestRunner::deterministic();
+    let mut distribution = BTreeMap::new();
+    for _ in 0..samples {
+        let value = generate(strategy, &mut runner);
+        *distribution.entry(key(&value)).or_insert(0) += 1;
+    }
+    distribution
+}
+
+fn generate<S: Strategy>(strategy: &S, runner: &mut TestRunner) -> S::Value {
+    match strategy.new_tree(runner) {
+        Ok(tree) => tree.current(),
+        Err(reason) => panic!("failed to generate a value: {}", reason),
+    }
+}
+
+fn assert_reaches<K>(measure: &str, min: K, samples: u32, distribution: &BTreeMap<K, u64>)
+where
+    K: Ord + fmt::Debug,
+{
+    let max = distribution.keys().next_back();
+    assert!(
+        max.is_some_and(|max| *max >= min),
+        "expected a value with {} at least {:?} in {} samples, but the largest was {:?} \
+         (distribution: {:?})",
+        measure,
+        min,
+        samples,
+        max,
+        distribution,
+    );
+}
@@ -0,0 +1,267 @@
+use alloc::boxed::Box;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::fmt;
+
+use proptest::strategy::{NewTree, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use crate::depth;
+use crate::guard;
+use crate::mutate;
+use crate::observer::{Event, Notify, ObserverRef};
+use crate::replay;
+use crate::shape::{sibling_stack, LevelTree, Parent, SiblingStack};
+use crate::simplify::Simplifier;
+use crate::stack;
+use crate::{RecursiveParams, Shape};
+
+/// The levels of a recursive strategy for a single value, from the outermost inwards.
+///
+/// Rather than nesting a strategy for each level inside the next, which makes generating and
+/// dropping the strategy recurse once per level, the levels are stored in a flat list. The nested
+/// strategy passed to each level's branch function refers back to the list weakly, so the list
+/// doesn't own itself.
+pub(crate) struct Tower<T> {
+    levels: Vec<TowerLevel<T>>,
+    base: SBoxedStrategy<T>,
+    /// Used instead of `base` once all levels have been recursed into, if set.
+    exhausted_leaf: Option<SBoxedStrategy<T>>,
+    /// Wraps the value tree of every node, if set.
+    simplifier: Option<Simplifier<T>>,
+    shape: Shape,
+    siblings: SiblingStack,
+    observer: Option<ObserverRef>,
+}
+
+struct TowerLevel<T> {
+    /// The number of levels of the recursive strategy inside this one. Unlike the index of the
+    /// level in the tower, this doesn't depend on which levels were left out because they never
+    /// recurse, and unlike the level itself, it stays the same if the depth limit changes.
+    height: u32,
+    branch_probability: f64,
+    /// The strategy for nodes that recurse at this level, or `None` if no branch alternatives are
+    /// allowed here, in which case nodes always fall through to the next level.
+    branch: Option<SBoxedStrategy<T>>,
+}
+
+impl<T: fmt::Debug + 'static> Tower<T> {
+    /// Builds a tower from the branch probability of each of the levels allowed by `params`,
+    /// outermost first, leaving out any levels which aren't listed. `recurse` is called with each
+    /// level and the strategy for nodes nested inside that level, starting with the innermost
+    /// level, and returns the strategy for nodes that recurse at that level, if recursing there is
+    /// allowed.
+    pub(crate) fn build<F>(
+        base: SBoxedStrategy<T>,
+        exhausted_leaf: Option<SBoxedStrategy<T>>,
+        simplifier: Option<Simplifier<T>>,
+        params: &RecursiveParams,
+        branch_probabilities: &[(u32, f64)],
+        mut recurse: F,
+    ) -> Arc<Self>
+    where
+        F: FnMut(u32, SBoxedStrategy<T>) -> Option<SBoxedStrategy<T>>,
+    {
+        let (depth, shape, observer) = (params.depth, params.shape, params.observer.clone());
+        Arc::new_cyclic(|tower| {
+            let siblings = sibling_stack();
+            let notify_leaf = |leaf: SBoxedStrategy<T>| match &observer {
+                Some(observer) => Notify::new(leaf, observer.clone(), Event::Leaf).sboxed(),
+                None => leaf,
+            };
+            let base = notify_leaf(base);
+            let exhausted_leaf = exhausted_leaf.map(notify_leaf);
+
+            let mut levels = Vec::with_capacity(branch_probabilities.len());
+            for (index, &(level, branch_probability)) in
+                branch_probabilities.iter().enumerate().rev()
+            {
+                let nested = Levels {
+                    tower: TowerRef::Weak(Weak::clone(tower)),
+                    start: index + 1,
+                    base: base.clone(),
+                };
+                let branch = recurse(level, nested.sboxed()).map(|mut branch| {
+                    if let Some(observer) = &observer {
+                        branch =
+                            Notify::new(branch, observer.clone(), Event::Branch(level)).sboxed();
+                    }
+                    if !shape.is_independent() {
+                        branch = Parent::new(branch, siblings.clone()).sboxed();
+                    }
+                    branch
+                });
+                levels.push(TowerLevel {
+                    height: depth - 1 - level,
+                    // Clamp the maximum branch probability to 0.9 to ensure we can
+                    // generate non-recursive cases reasonably often.
+                    branch_probability: branch_probability.min(0.9),
+                    branch,
+                });
+            }
+            levels.reverse();
+
+            Tower {
+                levels,
+                base,
+                exhausted_leaf,
+                simplifier,
+                shape,
+                siblings,
+                observer,
+            }
+        })
+    }
+
+    /// Returns the strategy for the outermost level.
+    pub(crate) fn strategy(self: Arc<Self>) -> Levels<T> {
+        self.levels_from(0)
+    }
+
+    fn levels_from(self: Arc<Self>, start: usize) -> Levels<T> {
+        Levels {
+            base: self.base.clone(),
+            tower: TowerRef::Strong(self),
+            start,
+        }
+    }
+
+    fn new_tree(self: Arc<Self>, start: usize, runner: &mut TestRunner) -> NewTree<Levels<T>> {
+        guard::node();
+        let exhausted = start != 0 && start == self.levels.len();
+        if exhausted {
+            if let Some(observer) = &self.observer {
+                observer.0.on_depth_exhausted();
+            }
+        }
+
+        let chosen = replay::decide(
+            runner,
+            |runner| {
+                for (index, level) in self.levels.iter().enumerate().skip(start) {
+                    if level.branch.is_some()
+                        && self
+                            .shape
+                            .choose(runner, &self.siblings, level.branch_probability)?
+                    {
+                        return Ok(Some(index));
+                    }
+                }
+                Ok(None)
+            },
+            |index| self.levels[index].height,
+            |level| self.replayed_level(start, level),
+        )?;
+
+        let regenerate = Arc::clone(&self).levels_from(start).sboxed();
+        let tree: Box<dyn ValueTree<Value = T>> = match chosen {
+            Some(index) => {
+                let branch = match &self.levels[index].branch {
+                    Some(branch) => branch.new_tree(runner)?,
+                    None => return Err("level has no allowed branch alternatives".into()),
+                };
+                let leaf = Arc::clone(&self).levels_from(index + 1).sboxed();
+                Box::new(LevelTree::new(branch, leaf, regenerate, runner))
+            }
+            None => {
+                let leaf = match &self.exhausted_leaf {
+                    Some(exhausted_leaf) if exhausted => exhausted_leaf.new_tree(runner)?,
+                    _ => self.base.new_tree(runner)?,
+                };
+                Box::new(LeafTree {
+                    tree: leaf,
+                    regenerate,
+                })
+            }
+        };
+        match self.simplifier {
+            Some(simplifier) => Ok(Box::new(simplifier.wrap(tree))),
+            None => Ok(tree),
+        }
+    }
+}
+
+impl<T> Tower<T> {
+    /// Returns the index of the level a replayed node recurses at, given the height of the level
+    /// it recursed at when it was recorded and the index of the outermost level it may recurse at.
+    /// This is the level with the recorded height if it is still available, or the next level
+    /// inwards if not, falling back to the outermost level available if the depth limit has been
+    /// lowered so that none of those are.
+    fn replayed_level(&self, start: usize, height: u32) -> Option<usize> {
+        let mut available =
+            (start..self.levels.len()).filter(|&index| self.levels[index].branch.is_some());
+        available
+            .clone()
+            .find(|&index| self.levels[index].height <= height)
+            .or_else(|| available.next())
+    }
+}
+
+enum TowerRef<T> {
+    Strong(Arc<Tower<T>>),
+    Weak(Weak<Tower<T>>),
+}
+
+/// Strategy for the nodes generated by a tower, starting at one of its levels. Each level either
+/// recurses, or falls through to the next level, until the base strategy is reached.
+pub(crate) struct Levels<T> {
+    tower: TowerRef<T>,
+    start: usize,
+    base: SBoxedStrategy<T>,
+}
+
+impl<T> fmt::Debug for Levels<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Levels")
+            .field("start", &self.start)
+            .finish()
+    }
+}
+
+impl<T: fmt::Debug + 'static> Strategy for Levels<T> {
+    type Tree = Box<dyn ValueTree<Value = T>>;
+    type Value = T;
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let tower = match &self.tower {
+            TowerRef::Strong(tower) => Some(Arc::clone(tower)),
+            TowerRef::Weak(tower) => tower.upgrade(),
+        };
+        match tower {
+            // Each node's branch generates the nodes nested inside it through this method, so
+            // this is where generation recurses once per level.
+            Some(tower) => stack::grow(|| tower.new_tree(self.start, runner)),
+            // The nested strategy outlived the value it was created for, for example by being
+            // stored in a set which was kept, so there are no levels left to recurse into.
+            None => Ok(Box::new(LeafTree {
+                tree: self.base.new_tree(runner)?,
+                regenerate: self.base.clone(),
+            })),
+        }
+    }
+}
+
+/// Value tree for a node generated by the base strategy.
+struct LeafTree<T> {
+    tree: Box<dyn ValueTree<Value = T>>,
+    regenerate: SBoxedStrategy<T>,
+}
+
+impl<T: fmt::Debug> ValueTree for LeafTree<T> {
+    type Value = T;
+
+    fn current(&self) -> T {
+        mutate::node(&self.regenerate, || {
+            depth::leaf();
+            self.tree.current()
+        })
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.tree.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.tree.complicate()
+    }
+}
@@ -0,0 +1,154 @@
+//! Ready-made recursive value types.
+//!
+//! When a test just needs "a tree of `T`" or "an expression over `T`", defining a dedicated enum
+//! and strategy factory is unnecessary boilerplate. The [`Tree`] and [`Expr`] types cover these
+//! cases, with strategies returned by [`recursive_tree`] and [`recursive_expr`].
+//!
+//! # Examples
+//!
+//! ```
+//! # use proptest::prelude::*;
+//! use proptest_recurse::tree::{recursive_expr, recursive_tree};
+//! use proptest_recurse::RecursiveParams;
+//!
+//! let trees = recursive_tree(any::<u8>(), 0..4, RecursiveParams::new(4, 32, 2));
+//! let exprs = recursive_expr(
+//!     any::<i32>(),
+//!     Just('-'),
+//!     prop_oneof![Just('+'), Just('*')],
+//!     RecursiveParams::new(6, 64, 2),
+//! );
+//! # let _ = (trees, exprs);
+//! ```
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::any::Any;
+
+use proptest::collection::{vec, SizeRange};
+use proptest::prelude::*;
+use proptest::strategy::SBoxedStrategy;
+
+use crate::{RecursiveParams, StrategyExt, StrategySet, TreeSize};
+
+/// A tree with values of type `T` at its leaves.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Tree<T> {
+    /// A leaf containing a single value.
+    Leaf(T),
+    /// An inner node with any number of children.
+    Node(Vec<Tree<T>>),
+}
+
+impl<T> Tree<T> {
+    /// Returns the number of inner nodes on the longest path from the root to a leaf.
+    pub fn depth(&self) -> u32 {
+        match self {
+            Tree::Leaf(_) => 0,
+            Tree::Node(children) => 1 + children.iter().map(Tree::depth).max().unwrap_or(0),
+        }
+    }
+}
+
+impl<T> TreeSize for Tree<T> {
+    fn node_count(&self) -> u64 {
+        match self {
+            Tree::Leaf(_) => 1,
+            Tree::Node(children) => 1 + children.iter().map(TreeSize::node_count).sum::<u64>(),
+        }
+    }
+}
+
+/// An expression tree with leaves of type `L`, unary operators of type `U` and binary operators of
+/// type `B`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Expr<L, U, B> {
+    /// A leaf, such as a literal or variable.
+    Leaf(L),
+    /// A unary operator applied to an operand.
+    Unary(U, Box<Expr<L, U, B>>),
+    /// A binary operator applied to a pair of operands.
+    Binary(B, Box<Expr<L, U, B>>, Box<Expr<L, U, B>>),
+}
+
+impl<L, U, B> Expr<L, U, B> {
+    /// Returns the number of operators on the longest path from the root to a leaf.
+    pub fn depth(&self) -> u32 {
+        match self {
+            Expr::Leaf(_) => 0,
+            Expr::Unary(_, operand) => 1 + operand.depth(),
+            Expr::Binary(_, left, right) => 1 + left.depth().max(right.depth()),
+        }
+    }
+}
+
+impl<L, U, B> TreeSize for Expr<L, U, B> {
+    fn node_count(&self) -> u64 {
+        match self {
+            Expr::Leaf(_) => 1,
+            Expr::Unary(_, operand) => 1 + operand.node_count(),
+            Expr::Binary(_, left, right) => 1 + left.node_count() + right.node_count(),
+        }
+    }
+}
+
+/// Returns a strategy for trees with leaves generated by `leaf`, and inner nodes with a number of
+/// children in the range `children`.
+pub fn recursive_tree<S>(
+    leaf: S,
+    children: impl Into<SizeRange>,
+    params: RecursiveParams,
+) -> SBoxedStrategy<Tree<S::Value>>
+where
+    S: Strategy + Send + Sync + 'static,
+    S::Value: Any,
+{
+    let children = children.into();
+    leaf.prop_map(Tree::Leaf).prop_mutually_recursive_with(
+        params,
+        &StrategySet::default(),
+        move |set| {
+            vec(
+                set.get::<Tree<S::Value>, _>(|_| unreachable!()),
+                children.clone(),
+            )
+            .prop_map(Tree::Node)
+            .sboxed()
+        },
+    )
+}
+
+/// Returns a strategy for expressions with leaves generated by `leaves` and operators generated by
+/// `unary_ops` and `binary_ops`.
+pub fn recursive_expr<SL, SU, SB>(
+    leaves: SL,
+    unary_ops: SU,
+    binary_ops: SB,
+    params: RecursiveParams,
+) -> SBoxedStrategy<Expr<SL::Value, SU::Value, SB::Value>>
+where
+    SL: Strategy + Send + Sync + 'static,
+    SU: Strategy + Send + Sync + 'static,
+    SB: Strategy + Send + Sync + 'static,
+    SL::Value: Any,
+    SU::Value: Any,
+    SB::Value: Any,
+{
+    let unary_ops = unary_ops.sboxed();
+    let binary_ops = binary_ops.sboxed();
+    leaves.prop_map(Expr::Leaf).prop_mutually_recursive_with(
+        params,
+        &StrategySet::default(),
+        move |set| {
+            let expr = set.get::<Expr<SL::Value, SU::Value, SB::Value>, _>(|_| unreachable!());
+            prop_oneof![
+                (unary_ops.clone(), expr.clone())
+                    .prop_map(|(op, operand)| Expr::Unary(op, Box::new(operand))),
+                (binary_ops.clone(), expr.clone(), expr).prop_map(|(op, left, right)| {
+                    Expr::Binary(op, Box::new(left), Box::new(right))
+                }),
+            ]
+            .sboxed()
+        },
+    )
+}
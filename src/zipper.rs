@@ -0,0 +1,173 @@
+//! Generating a value together with a focus point inside it.
+//!
+//! Code which traverses or rewrites recursive values, such as a zipper or a tree-rewriting pass,
+//! often needs a position in the value as well as the value itself. Picking a position after the
+//! fact requires a separate traversal for each type. Instead, [`with_path`] records the nodes
+//! built by recursive strategies while the value is constructed, and returns a [`Path`] to one of
+//! them, chosen at random.
+//!
+//! A path is a sequence of child indices, starting from the outermost node. The children of a node
+//! are the nodes built by recursive strategies, of any type, while building it, numbered in the
+//! order they were built. For branch functions which combine their nested strategies with tuples
+//! or collections and `prop_map`, this is the order of the fields and elements of the node, so
+//! `Node(vec![a, b, c])` has children `0`, `1` and `2`. Leaves generated by the base strategy of a
+//! recursive strategy are nodes too, but values generated by other strategies, such as literals,
+//! are not.
+//!
+//! The path is computed from the current value whenever it is produced, so it stays valid while
+//! the value shrinks.
+//!
+//! # Examples
+//!
+//! ```
+//! # use proptest::collection::vec;
+//! # use proptest::prelude::*;
+//! # use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+//! # use proptest::test_runner::TestRunner;
+//! use proptest_recurse::zipper::with_path;
+//! use proptest_recurse::{StrategyExt, StrategySet};
+//!
+//! #[derive(Clone, Debug)]
+//! enum Tree {
+//!     Leaf,
+//!     Node(Vec<Tree>),
+//! }
+//!
+//! impl Tree {
+//!     fn get(&self, path: &[usize]) -> Option<&Tree> {
+//!         match (self, path) {
+//!             (_, []) => Some(self),
+//!             (Tree::Node(children), [index, rest @ ..]) => children.get(*index)?.get(rest),
+//!             (Tree::Leaf, _) => None,
+//!         }
+//!     }
+//! }
+//!
+//! fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+//!     Just(Tree::Leaf).prop_mutually_recursive(4, 32, 4, set, |set| {
+//!         vec(set.get(arb_tree), 0..4).prop_map(Tree::Node).sboxed()
+//!     })
+//! }
+//!
+//! let (tree, path) = with_path(arb_tree(&mut StrategySet::default()))
+//!     .new_tree(&mut TestRunner::default())
+//!     .unwrap()
+//!     .current();
+//! assert!(tree.get(path.indices()).is_some());
+//! ```
+
+use std::fmt;
+use std::vec::Vec;
+
+use proptest::prelude::*;
+use proptest::strategy::{NewTree, SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use crate::depth::{self, TracedNode};
+
+/// A sequence of child indices leading from the outermost node of a value to one of the nodes
+/// inside it. The empty path refers to the outermost node itself.
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path {
+    indices: Vec<usize>,
+}
+
+impl Path {
+    /// Returns the index of each child on the path, outermost first.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Returns `true` if this path refers to the outermost node.
+    pub fn is_root(&self) -> bool {
+        self.indices.is_empty()
+    }
+
+    /// Returns the path to the parent of the node this path refers to, or `None` for the
+    /// outermost node.
+    pub fn parent(&self) -> Option<Path> {
+        let (_, parent) = self.indices.split_last()?;
+        Some(Path {
+            indices: parent.to_vec(),
+        })
+    }
+}
+
+impl fmt::Debug for Path {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(&self.indices).finish()
+    }
+}
+
+/// Wraps `strategy` so that each value is generated along with a [`Path`] to one of its nodes,
+/// chosen uniformly at random. See the [module documentation](self) for details.
+///
+/// Only the first outermost node built is considered, so `strategy` should generate values whose
+/// outermost node is built by a recursive strategy. If it builds no nodes at all, the path is
+/// empty.
+pub fn with_path<S>(strategy: S) -> SBoxedStrategy<(S::Value, Path)>
+where
+    S: Strategy + Send + Sync + 'static,
+    S::Tree: 'static,
+{
+    WithPath(strategy).sboxed()
+}
+
+#[derive(Debug)]
+struct WithPath<S>(S);
+
+impl<S: Strategy> Strategy for WithPath<S> {
+    type Tree = WithPathTree<S::Tree>;
+    type Value = (S::Value, Path);
+
+    fn new_tree(&self, runner: &mut TestRunner) -> NewTree<Self> {
+        let tree = self.0.new_tree(runner)?;
+        let choice = any::<u64>().new_tree(runner)?.current();
+        Ok(WithPathTree { tree, choice })
+    }
+}
+
+struct WithPathTree<T> {
+    tree: T,
+    /// Selects the focused node, modulo the number of nodes in the current value.
+    choice: u64,
+}
+
+impl<T: ValueTree> ValueTree for WithPathTree<T> {
+    type Value = (T::Value, Path);
+
+    fn current(&self) -> Self::Value {
+        let (value, nodes) = depth::trace(|| self.tree.current());
+        (value, choose_path(&nodes, self.choice))
+    }
+
+    fn simplify(&mut self) -> bool {
+        self.tree.simplify()
+    }
+
+    fn complicate(&mut self) -> bool {
+        self.tree.complicate()
+    }
+}
+
+fn choose_path(nodes: &[TracedNode], choice: u64) -> Path {
+    // Nodes are recorded in the order they were started, so the descendants of the first node
+    // immediately follow it.
+    let count = nodes
+        .iter()
+        .skip(1)
+        .position(|node| node.parent.is_none())
+        .map_or(nodes.len(), |position| position + 1);
+    if count == 0 {
+        return Path::default();
+    }
+
+    let mut indices = Vec::new();
+    let mut node = (choice % count as u64) as usize;
+    while let Some(parent) = nodes[node].parent {
+        indices.push(nodes[node].index);
+        node = parent;
+    }
+    indices.reverse();
+    Path { indices }
+}
@@ -0,0 +1,80 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::{Just, SBoxedStrategy, Union, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{balanced_oneof, StrategyExt, StrategySet};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Common,
+    Rare,
+}
+
+fn alternatives() -> Vec<(u32, SBoxedStrategy<Op>)> {
+    vec![
+        (100, Just(Op::Common).sboxed()),
+        (1, Just(Op::Rare).sboxed()),
+    ]
+}
+
+fn count_rare(strategy: &SBoxedStrategy<Op>) -> usize {
+    let mut runner = TestRunner::deterministic();
+    (0..1000)
+        .filter(|_| strategy.new_tree(&mut runner).unwrap().current() == Op::Rare)
+        .count()
+}
+
+#[test]
+fn boosts_rare_alternatives() {
+    let unbalanced = count_rare(&Union::new_weighted(alternatives()).sboxed());
+    let balanced = count_rare(&balanced_oneof(alternatives()));
+
+    assert!(unbalanced < 50, "{}", unbalanced);
+    assert!(balanced > 100, "{}", balanced);
+    assert!(balanced < 500, "{}", balanced);
+}
+
+#[test]
+fn counts_shared_between_clones() {
+    let strategy = balanced_oneof(alternatives());
+    let first = count_rare(&strategy.clone());
+    let second = count_rare(&strategy);
+    assert!(first > 100 && second > 100, "{} {}", first, second);
+}
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf(Op),
+    Node(Vec<Tree>),
+}
+
+#[test]
+fn in_branch() {
+    let strategy = balanced_oneof(alternatives())
+        .prop_map(Tree::Leaf)
+        .prop_mutually_recursive(3, 32, 4, &StrategySet::default(), |set| {
+            vec(set.get::<Tree, _>(|_| unreachable!()), 0..4)
+                .prop_map(Tree::Node)
+                .sboxed()
+        });
+
+    fn count(tree: &Tree) -> usize {
+        match tree {
+            Tree::Leaf(op) => (*op == Op::Rare) as usize,
+            Tree::Node(children) => children.iter().map(count).sum(),
+        }
+    }
+
+    let mut runner = TestRunner::deterministic();
+    let rare: usize = (0..200)
+        .map(|_| count(&strategy.new_tree(&mut runner).unwrap().current()))
+        .sum();
+    assert!(rare > 20, "{}", rare);
+}
+
+#[test]
+#[should_panic(expected = "weights must be positive")]
+fn zero_weight() {
+    let _ = balanced_oneof(vec![(0, Just(Op::Rare).sboxed())]);
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "std")]
+
+use std::collections::{BTreeMap, HashMap};
+
+use proptest::strategy::{Just, SBoxedStrategy};
+use proptest::{prelude::*, prop_oneof, proptest};
+
+use proptest_recurse::collection::{recursive_btree_map, recursive_hash_map, recursive_vec};
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    /// Checks that nodes at each depth have at most `max_children(depth)` children.
+    fn check(&self, depth: usize, max_children: &dyn Fn(usize) -> usize) -> bool {
+        match self {
+            Tree::Leaf => true,
+            Tree::Node(children) => {
+                children.len() <= max_children(depth)
+                    && children
+                        .iter()
+                        .all(|child| child.check(depth + 1, max_children))
+            }
+        }
+    }
+}
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive(4, 256, 8, set, |set| {
+        recursive_vec(set, arb_tree, 0..=8)
+            .prop_map(Tree::Node)
+            .sboxed()
+    })
+}
+
+#[derive(Clone, Debug)]
+enum Value {
+    Null,
+    Object(BTreeMap<String, Value>),
+    Table(HashMap<u8, Value>),
+}
+
+impl Value {
+    fn depth(&self) -> u32 {
+        match self {
+            Value::Null => 0,
+            Value::Object(entries) => 1 + entries.values().map(Value::depth).max().unwrap_or(0),
+            Value::Table(entries) => 1 + entries.values().map(Value::depth).max().unwrap_or(0),
+        }
+    }
+}
+
+fn arb_value(set: &mut StrategySet) -> SBoxedStrategy<Value> {
+    Just(Value::Null).prop_mutually_recursive(3, 32, 4, set, |set| {
+        prop_oneof![
+            recursive_btree_map(set, "[a-z]{1,4}", arb_value, 0..4).prop_map(Value::Object),
+            recursive_hash_map(set, any::<u8>(), arb_value, 0..4).prop_map(Value::Table),
+        ]
+        .sboxed()
+    })
+}
+
+proptest! {
+    #[test]
+    fn scaled(tree in arb_tree(&mut StrategySet::default())) {
+        // With depth 4, the maximum length of 8 is scaled by 4/4, 3/4, 2/4 and 1/4.
+        prop_assert!(tree.check(0, &|depth| [8, 6, 4, 2][depth]));
+    }
+
+    #[test]
+    fn maps(value in arb_value(&mut StrategySet::default())) {
+        prop_assert!(value.depth() <= 3);
+    }
+}
+
+#[test]
+fn unscaled_outside_branch() {
+    let mut set = StrategySet::default();
+    let strategy = recursive_vec(&mut set, arb_tree, 8..=8);
+    let mut runner = proptest::test_runner::TestRunner::default();
+    let value = strategy.new_tree(&mut runner).unwrap().current();
+    assert_eq!(value.len(), 8);
+}
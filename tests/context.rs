@@ -0,0 +1,109 @@
+use std::collections::BTreeSet;
+
+use proptest::prelude::*;
+use proptest::sample::select;
+use proptest::strategy::{SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::context::recursive_in_context;
+
+#[derive(Clone, Debug)]
+enum Stmt {
+    Print(String),
+    Let(String, Vec<Stmt>),
+}
+
+impl Stmt {
+    /// Returns `true` if every name printed is bound by an enclosing `Let`.
+    fn is_well_scoped(&self, scope: &BTreeSet<String>) -> bool {
+        match self {
+            Stmt::Print(name) => scope.contains(name),
+            Stmt::Let(name, body) => {
+                let mut scope = scope.clone();
+                scope.insert(name.clone());
+                body.iter().all(|stmt| stmt.is_well_scoped(&scope))
+            }
+        }
+    }
+
+    fn depth(&self) -> u32 {
+        match self {
+            Stmt::Print(_) => 0,
+            Stmt::Let(_, body) => 1 + body.iter().map(Stmt::depth).max().unwrap_or(0),
+        }
+    }
+}
+
+fn arb_stmt() -> SBoxedStrategy<Stmt> {
+    recursive_in_context(
+        vec!["root".to_owned()],
+        5,
+        32,
+        3,
+        |scope: &Vec<String>| select(scope.clone()).prop_map(Stmt::Print).sboxed(),
+        |scope, subtree| {
+            let name = format!("x{}", scope.len());
+            let mut inner = scope.clone();
+            inner.push(name.clone());
+            proptest::collection::vec(subtree.get(inner), 1..4)
+                .prop_map(move |body| Stmt::Let(name.clone(), body))
+                .sboxed()
+        },
+    )
+}
+
+fn root_scope() -> BTreeSet<String> {
+    std::iter::once("root".to_owned()).collect()
+}
+
+#[test]
+fn well_scoped() {
+    let strategy = arb_stmt();
+
+    let mut runner = TestRunner::deterministic();
+    let mut max_depth = 0;
+    for _ in 0..200 {
+        let stmt = strategy.new_tree(&mut runner).unwrap().current();
+        assert!(stmt.is_well_scoped(&root_scope()), "{:?}", stmt);
+        assert!(stmt.depth() <= 5);
+        max_depth = max_depth.max(stmt.depth());
+    }
+    assert!(max_depth >= 2);
+}
+
+#[test]
+fn uses_inner_bindings() {
+    let strategy = arb_stmt();
+
+    let mut runner = TestRunner::deterministic();
+    let found = (0..200).any(|_| {
+        let stmt = strategy.new_tree(&mut runner).unwrap().current();
+        format!("{:?}", stmt).contains("Print(\"x1\")")
+    });
+    assert!(found);
+}
+
+#[test]
+fn well_scoped_while_shrinking() {
+    let strategy = arb_stmt();
+
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..20 {
+        let mut tree = strategy.new_tree(&mut runner).unwrap();
+        while tree.simplify() {
+            assert!(tree.current().is_well_scoped(&root_scope()));
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn measured_by_with_depth() {
+    let strategy = proptest_recurse::with_depth(arb_stmt());
+
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..100 {
+        let stmt = strategy.new_tree(&mut runner).unwrap().current();
+        assert_eq!(stmt.depth, stmt.value.depth());
+    }
+}
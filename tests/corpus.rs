@@ -0,0 +1,65 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive(3, 16, 2, set, |set| {
+        vec(set.get(arb_tree), 0..2).prop_map(Tree::Node).sboxed()
+    })
+}
+
+/// A tree which the strategy can't generate, since it has more children than allowed.
+fn example() -> Tree {
+    Tree::Node(vec![Tree::Leaf; 5])
+}
+
+fn contains_example(tree: &Tree) -> bool {
+    *tree == example()
+        || match tree {
+            Tree::Leaf => false,
+            Tree::Node(children) => children.iter().any(contains_example),
+        }
+}
+
+#[test]
+fn corpus() {
+    let mut set = StrategySet::default();
+    set.with_corpus(vec![example()]);
+    let strategy = set.get(arb_tree);
+
+    let mut runner = TestRunner::deterministic();
+    let (mut whole, mut nested) = (0, 0);
+    for _ in 0..1000 {
+        let tree = strategy.new_tree(&mut runner).unwrap().current();
+        if tree == example() {
+            whole += 1;
+        } else if contains_example(&tree) {
+            nested += 1;
+        }
+    }
+
+    assert!((150..350).contains(&whole), "{}", whole);
+    assert!(nested > 0);
+}
+
+#[test]
+fn empty_corpus() {
+    let mut set = StrategySet::default();
+    set.with_corpus(Vec::<Tree>::new());
+    let strategy = set.get(arb_tree);
+
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..100 {
+        let tree = strategy.new_tree(&mut runner).unwrap().current();
+        assert!(!contains_example(&tree));
+    }
+}
@@ -0,0 +1,56 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy};
+use proptest::test_runner::TestRunner;
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    fn depth(&self) -> u32 {
+        match self {
+            Tree::Leaf => 0,
+            Tree::Node(children) => children.iter().map(Tree::depth).max().unwrap_or(0) + 1,
+        }
+    }
+}
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive(6, 64, 2, set, |set| {
+        vec(set.get::<Tree, _>(arb_tree), 1..3)
+            .prop_map(Tree::Node)
+            .sboxed()
+    })
+}
+
+proptest! {
+    #[test]
+    fn shares_depth_budget((a, b) in StrategySet::default().correlated(arb_tree, arb_tree)) {
+        // Each depth is rounded separately, so the total may exceed the budget by one.
+        prop_assert!(a.depth() + b.depth() <= 7);
+    }
+}
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn counted_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    CALLS.fetch_add(1, Ordering::SeqCst);
+    arb_tree(set)
+}
+
+#[test]
+fn factories_called_once_per_split() {
+    let strategy = StrategySet::default().correlated(counted_tree, counted_tree);
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..1000 {
+        strategy.new_tree(&mut runner).unwrap();
+    }
+    assert!(CALLS.load(Ordering::SeqCst) <= 2 * 101);
+}
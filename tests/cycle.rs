@@ -0,0 +1,76 @@
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy};
+use proptest::{prelude::*, prop_oneof, proptest};
+
+use proptest_recurse::cycle::{Ancestors, BackRef};
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Edge {
+    Child(Tree),
+    Back(BackRef<Tree>),
+}
+
+#[derive(Clone, Debug)]
+struct Tree(Vec<Edge>);
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    Just(Tree(vec![])).prop_mutually_recursive(4, 16, 2, set, |set| {
+        let edge = prop_oneof![
+            set.get::<Tree, _>(arb_tree).prop_map(Edge::Child),
+            set.back_ref::<Tree>().prop_map(Edge::Back),
+        ];
+        vec(edge, 0..3).prop_map(Tree).sboxed()
+    })
+}
+
+/// Flattens the tree into a list of nodes, recording each node's parent and back edges.
+fn materialize(
+    tree: &Tree,
+    parent: Option<usize>,
+    ancestors: &mut Ancestors<usize>,
+    nodes: &mut Vec<(Option<usize>, Vec<usize>)>,
+) {
+    let index = nodes.len();
+    nodes.push((parent, vec![]));
+    ancestors.push(index);
+    for edge in &tree.0 {
+        match edge {
+            Edge::Child(child) => materialize(child, Some(index), ancestors, nodes),
+            Edge::Back(back_ref) => {
+                let target = *ancestors.resolve(*back_ref);
+                nodes[index].1.push(target);
+            }
+        }
+    }
+    ancestors.pop();
+}
+
+proptest! {
+    #[test]
+    fn back_edges_point_to_ancestors(tree in arb_tree(&mut StrategySet::default())) {
+        let mut nodes = vec![];
+        materialize(&tree, None, &mut Ancestors::new(), &mut nodes);
+
+        for (index, (_, back_edges)) in nodes.iter().enumerate() {
+            for &target in back_edges {
+                let mut current = Some(index);
+                while current != Some(target) {
+                    current = nodes[current.unwrap()].0;
+                    prop_assert!(current.is_some());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn resolve_wraps() {
+    let mut ancestors = Ancestors::new();
+    ancestors.push('a');
+    ancestors.push('b');
+
+    assert_eq!(*ancestors.resolve(BackRef::<()>::new(0)), 'b');
+    assert_eq!(*ancestors.resolve(BackRef::<()>::new(1)), 'a');
+    assert_eq!(*ancestors.resolve(BackRef::<()>::new(2)), 'b');
+}
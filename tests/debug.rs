@@ -0,0 +1,56 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::Just;
+
+use proptest_recurse::{strategy_set, RecursiveParams, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum First {
+    Zero,
+    Second,
+}
+
+#[derive(Clone, Debug)]
+enum Second {
+    Zero,
+    First,
+}
+
+strategy_set! {
+    fn strategies();
+
+    fn arb_first: First => {
+        base: Just(First::Zero),
+        branch(set) => vec(set.get::<Second, _>(arb_second), 0..8).prop_map(|_| First::Second),
+        params: (5, 32, 8),
+    }
+
+    fn arb_second: Second => {
+        base: Just(Second::Zero),
+        branch(set) => set.get::<First, _>(arb_first).prop_map(|_| Second::First),
+        params: (3, 16, 1),
+    }
+}
+
+#[test]
+fn debug() {
+    assert_eq!(
+        format!("{:?}", StrategySet::default()),
+        "StrategySet { .. }"
+    );
+
+    let mut set = strategies();
+    let _ = set.get::<Vec<u8>, _>(|_| vec(any::<u8>(), 0..4).sboxed());
+    let _ = set.get::<Option<First>, _>(|set| {
+        Just(None).prop_mutually_recursive_with(
+            RecursiveParams::new(2, 8, 1).target_size(1..=8),
+            set,
+            |set| set.get(arb_first).prop_map(Some).sboxed(),
+        )
+    });
+    assert_eq!(
+        format!("{:?}", set),
+        "StrategySet { First (depth=5, size=32), Option<First> (depth=2, size=1..=8), \
+         Second (depth=3, size=16), Vec<u8>, .. }"
+    );
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "std")]
+
+use std::ops::RangeInclusive;
+use std::thread;
+
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{RecursiveParams, Shape, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    /// Computes the depth without recursing, since the values are too deep for the test thread's
+    /// stack.
+    fn depth(&self) -> u32 {
+        let mut max = 0;
+        let mut stack = vec![(self, 0)];
+        while let Some((tree, depth)) = stack.pop() {
+            max = max.max(depth);
+            if let Tree::Node(children) = tree {
+                stack.extend(children.iter().map(|child| (child, depth + 1)));
+            }
+        }
+        max
+    }
+}
+
+impl Drop for Tree {
+    /// Drops the children without recursing, for the same reason.
+    fn drop(&mut self) {
+        if let Tree::Node(children) = self {
+            let mut stack = std::mem::take(children);
+            while let Some(mut tree) = stack.pop() {
+                if let Tree::Node(children) = &mut tree {
+                    stack.append(children);
+                }
+            }
+        }
+    }
+}
+
+/// Generates, shrinks and drops values on a thread with a small stack, so that any recursion over
+/// the levels of the strategy overflows. Returns the depth of the deepest value generated.
+fn check(params: RecursiveParams, children: RangeInclusive<usize>) -> u32 {
+    let strategy: SBoxedStrategy<Tree> = Just(Tree::Leaf).prop_mutually_recursive_with(
+        params,
+        &StrategySet::default(),
+        move |set| {
+            vec(set.get::<Tree, _>(|_| unreachable!()), children.clone())
+                .prop_map(Tree::Node)
+                .sboxed()
+        },
+    );
+
+    thread::Builder::new()
+        .stack_size(2 * 1024 * 1024)
+        .spawn(move || {
+            let mut runner = TestRunner::deterministic();
+            let mut max_depth = 0;
+            for _ in 0..5 {
+                let mut tree = strategy.new_tree(&mut runner).unwrap();
+                let depth = tree.current().depth();
+                assert!(depth <= 1000);
+                max_depth = max_depth.max(depth);
+
+                let mut simplified = 0;
+                while simplified < 20 && tree.simplify() {
+                    simplified += 1;
+                    assert!(tree.current().depth() <= 1000);
+                }
+                if simplified > 0 {
+                    tree.complicate();
+                    assert!(tree.current().depth() <= 1000);
+                }
+            }
+            max_depth
+        })
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+#[test]
+fn deep_targeted() {
+    assert!(
+        check(
+            RecursiveParams::new(1000, 0, 1).target_size(1000..=1000),
+            1..=1
+        ) >= 500
+    );
+}
+
+#[test]
+fn deep_linear() {
+    let params = RecursiveParams::new(1000, 0, 1)
+        .target_size(500..=1000)
+        .shape(Shape::Linear);
+    assert!(check(params, 1..=2) >= 500);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "std")]
+
+use proptest::prelude::*;
+use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+use proptest::test_runner::{TestError, TestRunner};
+
+use proptest_recurse::{diagnosed, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Lit(u8),
+    Add(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn size(&self) -> u64 {
+        match self {
+            Expr::Lit(_) => 1,
+            Expr::Add(l, r) => 1 + l.size() + r.size(),
+        }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    any::<u8>()
+        .prop_map(Expr::Lit)
+        .prop_mutually_recursive(4, 32, 2, set, |set| {
+            (set.get(arb_expr), set.get(arb_expr))
+                .prop_map(|(l, r)| Expr::Add(Box::new(l), Box::new(r)))
+                .sboxed()
+        })
+}
+
+#[test]
+fn debug_output() {
+    let leaf = diagnosed(Just(Expr::Lit(1)).prop_mutually_recursive(
+        0,
+        1,
+        1,
+        &StrategySet::default(),
+        |_| unreachable!(),
+    ));
+    let value = leaf
+        .new_tree(&mut TestRunner::deterministic())
+        .unwrap()
+        .current();
+    assert_eq!(format!("{:?}", value), "Lit(1) (depth 0, 1 node)");
+    assert!(matches!(*value, Expr::Lit(1)));
+}
+
+#[test]
+fn minimal_failure_shows_shape() {
+    let mut runner = TestRunner::deterministic();
+    let result = runner.run(&diagnosed(arb_expr(&mut StrategySet::default())), |expr| {
+        prop_assert!(expr.size() < 3);
+        Ok(())
+    });
+    match result {
+        Err(TestError::Fail(_, expr)) => {
+            assert_eq!(expr.with_depth().node_count, expr.size());
+            assert_eq!(
+                format!("{:?}", expr),
+                "Add(Lit(0), Lit(0)) (depth 1, 3 nodes)"
+            );
+            assert!(matches!(expr.into_inner(), Expr::Add(..)));
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,54 @@
+use std::fmt::Debug;
+
+use proptest::strategy::SBoxedStrategy;
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::{dyn_oneof, StrategyExt, StrategySet};
+
+trait Node: Debug {
+    fn depth(&self) -> u32;
+}
+
+#[derive(Clone, Debug)]
+struct Lit;
+
+impl Node for Lit {
+    fn depth(&self) -> u32 {
+        0
+    }
+}
+
+#[derive(Debug)]
+struct Add(Box<dyn Node>, Box<dyn Node>);
+
+impl Node for Add {
+    fn depth(&self) -> u32 {
+        self.0.depth().max(self.1.depth()) + 1
+    }
+}
+
+#[derive(Debug)]
+struct Neg(Box<dyn Node>);
+
+impl Node for Neg {
+    fn depth(&self) -> u32 {
+        self.0.depth() + 1
+    }
+}
+
+fn arb_node(set: &mut StrategySet) -> SBoxedStrategy<Box<dyn Node>> {
+    dyn_oneof![Box<dyn Node>; Just(Lit)].prop_mutually_recursive(4, 16, 2, set, |set| {
+        let node = set.get::<Box<dyn Node>, _>(arb_node);
+        dyn_oneof![Box<dyn Node>;
+            2 => (node.clone(), node.clone()).prop_map(|(l, r)| Add(l, r)),
+            1 => node.prop_map(Neg),
+        ]
+    })
+}
+
+proptest! {
+    #[test]
+    fn create_node(x in arb_node(&mut StrategySet::default())) {
+        assert!(x.depth() <= 4);
+    }
+}
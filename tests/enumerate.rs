@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+
+use proptest_recurse::enumerate::{vec, Enumeration};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum First {
+    Zero,
+    Second(Vec<Second>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Second {
+    Zero,
+    First(Box<First>),
+}
+
+impl First {
+    fn depth(&self) -> u32 {
+        match self {
+            First::Zero => 0,
+            First::Second(s) => s.iter().map(Second::depth).max().map_or(0, |d| d + 1),
+        }
+    }
+}
+
+impl Second {
+    fn depth(&self) -> u32 {
+        match self {
+            Second::Zero => 0,
+            Second::First(f) => f.depth() + 1,
+        }
+    }
+}
+
+fn enum_first() -> Enumeration<First> {
+    Enumeration::just(First::Zero)
+        .recursive(2, |first| vec(enum_second(first), 0..=2).map(First::Second))
+}
+
+/// The values of `Second` containing the given values of `First`.
+fn enum_second(first: Enumeration<First>) -> Enumeration<Second> {
+    Enumeration::just(Second::Zero).union(first.map(|f| Second::First(Box::new(f))))
+}
+
+#[test]
+fn counts() {
+    let values = Enumeration::new(0..3u8);
+    assert_eq!(values.iter().collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(vec(values.clone(), 0..=2).iter().count(), 1 + 3 + 9);
+    assert_eq!(values.clone().zip(values.clone()).iter().count(), 9);
+    assert_eq!(values.clone().union(Enumeration::just(7)).iter().count(), 4);
+    assert_eq!(Enumeration::<u8>::empty().iter().count(), 0);
+}
+
+#[test]
+fn mutually_recursive() {
+    let values: Vec<First> = enum_first().iter().collect();
+    let distinct: HashSet<&First> = values.iter().collect();
+
+    assert_eq!(distinct.len(), values.len());
+    assert!(values.iter().all(|value| value.depth() <= 4));
+    assert!(values.iter().any(|value| value.depth() == 4));
+    assert!(values.contains(&First::Second(vec![
+        Second::Zero,
+        Second::First(Box::new(First::Second(vec![]))),
+    ])));
+}
+
+#[test]
+fn iterated_repeatedly() {
+    let values = enum_first();
+    assert_eq!(values.iter().count(), values.iter().count());
+}
@@ -0,0 +1,88 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::{Just, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Zero,
+    Rich(Vec<u8>),
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    /// Calls `f` with each leaf and its distance from the root.
+    fn visit_leaves(&self, depth: u32, f: &mut impl FnMut(&Tree, u32)) {
+        match self {
+            Tree::Node(children) => {
+                for child in children {
+                    child.visit_leaves(depth + 1, f);
+                }
+            }
+            leaf => f(leaf, depth),
+        }
+    }
+}
+
+fn arb_tree() -> impl Strategy<Value = Tree> {
+    Just(Tree::Zero)
+        .prop_mutually_recursive_unboxed(3, 64, 2, &StrategySet::default(), |set| {
+            vec(set.get::<Tree, _>(|_| unreachable!()), 1..3)
+                .prop_map(Tree::Node)
+                .sboxed()
+        })
+        .exhausted_leaf(vec(any::<u8>(), 1..4).prop_map(Tree::Rich))
+}
+
+fn check_leaves(tree: &Tree) {
+    tree.visit_leaves(0, &mut |leaf, depth| match leaf {
+        Tree::Rich(values) => {
+            assert!(depth <= 3);
+            assert!(!values.is_empty());
+        }
+        _ => assert!(depth < 3),
+    });
+}
+
+proptest! {
+    #[test]
+    fn rich_leaves_at_depth_limit(tree in arb_tree()) {
+        check_leaves(&tree);
+    }
+}
+
+#[test]
+fn rich_leaves_generated() {
+    let mut runner = TestRunner::deterministic();
+    let mut rich = 0;
+    for _ in 0..100 {
+        let tree = arb_tree().new_tree(&mut runner).unwrap().current();
+        tree.visit_leaves(0, &mut |leaf, _| {
+            if let Tree::Rich(_) = leaf {
+                rich += 1;
+            }
+        });
+    }
+    assert!(rich > 0);
+}
+
+/// A node which recursed at the innermost level shrinks to an exhausted leaf, even at the root, so
+/// a failing value can shrink to a single rich leaf, `Rich([0])`.
+#[test]
+fn shrinks_to_rich_root() {
+    let mut runner = TestRunner::deterministic();
+    let shrunk: Vec<Tree> = (0..100)
+        .map(|_| {
+            let mut tree = arb_tree().new_tree(&mut runner).unwrap();
+            while tree.simplify() {
+                check_leaves(&tree.current());
+            }
+            tree.current()
+        })
+        .collect();
+    assert!(shrunk
+        .iter()
+        .any(|tree| matches!(tree, Tree::Rich(values) if values == &[0])));
+}
@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+use proptest::strategy::{Just, SBoxedStrategy};
+use proptest::{prelude::*, prop_oneof, proptest};
+
+use proptest_recurse::collection::{Family, FAMILY_SIZE};
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+struct Pair<T>(T, T);
+
+impl<T: Clone + std::fmt::Debug + 'static> Family for Pair<T> {
+    type Element = T;
+
+    fn strategy(_: &StrategySet, element: SBoxedStrategy<T>) -> SBoxedStrategy<Self> {
+        (element.clone(), element)
+            .prop_map(|(first, second)| Pair(first, second))
+            .sboxed()
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Lit,
+    Call(Box<Expr>, Vec<Expr>),
+    Block(Option<Arc<Expr>>, VecDeque<Expr>),
+    Pair(Box<Pair<Expr>>),
+}
+
+impl Expr {
+    /// Returns the depth of this expression, checking the lengths of nested collections.
+    fn depth(&self) -> u32 {
+        fn max_depth<'a>(exprs: impl Iterator<Item = &'a Expr>) -> u32 {
+            exprs.map(Expr::depth).max().unwrap_or(0)
+        }
+
+        match self {
+            Expr::Lit => 0,
+            Expr::Call(callee, args) => {
+                assert!(args.len() < FAMILY_SIZE.end);
+                1 + callee.depth().max(max_depth(args.iter()))
+            }
+            Expr::Block(result, stmts) => {
+                assert!(stmts.len() < FAMILY_SIZE.end);
+                1 + max_depth(result.iter().map(|result| &**result).chain(stmts))
+            }
+            Expr::Pair(pair) => 1 + pair.0.depth().max(pair.1.depth()),
+        }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    Just(Expr::Lit).prop_mutually_recursive(4, 64, 4, set, |set| {
+        prop_oneof![
+            (
+                set.get_family::<Box<Expr>, _>(arb_expr),
+                set.get_family::<Vec<Expr>, _>(arb_expr),
+            )
+                .prop_map(|(callee, args)| Expr::Call(callee, args)),
+            (
+                set.get_family::<Option<Arc<Expr>>, _>(|set| {
+                    set.get_family::<Arc<Expr>, _>(arb_expr)
+                }),
+                set.get_family::<VecDeque<Expr>, _>(arb_expr),
+            )
+                .prop_map(|(result, stmts)| Expr::Block(result, stmts)),
+            set.get_family::<Box<Pair<Expr>>, _>(|set| set.get_family::<Pair<Expr>, _>(arb_expr))
+                .prop_map(Expr::Pair),
+        ]
+        .sboxed()
+    })
+}
+
+proptest! {
+    #[test]
+    fn family(expr in arb_expr(&mut StrategySet::default())) {
+        prop_assert!(expr.depth() <= 4);
+    }
+}
@@ -0,0 +1,113 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use proptest::prelude::*;
+use proptest::strategy::{SBoxedStrategy, ValueTree};
+use proptest::test_runner::{Config, TestError, TestRunner};
+
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Lit(i32),
+    Div(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn eval(&self) -> Option<i32> {
+        match self {
+            Expr::Lit(value) => Some(*value),
+            Expr::Div(l, r) => l.eval()?.checked_div(r.eval()?),
+        }
+    }
+
+    fn all_nodes(&self, f: &mut impl FnMut(&Expr) -> bool) -> bool {
+        f(self)
+            && match self {
+                Expr::Lit(_) => true,
+                Expr::Div(l, r) => l.all_nodes(f) && r.all_nodes(f),
+            }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    (-2..3)
+        .prop_map(Expr::Lit)
+        .prop_mutually_recursive_unboxed(4, 32, 4, set, |set| {
+            (set.get(arb_expr), set.get(arb_expr))
+                .prop_map(|(l, r)| Expr::Div(Box::new(l), Box::new(r)))
+                .sboxed()
+        })
+        .prop_filter_subtree("division by zero", 8, |expr| expr.eval().is_some())
+        .sboxed()
+}
+
+#[test]
+fn every_node_passes() {
+    let strategy = arb_expr(&mut StrategySet::default());
+
+    let mut runner = TestRunner::deterministic();
+    let mut divisions = 0;
+    for _ in 0..200 {
+        let expr = strategy.new_tree(&mut runner).unwrap().current();
+        assert!(expr.all_nodes(&mut |node| node.eval().is_some()));
+        if let Expr::Div(..) = expr {
+            divisions += 1;
+        }
+    }
+    assert!(divisions > 0);
+}
+
+#[test]
+fn shrinks_to_passing_values() {
+    let strategy = arb_expr(&mut StrategySet::default());
+
+    let mut runner = TestRunner::deterministic();
+    let result = runner.run(&strategy, |expr| {
+        prop_assert!(!matches!(expr, Expr::Div(..)));
+        Ok(())
+    });
+    match result {
+        Err(TestError::Fail(_, expr)) => {
+            assert!(expr.all_nodes(&mut |node| node.eval().is_some()));
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn outermost_rejections_are_counted() {
+    let strategy = Just(0u32)
+        .prop_mutually_recursive_unboxed(2, 8, 2, &StrategySet::default(), |set| {
+            set.get::<u32, _>(|_| unreachable!())
+                .prop_map(|n| n + 1)
+                .sboxed()
+        })
+        .prop_filter_subtree("never", 2, |_| false);
+
+    let mut runner = TestRunner::deterministic();
+    assert!(strategy.new_tree(&mut runner).is_err());
+}
+
+#[test]
+fn retries_are_shared_by_the_whole_value() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    let strategy = Just(0u32)
+        .prop_mutually_recursive_unboxed(6, 64, 1, &StrategySet::default(), |set| {
+            set.get::<u32, _>(|_| unreachable!())
+                .prop_map(|n| n + 1)
+                .sboxed()
+        })
+        .prop_filter_subtree("never", 3, |_| {
+            CALLS.fetch_add(1, Ordering::SeqCst);
+            false
+        });
+
+    let mut runner = TestRunner::new(Config {
+        max_local_rejects: 4,
+        ..Config::default()
+    });
+    assert!(strategy.new_tree(&mut runner).is_err());
+    // Each value is generated at most `retries + 1` times before a local rejection is counted.
+    assert!(CALLS.load(Ordering::SeqCst) <= 4 * 5);
+}
@@ -0,0 +1,75 @@
+#![cfg(feature = "fuzz")]
+
+use arbitrary::Unstructured;
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, Strategy};
+
+use proptest_recurse::{fuzz, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tree {
+    Leaf(u8),
+    Node(Vec<Tree>),
+}
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    (0..=255u8)
+        .prop_map(Tree::Leaf)
+        .prop_mutually_recursive(4, 32, 4, set, |set| {
+            vec(set.get::<Tree, _>(arb_tree), 0..4)
+                .prop_map(Tree::Node)
+                .sboxed()
+        })
+}
+
+#[test]
+fn deterministic() {
+    let strategy = arb_tree(&mut StrategySet::default());
+    let data: Vec<u8> = (0..=255).cycle().step_by(7).take(1024).collect();
+
+    let first = fuzz::generate(&strategy, &data).unwrap();
+    let second = fuzz::generate(&strategy, &data).unwrap();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn different_inputs() {
+    let strategy = arb_tree(&mut StrategySet::default());
+
+    let values: Vec<Tree> = (0..=255u8)
+        .map(|byte| fuzz::generate(&strategy, &[byte; 64]).unwrap())
+        .collect();
+    assert!(values.iter().any(|value| *value != values[0]));
+}
+
+#[test]
+fn empty_input() {
+    let strategy = arb_tree(&mut StrategySet::default());
+    assert!(fuzz::generate(&strategy, &[]).is_some());
+    assert!(fuzz::generate(&Just(1).prop_filter("never", |_| false), &[]).is_none());
+}
+
+#[test]
+fn unstructured() {
+    let strategy = arb_tree(&mut StrategySet::default());
+    let data: Vec<u8> = (0..=255).cycle().step_by(7).take(1024).collect();
+
+    let mut first = Unstructured::new(&data);
+    let mut second = Unstructured::new(&data);
+    assert_eq!(
+        fuzz::arbitrary(&strategy, &mut first).unwrap(),
+        fuzz::arbitrary(&strategy, &mut second).unwrap()
+    );
+    // Only part of the input is used, so other values can be read afterwards.
+    assert!(!first.is_empty());
+    assert_eq!(first.len(), second.len());
+}
+
+#[test]
+fn unstructured_rejected() {
+    let mut u = Unstructured::new(&[1, 2, 3, 4]);
+    assert!(matches!(
+        fuzz::arbitrary(&Just(1).prop_filter("never", |_| false), &mut u),
+        Err(arbitrary::Error::IncorrectFormat)
+    ));
+}
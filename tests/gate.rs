@@ -0,0 +1,94 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::{SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{DepthGate, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Item {
+    Const,
+    Module(Vec<Item>),
+    Lambda(Box<Item>),
+}
+
+impl Item {
+    fn contains_module(&self) -> bool {
+        match self {
+            Item::Const => false,
+            Item::Module(_) => true,
+            Item::Lambda(body) => body.contains_module(),
+        }
+    }
+
+    fn lambda_depth(&self) -> u32 {
+        match self {
+            Item::Const => 0,
+            Item::Module(items) => items.iter().map(Item::lambda_depth).max().unwrap_or(0),
+            Item::Lambda(body) => 1 + body.lambda_depth(),
+        }
+    }
+}
+
+fn arb_item(set: &mut StrategySet) -> SBoxedStrategy<Item> {
+    Just(Item::Const).prop_mutually_recursive(4, 64, 4, set, |set| {
+        let item = set.get(arb_item);
+        set.gated_oneof(vec![
+            (
+                1,
+                DepthGate::root(),
+                vec(item.clone(), 0..4).prop_map(Item::Module).sboxed(),
+            ),
+            (
+                3,
+                DepthGate::any().remaining(2..),
+                item.prop_map(|body| Item::Lambda(Box::new(body))).sboxed(),
+            ),
+        ])
+    })
+}
+
+#[test]
+fn gated() {
+    let strategy = arb_item(&mut StrategySet::default());
+
+    let mut runner = TestRunner::deterministic();
+    let (mut modules, mut max_lambda_depth) = (0, 0);
+    for _ in 0..1000 {
+        let item = strategy.new_tree(&mut runner).unwrap().current();
+        match &item {
+            Item::Module(items) => {
+                modules += 1;
+                assert!(!items.iter().any(Item::contains_module), "{:?}", item);
+            }
+            _ => assert!(!item.contains_module(), "{:?}", item),
+        }
+        max_lambda_depth = max_lambda_depth.max(item.lambda_depth());
+    }
+
+    assert!(modules > 0);
+    // Levels 0, 1 and 2 have at least two levels of recursion remaining.
+    assert_eq!(max_lambda_depth, 3);
+}
+
+#[test]
+fn all_excluded() {
+    fn arb_item(set: &mut StrategySet) -> SBoxedStrategy<Item> {
+        Just(Item::Const).prop_mutually_recursive(4, 64, 4, set, |set| {
+            let item = set.get(arb_item);
+            set.gated_oneof(vec![(
+                1,
+                DepthGate::any().levels(..1),
+                item.prop_map(|body| Item::Lambda(Box::new(body))).sboxed(),
+            )])
+        })
+    }
+
+    let strategy = arb_item(&mut StrategySet::default());
+
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..1000 {
+        let item = strategy.new_tree(&mut runner).unwrap().current();
+        assert!(item.lambda_depth() <= 1, "{:?}", item);
+    }
+}
@@ -0,0 +1,170 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{StrategyExt, StrategySet, TypeMismatch};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Stmt {
+    Nop,
+    Block(Vec<Stmt>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Function {
+    body: Stmt,
+}
+
+fn arb_stmt(set: &mut StrategySet) -> SBoxedStrategy<Stmt> {
+    Just(Stmt::Nop).prop_mutually_recursive(3, 16, 2, set, |set| {
+        vec(set.get::<Stmt, _>(arb_stmt), 0..3)
+            .prop_map(Stmt::Block)
+            .sboxed()
+    })
+}
+
+/// Uses the registered statement strategy if there is one, or a single `Nop` otherwise.
+fn arb_function(set: &mut StrategySet) -> SBoxedStrategy<Function> {
+    set.get_opt::<Stmt>()
+        .unwrap_or_else(|| Just(Stmt::Nop).sboxed())
+        .prop_map(|body| Function { body })
+        .sboxed()
+}
+
+fn sample<T: std::fmt::Debug>(strategy: &SBoxedStrategy<T>) -> T {
+    strategy
+        .new_tree(&mut TestRunner::default())
+        .unwrap()
+        .current()
+}
+
+#[test]
+fn get_opt() {
+    let mut set = StrategySet::default();
+    assert!(set.get_opt::<Stmt>().is_none());
+    assert_eq!(sample(&set.get(arb_function)), Function { body: Stmt::Nop });
+    assert!(set.get_opt::<Stmt>().is_none());
+
+    let mut set = StrategySet::default();
+    let _ = set.get(arb_stmt);
+    assert!(set.get_opt::<Stmt>().is_some());
+    assert!(set.get_opt::<Function>().is_none());
+}
+
+#[test]
+fn get_opt_registered() {
+    let mut set = StrategySet::default();
+    let _ = set.get(arb_stmt);
+    let strategy = set.get(arb_function);
+    let mut runner = TestRunner::default();
+    assert!((0..64).any(|_| {
+        let function = strategy.new_tree(&mut runner).unwrap().current();
+        function.body != Stmt::Nop
+    }));
+}
+
+#[test]
+fn remove() {
+    let mut set = StrategySet::default();
+    assert!(set.remove::<Stmt>().is_none());
+
+    let _ = set.get(arb_stmt);
+    let _ = set.get(arb_function);
+    let before = set.clone();
+    assert!(set.remove::<Stmt>().is_some());
+    assert!(set.get_opt::<Stmt>().is_none());
+    assert!(set.get_opt::<Function>().is_some());
+    assert!(before.get_opt::<Stmt>().is_some());
+
+    // Replace the statement strategy with a restricted one.
+    let _ = set.remove::<Function>();
+    let _ = set.get::<Stmt, _>(|_| Just(Stmt::Nop).sboxed());
+    assert_eq!(sample(&set.get(arb_function)), Function { body: Stmt::Nop });
+}
+
+#[test]
+fn clear() {
+    let mut set = StrategySet::default();
+    let _ = set.get(arb_stmt);
+    let _ = set.get(arb_function);
+    let before = set.clone();
+    set.clear();
+    assert!(set.get_opt::<Stmt>().is_none());
+    assert!(set.get_opt::<Function>().is_none());
+    assert!(before.get_opt::<Function>().is_some());
+}
+
+#[derive(Debug)]
+enum SetupError {
+    MissingSchema,
+    TypeMismatch(TypeMismatch),
+}
+
+impl std::fmt::Display for SetupError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SetupError::MissingSchema => write!(f, "missing schema"),
+            SetupError::TypeMismatch(err) => err.fmt(f),
+        }
+    }
+}
+
+impl From<TypeMismatch> for SetupError {
+    fn from(err: TypeMismatch) -> Self {
+        SetupError::TypeMismatch(err)
+    }
+}
+
+fn arb_function_from_schema(
+    schema: Option<u32>,
+) -> impl FnOnce(&mut StrategySet) -> Result<SBoxedStrategy<Function>, SetupError> {
+    move |set| {
+        let depth = schema.ok_or(SetupError::MissingSchema)?;
+        let body = Just(Stmt::Nop).prop_mutually_recursive(depth, 16, 2, set, |set| {
+            vec(set.get::<Stmt, _>(|_| unreachable!()), 0..3)
+                .prop_map(Stmt::Block)
+                .sboxed()
+        });
+        Ok(body.prop_map(|body| Function { body }).sboxed())
+    }
+}
+
+#[test]
+fn try_get() {
+    let mut set = StrategySet::default();
+    assert!(set
+        .try_get::<_, TypeMismatch, _>(|set| Ok(arb_stmt(set)))
+        .is_ok());
+    assert!(set
+        .try_get::<Stmt, TypeMismatch, _>(|_| panic!("strategy should be registered"))
+        .is_ok());
+}
+
+#[test]
+fn try_get_error() {
+    let mut set = StrategySet::default();
+    let err = set.try_get(arb_function_from_schema(None)).unwrap_err();
+    assert_eq!(err.to_string(), "missing schema");
+    assert!(set.get_opt::<Function>().is_none());
+
+    assert!(set.try_get(arb_function_from_schema(Some(2))).is_ok());
+    assert!(set.get_opt::<Function>().is_some());
+    // The registered strategy is reused, so the factory is not called again.
+    assert!(set.try_get(arb_function_from_schema(None)).is_ok());
+}
+
+/// Lookups only require the value type to be `Any`, so they can be wrapped by generic helpers.
+fn get_generic<T: std::any::Any>(
+    set: &mut StrategySet,
+    factory: fn(&mut StrategySet) -> SBoxedStrategy<T>,
+) -> Option<SBoxedStrategy<T>> {
+    let _ = set.get(factory);
+    set.get_opt()
+}
+
+#[test]
+fn generic_lookup() {
+    let mut set = StrategySet::default();
+    assert!(get_generic(&mut set, arb_stmt).is_some());
+}
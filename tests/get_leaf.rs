@@ -0,0 +1,143 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{RecursiveParams, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tree {
+    Leaf(u8),
+    Node(Vec<Tree>),
+}
+
+#[derive(Clone, Debug)]
+enum Forest {
+    Empty(Tree),
+    Cons(Tree, Box<Forest>),
+}
+
+impl Forest {
+    fn trees(&self) -> Vec<&Tree> {
+        match self {
+            Forest::Empty(tree) => vec![tree],
+            Forest::Cons(tree, rest) => {
+                let mut trees = rest.trees();
+                trees.insert(0, tree);
+                trees
+            }
+        }
+    }
+}
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    any::<u8>()
+        .prop_map(Tree::Leaf)
+        .prop_mutually_recursive(4, 32, 4, set, |set| {
+            vec(set.get(arb_tree), 1..4).prop_map(Tree::Node).sboxed()
+        })
+}
+
+fn arb_forest(set: &mut StrategySet) -> SBoxedStrategy<Forest> {
+    set.recursive_with_base(
+        RecursiveParams::new(3, 16, 1),
+        |set| {
+            let _ = set.get(arb_tree);
+            let tree = set
+                .get_leaf::<Tree>()
+                .unwrap_or_else(|| Just(Tree::Leaf(0)).sboxed());
+            tree.prop_map(Forest::Empty).sboxed()
+        },
+        |set| {
+            (set.get(arb_tree), set.get(arb_forest))
+                .prop_map(|(tree, forest)| Forest::Cons(tree, Box::new(forest)))
+                .sboxed()
+        },
+    )
+}
+
+fn sample<T: std::fmt::Debug>(strategy: &SBoxedStrategy<T>) -> Vec<T> {
+    let mut runner = TestRunner::deterministic();
+    (0..100)
+        .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+        .collect()
+}
+
+#[test]
+fn unregistered() {
+    assert!(StrategySet::default().get_leaf::<Tree>().is_none());
+}
+
+#[test]
+fn within_branch() {
+    let mut set = StrategySet::default();
+    let strategy = Just(Tree::Leaf(0)).prop_mutually_recursive(4, 32, 4, &set, |set| {
+        let leaf = set.get_leaf::<Tree>().unwrap();
+        vec(leaf, 1..4).prop_map(Tree::Node).sboxed()
+    });
+    let _ = set.get(|_| strategy.clone());
+
+    for tree in sample(&strategy) {
+        if let Tree::Node(children) = tree {
+            assert!(children.iter().all(|child| *child == Tree::Leaf(0)));
+        }
+    }
+}
+
+#[test]
+fn registered_recursive() {
+    let mut set = StrategySet::default();
+    let _ = set.get(arb_tree);
+    let leaf = set.get_leaf::<Tree>().unwrap();
+
+    assert!(sample(&leaf)
+        .iter()
+        .all(|tree| matches!(tree, Tree::Leaf(_))));
+}
+
+#[test]
+fn registered_non_recursive() {
+    let mut set = StrategySet::default();
+    let _ = set.get(|_| Just(7u32).sboxed());
+    let _ = set.get(|_| {
+        vec(any::<u8>().prop_map(Tree::Leaf), 1..4)
+            .prop_map(Tree::Node)
+            .sboxed()
+    });
+
+    assert!(set.get_leaf::<u32>().is_none());
+    assert!(set.get_leaf::<Tree>().is_none());
+}
+
+#[test]
+fn applies_hooks() {
+    let mut set = StrategySet::default();
+    let _ = set.get(arb_tree);
+    set.map_type::<Tree, _>(|strategy| strategy.prop_map(|_| Tree::Leaf(1)).sboxed());
+
+    assert!(sample(&set.get_leaf::<Tree>().unwrap())
+        .iter()
+        .all(|tree| *tree == Tree::Leaf(1)));
+}
+
+#[test]
+fn base_uses_other_leaves() {
+    let mut set = StrategySet::default();
+    let _ = set.get(arb_forest);
+    let leaf = set.get_leaf::<Forest>().unwrap();
+
+    for forest in sample(&leaf) {
+        match forest {
+            Forest::Empty(Tree::Leaf(_)) => (),
+            forest => panic!("unexpected leaf {:?}", forest),
+        }
+    }
+}
+
+#[test]
+fn recursive_with_base() {
+    let strategy = arb_forest(&mut StrategySet::default());
+    let forests = sample(&strategy);
+
+    assert!(forests.iter().any(|forest| forest.trees().len() > 1));
+}
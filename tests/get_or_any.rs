@@ -0,0 +1,42 @@
+use proptest::prelude::*;
+use proptest::strategy::{Just, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::StrategySet;
+
+#[test]
+fn fallback() {
+    let set = StrategySet::default();
+    let strategy = set.get_or_any::<u32>();
+
+    let mut runner = TestRunner::deterministic();
+    let values: Vec<u32> = (0..100)
+        .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+        .collect();
+    assert!(values.iter().any(|&value| value != values[0]));
+    assert!(set.get_opt::<u32>().is_none());
+}
+
+#[test]
+fn registered() {
+    let mut set = StrategySet::default();
+    let _ = set.get::<u32, _>(|_| Just(7).sboxed());
+    let strategy = set.get_or_any::<u32>();
+
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..100 {
+        assert_eq!(strategy.new_tree(&mut runner).unwrap().current(), 7);
+    }
+}
+
+#[test]
+fn hooks() {
+    let mut set = StrategySet::default();
+    set.map_type::<u32, _>(|strategy| strategy.prop_map(|value| value % 10).sboxed());
+    let strategy = set.get_or_any::<u32>();
+
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..100 {
+        assert!(strategy.new_tree(&mut runner).unwrap().current() < 10);
+    }
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "std")]
+
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::{global, global_set, register_global, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive(4, 16, 2, set, |set| {
+        vec(set.get::<Tree, _>(arb_tree), 0..3)
+            .prop_map(Tree::Node)
+            .sboxed()
+    })
+}
+
+impl Tree {
+    fn depth(&self) -> u32 {
+        match self {
+            Tree::Leaf => 0,
+            Tree::Node(children) => 1 + children.iter().map(Tree::depth).max().unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Forest(Vec<Tree>);
+
+fn arb_forest(_: &mut StrategySet) -> SBoxedStrategy<Forest> {
+    // Factories may use the global set themselves.
+    vec(global::of(arb_tree), 0..3).prop_map(Forest).sboxed()
+}
+
+proptest! {
+    #[test]
+    fn tree(tree in global::of(arb_tree)) {
+        prop_assert!(tree.depth() <= 4);
+    }
+
+    #[test]
+    fn forest(forest in global::of(arb_forest)) {
+        prop_assert!(forest.0.iter().all(|tree| tree.depth() <= 4));
+    }
+}
+
+#[test]
+fn registered() {
+    let _ = global::of(arb_forest);
+    let mut set = global::set();
+    let _ = set.get::<Tree, _>(|_| panic!("tree should be registered"));
+    let _ = set.get::<Forest, _>(|_| panic!("forest should be registered"));
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Lit,
+    Block(Vec<Stmt>),
+}
+
+#[derive(Clone, Debug)]
+enum Stmt {
+    Expr(Expr),
+}
+
+impl Expr {
+    fn depth(&self) -> u32 {
+        match self {
+            Expr::Lit => 0,
+            Expr::Block(stmts) => 1 + stmts.iter().map(Stmt::depth).max().unwrap_or(0),
+        }
+    }
+}
+
+impl Stmt {
+    fn depth(&self) -> u32 {
+        match self {
+            Stmt::Expr(expr) => expr.depth(),
+        }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    Just(Expr::Lit).prop_mutually_recursive(3, 16, 2, set, |set| {
+        vec(set.get_registered::<Stmt>(), 0..3)
+            .prop_map(Expr::Block)
+            .sboxed()
+    })
+}
+
+fn arb_stmt(set: &mut StrategySet) -> SBoxedStrategy<Stmt> {
+    set.get_registered::<Expr>().prop_map(Stmt::Expr).sboxed()
+}
+
+#[test]
+fn register() {
+    register_global!(arb_expr, arb_stmt);
+
+    let mut set = global_set();
+    let _ = set.get::<Expr, _>(|_| panic!("expr should be registered"));
+    let _ = set.get::<Stmt, _>(|_| panic!("stmt should be registered"));
+
+    let strategy = global_set().get_registered::<Stmt>();
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..100 {
+        let stmt = strategy.new_tree(&mut runner).unwrap().current();
+        assert!(stmt.depth() <= 3);
+    }
+}
+
+#[test]
+#[should_panic(expected = "no factory registered for `u8`")]
+fn unregistered() {
+    let _ = StrategySet::default().get_registered::<u8>();
+}
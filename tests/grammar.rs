@@ -0,0 +1,65 @@
+use proptest::collection::vec;
+use proptest::strategy::Just;
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::grammar::{Grammar, Rule};
+use proptest_recurse::StrategySet;
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Lit,
+    Neg(Box<Expr>),
+    Block(Block),
+}
+
+#[derive(Clone, Debug)]
+struct Block(Vec<Expr>);
+
+impl Expr {
+    fn depth(&self) -> u32 {
+        match self {
+            Expr::Lit => 0,
+            Expr::Neg(e) => e.depth() + 1,
+            Expr::Block(b) => b.depth() + 1,
+        }
+    }
+}
+
+impl Block {
+    fn depth(&self) -> u32 {
+        self.0.iter().map(Expr::depth).max().unwrap_or(0)
+    }
+}
+
+fn grammar() -> Grammar {
+    Grammar::new()
+        .rule(
+            Rule::new(4, 32, 4)
+                .terminal(1, Just(Expr::Lit))
+                .production(2, |g| g.get::<Expr>().prop_map(|e| Expr::Neg(Box::new(e))))
+                .production(1, |g| g.get::<Block>().prop_map(Expr::Block)),
+        )
+        .rule(
+            Rule::new(2, 16, 4)
+                .terminal(1, Just(Block(Vec::new())))
+                .production(1, |g| vec(g.get::<Expr>(), 0..4).prop_map(Block)),
+        )
+}
+
+proptest! {
+    #[test]
+    fn compile_expr(x in grammar().get::<Expr>(&mut grammar().compile())) {
+        assert!(x.depth() <= 16);
+    }
+
+    #[test]
+    fn get_block(x in grammar().get::<Block>(&mut StrategySet::default())) {
+        assert!(x.depth() <= 16);
+    }
+}
+
+#[test]
+#[should_panic(expected = "grammar has no rule for `u32`")]
+fn missing_rule() {
+    let _ = Grammar::new().get::<u32>(&mut StrategySet::default());
+}
@@ -0,0 +1,84 @@
+#![cfg(feature = "std")]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::time::Duration;
+
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{RecursiveParams, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    fn size(&self) -> u64 {
+        match self {
+            Tree::Leaf => 1,
+            Tree::Node(children) => 1 + children.iter().map(Tree::size).sum::<u64>(),
+        }
+    }
+}
+
+/// A strategy whose values have billions of nodes, since every level recurses and has up to 64
+/// children.
+fn runaway(params: RecursiveParams) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive_with(params, &StrategySet::default(), |set| {
+        vec(set.get::<Tree, _>(|_| unreachable!()), 32..64)
+            .prop_map(Tree::Node)
+            .sboxed()
+    })
+}
+
+fn panic_message(strategy: &SBoxedStrategy<Tree>) -> String {
+    let mut runner = TestRunner::deterministic();
+    let payload = catch_unwind(AssertUnwindSafe(|| {
+        strategy.new_tree(&mut runner).map(|_| ())
+    }))
+    .unwrap_err();
+    payload
+        .downcast::<String>()
+        .map(|message| *message)
+        .unwrap()
+}
+
+#[test]
+fn max_nodes() {
+    let strategy = runaway(RecursiveParams::new(8, 1 << 30, 1).max_nodes(1000));
+    assert_eq!(
+        panic_message(&strategy),
+        "generating a value of `Tree (depth=8, size=1073741824)` built more than 1000 nodes; check \
+         the parameters of its recursive strategy and the sizes of any collections in its branch \
+         function"
+    );
+}
+
+#[test]
+fn timeout() {
+    let strategy = runaway(RecursiveParams::new(8, 1 << 30, 1).timeout(Duration::from_millis(10)));
+    assert!(panic_message(&strategy).starts_with(
+        "generating a value of `Tree (depth=8, size=1073741824)` took longer than 10ms"
+    ));
+}
+
+#[test]
+fn within_limits() {
+    let strategy = runaway(RecursiveParams::new(1, 32, 1).max_nodes(100));
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..100 {
+        assert!(strategy.new_tree(&mut runner).unwrap().current().size() <= 100);
+    }
+}
+
+#[test]
+fn guard_removed_after_panic() {
+    let _ = panic_message(&runaway(RecursiveParams::new(8, 1 << 30, 1).max_nodes(10)));
+
+    let strategy = runaway(RecursiveParams::new(2, 1 << 30, 1));
+    let mut runner = TestRunner::deterministic();
+    strategy.new_tree(&mut runner).unwrap();
+}
@@ -0,0 +1,75 @@
+use proptest::prelude::*;
+use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    fn depth(&self) -> u32 {
+        match self {
+            Tree::Leaf => 0,
+            Tree::Node(children) => 1 + children.iter().map(Tree::depth).max().unwrap_or(0),
+        }
+    }
+}
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive(3, 16, 4, set, |set| {
+        let tree = set.handle::<Tree>();
+        (tree.strategy(), tree.strategy())
+            .prop_map(|(l, r)| Tree::Node(vec![l, r]))
+            .sboxed()
+    })
+}
+
+#[test]
+fn resolves_current_level() {
+    let strategy = arb_tree(&mut StrategySet::default());
+
+    let mut runner = TestRunner::deterministic();
+    let depths: Vec<u32> = (0..100)
+        .map(|_| strategy.new_tree(&mut runner).unwrap().current().depth())
+        .collect();
+    assert!(depths.iter().all(|&depth| depth <= 3));
+    assert!(depths.iter().any(|&depth| depth > 0));
+}
+
+#[test]
+fn applies_hooks() {
+    let mut set = StrategySet::default();
+    let _ = set.get::<u32, _>(|_| (0..100u32).sboxed());
+    set.map_type::<u32, _>(|strategy| strategy.prop_map(|value| value % 10).sboxed());
+    let handle = set.handle::<u32>();
+
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..100 {
+        assert!(handle.strategy().new_tree(&mut runner).unwrap().current() < 10);
+    }
+}
+
+#[test]
+fn unaffected_by_later_changes() {
+    let mut set = StrategySet::default();
+    let _ = set.get::<u32, _>(|_| Just(1).sboxed());
+    let handle = set.handle::<u32>();
+    set.remove::<u32>();
+    let _ = set.get::<u32, _>(|_| Just(2).sboxed());
+
+    let mut runner = TestRunner::deterministic();
+    assert_eq!(
+        handle.strategy().new_tree(&mut runner).unwrap().current(),
+        1
+    );
+}
+
+#[test]
+#[should_panic(expected = "no strategy registered for `u32`")]
+fn unregistered() {
+    let _ = StrategySet::default().handle::<u32>();
+}
@@ -0,0 +1,33 @@
+#![cfg(feature = "json")]
+
+use proptest::{prelude::*, proptest};
+use serde_json::Value;
+
+use proptest_recurse::json::{arb_json, arb_json_with};
+use proptest_recurse::{RecursiveParams, StrategySet};
+
+fn depth(json: &Value) -> u32 {
+    match json {
+        Value::Array(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+        Value::Object(entries) => 1 + entries.values().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+proptest! {
+    #[test]
+    fn default_depth(json in arb_json(&mut StrategySet::default())) {
+        prop_assert!(depth(&json) <= 4);
+    }
+
+    #[test]
+    fn custom_depth(json in arb_json_with(RecursiveParams::new(6, 128, 4), &mut StrategySet::default())) {
+        prop_assert!(depth(&json) <= 6);
+    }
+
+    #[test]
+    fn round_trip(json in arb_json(&mut StrategySet::default())) {
+        let text = serde_json::to_string(&json).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Value>(&text).unwrap(), json);
+    }
+}
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::{AnyValue, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum First {
+    Zero,
+    Second(Vec<Second>),
+}
+
+#[derive(Clone, Debug)]
+enum Second {
+    Zero,
+    First(Box<First>),
+}
+
+fn arb_first(set: &mut StrategySet) -> SBoxedStrategy<First> {
+    Just(First::Zero).prop_mutually_recursive(4, 16, 2, set, |set| {
+        vec(set.get::<Second, _>(arb_second), 0..3)
+            .prop_map(First::Second)
+            .sboxed()
+    })
+}
+
+fn arb_second(set: &mut StrategySet) -> SBoxedStrategy<Second> {
+    Just(Second::Zero).prop_mutually_recursive(4, 16, 1, set, |set| {
+        set.get::<First, _>(arb_first)
+            .prop_map(|first| Second::First(Box::new(first)))
+            .sboxed()
+    })
+}
+
+fn arb_u32(set: &mut StrategySet) -> SBoxedStrategy<u32> {
+    any::<u32>().prop_mutually_recursive(3, 8, 1, set, |set| {
+        set.get(arb_u32).prop_map(|n| n + 1).sboxed()
+    })
+}
+
+fn count(first: &First, counts: &mut BTreeMap<&'static str, usize>) {
+    *counts.entry("First").or_default() += 1;
+    if let First::Second(seconds) = first {
+        for second in seconds {
+            *counts.entry("Second").or_default() += 1;
+            if let Second::First(first) = second {
+                count(first, counts);
+            }
+        }
+    }
+}
+
+type Counts = Arc<Mutex<BTreeMap<&'static str, usize>>>;
+
+fn counting_set(counts: &Counts) -> StrategySet {
+    let mut set = StrategySet::default();
+    let counts = Arc::clone(counts);
+    set.map_all(move |strategy| {
+        let counts = Arc::clone(&counts);
+        strategy
+            .prop_map(move |value: AnyValue| {
+                let name = value.type_name().rsplit("::").next().unwrap();
+                *counts.lock().unwrap().entry(name).or_default() += 1;
+                value
+            })
+            .sboxed()
+    });
+    set
+}
+
+#[test]
+fn counts_every_value() {
+    let counts = Counts::default();
+    let strategy = counting_set(&counts).get(arb_first);
+    let mut runner = TestRunner::default();
+    let mut expected = BTreeMap::new();
+    for _ in 0..64 {
+        count(
+            &strategy.new_tree(&mut runner).unwrap().current(),
+            &mut expected,
+        );
+    }
+    // Values are only counted when generated, not when shrinking, so the counts match exactly.
+    assert_eq!(*counts.lock().unwrap(), expected);
+}
+
+proptest! {
+    #[test]
+    fn after_map_type(value in {
+        let mut set = StrategySet::default();
+        set.map_type::<u32, _>(|strategy| strategy.prop_map(|n| n % 10).sboxed());
+        set.map_all(|strategy| {
+            strategy
+                .prop_map(|value| match value.downcast_ref::<u32>() {
+                    Some(n) => {
+                        assert!(*n < 10);
+                        value
+                    }
+                    None => value,
+                })
+                .sboxed()
+        });
+        set.get(arb_u32)
+    }) {
+        prop_assert!(value < 10);
+    }
+}
+
+#[test]
+#[should_panic(expected = "changed the value type from `u32` to `u64`")]
+fn type_changed() {
+    let mut set = StrategySet::default();
+    set.map_all(|strategy| {
+        strategy
+            .prop_map(|value| match value.downcast::<u32>() {
+                Ok(_) => AnyValue::new(0u64),
+                Err(value) => value,
+            })
+            .sboxed()
+    });
+    let strategy = set.get(arb_u32);
+    strategy
+        .new_tree(&mut TestRunner::default())
+        .unwrap()
+        .current();
+}
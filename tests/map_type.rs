@@ -0,0 +1,111 @@
+use proptest::collection::vec;
+use proptest::strategy::SBoxedStrategy;
+use proptest::{prelude::*, prop_oneof, proptest};
+
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Num(i32),
+    Sum(Vec<Expr>),
+    Block(Vec<Stmt>, Box<Expr>),
+}
+
+#[derive(Clone, Debug)]
+enum Stmt {
+    Expr(Expr),
+}
+
+fn key(expr: &Expr) -> (u8, i32) {
+    match expr {
+        Expr::Num(n) => (0, *n),
+        Expr::Sum(_) => (1, 0),
+        Expr::Block(..) => (2, 0),
+    }
+}
+
+/// Sorts the operands of sums, which is only valid because addition is commutative.
+fn normalize(expr: Expr) -> Expr {
+    match expr {
+        Expr::Sum(mut operands) => {
+            operands.sort_by_key(key);
+            Expr::Sum(operands)
+        }
+        expr => expr,
+    }
+}
+
+fn is_normalized(expr: &Expr) -> bool {
+    match expr {
+        Expr::Num(_) => true,
+        Expr::Sum(operands) => {
+            operands
+                .windows(2)
+                .all(|pair| key(&pair[0]) <= key(&pair[1]))
+                && operands.iter().all(is_normalized)
+        }
+        Expr::Block(stmts, expr) => {
+            stmts.iter().all(|Stmt::Expr(expr)| is_normalized(expr)) && is_normalized(expr)
+        }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    any::<i32>()
+        .prop_map(Expr::Num)
+        .prop_mutually_recursive(4, 32, 3, set, |set| {
+            let expr = set.get::<Expr, _>(arb_expr);
+            prop_oneof![
+                vec(expr.clone(), 0..4).prop_map(Expr::Sum),
+                (vec(set.get::<Stmt, _>(arb_stmt), 0..3), expr)
+                    .prop_map(|(stmts, expr)| Expr::Block(stmts, Box::new(expr))),
+            ]
+            .sboxed()
+        })
+}
+
+fn arb_stmt(set: &mut StrategySet) -> SBoxedStrategy<Stmt> {
+    set.get::<Expr, _>(arb_expr).prop_map(Stmt::Expr).sboxed()
+}
+
+fn normalized_set() -> StrategySet {
+    let mut set = StrategySet::default();
+    set.map_type::<Expr, _>(|strategy| strategy.prop_map(normalize).sboxed());
+    set
+}
+
+proptest! {
+    #[test]
+    fn nested(expr in normalized_set().get(arb_expr)) {
+        prop_assert!(is_normalized(&expr));
+    }
+
+    #[test]
+    fn other_factories(stmt in normalized_set().get(arb_stmt)) {
+        let Stmt::Expr(expr) = stmt;
+        prop_assert!(is_normalized(&expr));
+    }
+}
+
+#[test]
+fn composed() {
+    use proptest::strategy::ValueTree;
+    use proptest::test_runner::TestRunner;
+
+    let mut set = StrategySet::default();
+    set.map_type::<u32, _>(|strategy| strategy.prop_map(|n| n + 1).sboxed());
+    set.map_type::<u32, _>(|strategy| strategy.prop_map(|n| n * 2).sboxed());
+    let strategy = set.get(|_| Just(1u32).sboxed());
+    let value = strategy
+        .new_tree(&mut TestRunner::default())
+        .unwrap()
+        .current();
+    assert_eq!(value, 4);
+}
+
+#[test]
+fn unregistered() {
+    let mut set = normalized_set();
+    let _ = set.get(|_| Just(0u8).sboxed());
+    assert!(set.get_opt::<u8>().is_some());
+}
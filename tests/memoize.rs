@@ -0,0 +1,107 @@
+use std::collections::HashSet;
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::{SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{RecursiveParams, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum Tree {
+    Leaf(u32),
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    fn depth(&self) -> u32 {
+        match self {
+            Tree::Leaf(_) => 0,
+            Tree::Node(children) => 1 + children.iter().map(Tree::depth).max().unwrap_or(0),
+        }
+    }
+
+    fn subtrees<'a>(&'a self, out: &mut Vec<&'a Tree>) {
+        out.push(self);
+        if let Tree::Node(children) = self {
+            for child in children {
+                child.subtrees(out);
+            }
+        }
+    }
+}
+
+fn arb_tree(max_height: u32, reuse_probability: f64) -> SBoxedStrategy<Tree> {
+    any::<u32>()
+        .prop_map(Tree::Leaf)
+        .prop_mutually_recursive_memoized(
+            RecursiveParams::new(5, 256, 3),
+            max_height,
+            reuse_probability,
+            &StrategySet::default(),
+            |set| {
+                vec(set.get::<Tree, _>(|_| unreachable!()), 1..4)
+                    .prop_map(Tree::Node)
+                    .sboxed()
+            },
+        )
+}
+
+/// Returns the fraction of subtrees with at most `height` levels which are distinct, and the
+/// maximum depth of all trees.
+fn distinct(strategy: &SBoxedStrategy<Tree>, height: u32) -> (f64, u32) {
+    let mut runner = TestRunner::deterministic();
+    let mut trees = Vec::new();
+    for _ in 0..64 {
+        trees.push(strategy.new_tree(&mut runner).unwrap().current());
+    }
+
+    let mut subtrees = Vec::new();
+    for tree in &trees {
+        tree.subtrees(&mut subtrees);
+    }
+    subtrees.retain(|subtree| subtree.depth() <= height);
+    let unique: HashSet<_> = subtrees.iter().collect();
+    let max_depth = trees.iter().map(Tree::depth).max().unwrap();
+    (unique.len() as f64 / subtrees.len() as f64, max_depth)
+}
+
+#[test]
+fn reuses_small_subtrees() {
+    let (memoized, max_depth) = distinct(&arb_tree(1, 0.9), 1);
+    let (fresh, _) = distinct(&arb_tree(1, 0.0), 1);
+    assert!(max_depth <= 5);
+    assert!(fresh > 0.95, "{}", fresh);
+    assert!(memoized < 0.5, "{}", memoized);
+}
+
+#[test]
+fn leaves_only() {
+    let (leaves, _) = distinct(&arb_tree(0, 0.9), 0);
+    let (height_one, _) = distinct(&arb_tree(0, 0.9), 1);
+    assert!(leaves < 0.5, "{}", leaves);
+    assert!(height_one > leaves);
+}
+
+#[test]
+#[should_panic(expected = "reuse probability must be between 0 and 1")]
+fn invalid_probability() {
+    let _ = arb_tree(1, -0.5);
+}
+
+#[test]
+fn reproducible_from_seed() {
+    let strategy = arb_tree(1, 0.9);
+    let mut runner = TestRunner::default();
+    for _ in 0..16 {
+        strategy.new_tree(&mut runner).unwrap();
+    }
+
+    let generate = |strategy: &SBoxedStrategy<Tree>| {
+        strategy
+            .new_tree(&mut TestRunner::deterministic())
+            .unwrap()
+            .current()
+    };
+    assert_eq!(generate(&strategy), generate(&arb_tree(1, 0.9)));
+}
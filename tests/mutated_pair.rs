@@ -0,0 +1,118 @@
+#![cfg(feature = "std")]
+
+use std::cell::Cell;
+use std::collections::HashSet;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::tree::{recursive_tree, Tree};
+use proptest_recurse::{mutated_pair, RecursiveParams};
+
+fn leaves(tree: &Tree<u64>, out: &mut Vec<u64>) {
+    match tree {
+        Tree::Leaf(value) => out.push(*value),
+        Tree::Node(children) => children.iter().for_each(|child| leaves(child, out)),
+    }
+}
+
+fn arb_pair() -> SBoxedStrategy<(Tree<u64>, Tree<u64>)> {
+    mutated_pair(recursive_tree(
+        any::<u64>(),
+        1..4,
+        RecursiveParams::new(5, 64, 2),
+    ))
+}
+
+proptest! {
+    #[test]
+    fn depth_limit((original, mutated) in arb_pair()) {
+        prop_assert!(original.depth() <= 5);
+        prop_assert!(mutated.depth() <= 5);
+    }
+}
+
+#[test]
+fn deterministic() {
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..20 {
+        let mut tree = arb_pair().new_tree(&mut runner).unwrap();
+        assert_eq!(tree.current(), tree.current());
+        for _ in 0..100 {
+            if !tree.simplify() {
+                break;
+            }
+            assert_eq!(tree.current(), tree.current());
+        }
+    }
+}
+
+#[test]
+fn mutates_one_subtree() {
+    let mut runner = TestRunner::deterministic();
+    let (mut changed, mut large, mut kept, mut total) = (0, 0, 0, 0);
+    for _ in 0..500 {
+        let (original, mutated) = arb_pair().new_tree(&mut runner).unwrap().current();
+        if original != mutated {
+            changed += 1;
+        }
+
+        let mut original_leaves = Vec::new();
+        leaves(&original, &mut original_leaves);
+        if original_leaves.len() < 8 {
+            continue;
+        }
+        large += 1;
+        let mut mutated_leaves = Vec::new();
+        leaves(&mutated, &mut mutated_leaves);
+        let mutated_leaves: HashSet<_> = mutated_leaves.into_iter().collect();
+        kept += original_leaves
+            .iter()
+            .filter(|leaf| mutated_leaves.contains(leaf))
+            .count();
+        total += original_leaves.len();
+    }
+
+    assert!(changed > 250, "only {} of 500 pairs differed", changed);
+    // Most nodes are near the leaves, so a single regenerated subtree is usually small.
+    assert!(large > 0);
+    assert!(
+        kept * 2 > total,
+        "only {} of {} leaves were kept",
+        kept,
+        total
+    );
+}
+
+thread_local! {
+    static LEAVES_BUILT: Cell<usize> = const { Cell::new(0) };
+    static PANIC_AT_LEAF: Cell<usize> = const { Cell::new(usize::MAX) };
+}
+
+#[test]
+fn mutation_cleared_after_panic() {
+    let leaf = any::<u64>().prop_map(|value| {
+        let built = LEAVES_BUILT.with(|built| built.replace(built.get() + 1));
+        assert_ne!(built, PANIC_AT_LEAF.with(Cell::get), "leaf panicked");
+        value
+    });
+    let strategy = recursive_tree(leaf, 1..4, RecursiveParams::new(5, 64, 2));
+    let pair = mutated_pair(strategy.clone());
+
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..20 {
+        // Panic on the first leaf of the mutated copy, before the mutation is likely to be applied.
+        let tree = pair.new_tree(&mut runner).unwrap();
+        let mut original = Vec::new();
+        leaves(&tree.current().0, &mut original);
+        LEAVES_BUILT.with(|built| built.set(0));
+        PANIC_AT_LEAF.with(|panic_at| panic_at.set(original.len()));
+        assert!(catch_unwind(AssertUnwindSafe(|| tree.current())).is_err());
+        PANIC_AT_LEAF.with(|panic_at| panic_at.set(usize::MAX));
+
+        let tree = strategy.new_tree(&mut runner).unwrap();
+        assert_eq!(tree.current(), tree.current());
+    }
+}
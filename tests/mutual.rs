@@ -66,3 +66,38 @@ proptest! {
         assert!(x.depth() <= 8);
     }
 }
+
+proptest! {
+    #[test]
+    fn create_first_unboxed(
+        x in Just(First::Zero).prop_mutually_recursive_unboxed(5, 32, 8, &Default::default(), |set| {
+            vec(set.get::<Second, _>(arb_second), 0..8)
+                .prop_map(First::Second)
+                .sboxed()
+        })
+    ) {
+        assert!(x.depth() <= 8);
+    }
+}
+
+proptest! {
+    #[test]
+    fn create_from_set(x in proptest_recurse::from_set(arb_first)) {
+        assert!(x.depth() <= 8);
+    }
+
+    #[test]
+    fn create_from_shared_set(
+        (first, second) in {
+            let mut set = StrategySet::default();
+            let _ = set.get::<Second, _>(arb_second);
+            (
+                proptest_recurse::from_shared_set(&set, arb_first),
+                proptest_recurse::from_shared_set(&set, arb_second),
+            )
+        }
+    ) {
+        assert!(first.depth() <= 8);
+        assert!(second.depth() <= 8);
+    }
+}
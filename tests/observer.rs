@@ -0,0 +1,101 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{Observer, RecursiveParams, Shape, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    /// Returns the number of nodes and leaves, and the maximum depth.
+    fn stats(&self) -> (u32, u32, u32) {
+        match self {
+            Tree::Leaf => (0, 1, 0),
+            Tree::Node(children) => children
+                .iter()
+                .map(Tree::stats)
+                .fold((1, 0, 1), |(nodes, leaves, depth), (n, l, d)| {
+                    (nodes + n, leaves + l, depth.max(d + 1))
+                }),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Counts {
+    branches: Mutex<Vec<u32>>,
+    leaves: AtomicU32,
+    exhausted: AtomicU32,
+}
+
+impl Observer for Counts {
+    fn on_branch(&self, level: u32) {
+        self.branches.lock().unwrap().push(level);
+    }
+
+    fn on_leaf(&self) {
+        self.leaves.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_depth_exhausted(&self) {
+        self.exhausted.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+fn arb_tree(params: RecursiveParams) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive_with(params, &StrategySet::default(), |set| {
+        vec(set.get::<Tree, _>(|_| unreachable!()), 1..3)
+            .prop_map(Tree::Node)
+            .sboxed()
+    })
+}
+
+fn check(shape: Shape) {
+    let counts = Arc::new(Counts::default());
+    let params = RecursiveParams::new(3, 64, 2)
+        .shape(shape)
+        .observer(Arc::clone(&counts));
+    let strategy = arb_tree(params);
+    let mut runner = TestRunner::deterministic();
+    let (mut nodes, mut leaves, mut full_depth) = (0, 0, false);
+    for _ in 0..256 {
+        let (n, l, d) = strategy.new_tree(&mut runner).unwrap().current().stats();
+        nodes += n;
+        leaves += l;
+        full_depth |= d == 3;
+    }
+
+    let branches = counts.branches.lock().unwrap();
+    assert_eq!(branches.len() as u32, nodes);
+    assert!(branches.iter().all(|&level| level < 3));
+    assert_eq!(counts.leaves.load(Ordering::Relaxed), leaves);
+    assert!(full_depth);
+    assert!(counts.exhausted.load(Ordering::Relaxed) > 0);
+    assert!(counts.exhausted.load(Ordering::Relaxed) <= leaves);
+}
+
+#[test]
+fn independent() {
+    check(Shape::default());
+}
+
+#[test]
+fn balanced() {
+    check(Shape::Balanced);
+}
+
+#[test]
+fn params_eq() {
+    let observer = Arc::new(Counts::default());
+    let params = RecursiveParams::new(3, 64, 2).observer(Arc::clone(&observer));
+    assert_eq!(params, params.clone());
+    assert_ne!(params, RecursiveParams::new(3, 64, 2));
+    assert_ne!(params, RecursiveParams::new(3, 64, 2).observer(observer));
+}
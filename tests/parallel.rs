@@ -0,0 +1,139 @@
+#![cfg(feature = "rayon")]
+
+use proptest::prelude::*;
+use proptest::strategy::{SBoxedStrategy, ValueTree};
+use proptest::test_runner::{Config, RngAlgorithm, TestError, TestRng, TestRunner};
+
+use proptest_recurse::parallel::recursive_vec;
+use proptest_recurse::{RecursiveParams, Shape, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Tree {
+    Leaf(u8),
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    fn nodes(&self) -> Vec<&Vec<Tree>> {
+        match self {
+            Tree::Leaf(_) => vec![],
+            Tree::Node(children) => {
+                let mut nodes = vec![children];
+                nodes.extend(children.iter().flat_map(Tree::nodes));
+                nodes
+            }
+        }
+    }
+
+    fn leaves(&self) -> Vec<u8> {
+        match self {
+            Tree::Leaf(value) => vec![*value],
+            Tree::Node(children) => children.iter().flat_map(Tree::leaves).collect(),
+        }
+    }
+}
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    any::<u8>()
+        .prop_map(Tree::Leaf)
+        .prop_mutually_recursive(6, 512, 6, set, |set| {
+            recursive_vec(set, arb_tree, 1..8)
+                .prop_map(Tree::Node)
+                .sboxed()
+        })
+}
+
+fn generate(seed: u8, threads: usize) -> Vec<Tree> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap();
+    pool.install(|| {
+        let strategy = arb_tree(&mut StrategySet::default());
+        let mut runner = TestRunner::new_with_rng(
+            Config::default(),
+            TestRng::from_seed(RngAlgorithm::ChaCha, &[seed; 32]),
+        );
+        (0..32)
+            .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+            .collect()
+    })
+}
+
+#[test]
+fn deterministic() {
+    for seed in 0..4 {
+        let expected = generate(seed, 1);
+        assert_eq!(generate(seed, 1), expected);
+        assert_eq!(generate(seed, 4), expected);
+    }
+    assert_ne!(generate(0, 4), generate(1, 4));
+}
+
+#[test]
+fn shrinks() {
+    let mut runner = TestRunner::new_with_rng(
+        Config::default(),
+        TestRng::from_seed(RngAlgorithm::ChaCha, &[7; 32]),
+    );
+    let result = runner.run(&arb_tree(&mut StrategySet::default()), |tree| {
+        prop_assert!(tree.leaves().iter().all(|&leaf| leaf < 200));
+        Ok(())
+    });
+
+    match result {
+        Err(TestError::Fail(_, tree)) => assert_eq!(tree.leaves(), vec![200]),
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn simplify_and_complicate() {
+    let strategy = arb_tree(&mut StrategySet::default());
+    let mut runner = TestRunner::deterministic();
+    let mut tree = loop {
+        let tree = strategy.new_tree(&mut runner).unwrap();
+        if matches!(tree.current(), Tree::Node(ref children) if children.len() > 1) {
+            break tree;
+        }
+    };
+
+    let initial = tree.current();
+    assert!(tree.simplify());
+    assert_ne!(tree.current(), initial);
+    assert!(tree.complicate());
+    assert_eq!(tree.current(), initial);
+}
+
+#[test]
+fn linear_shape() {
+    let strategy = any::<u8>()
+        .prop_map(Tree::Leaf)
+        .prop_mutually_recursive_with(
+            RecursiveParams::new(6, 512, 6).shape(Shape::Linear),
+            &StrategySet::default(),
+            |set| {
+                recursive_vec(set, |_| unreachable!(), 1..8)
+                    .prop_map(Tree::Node)
+                    .sboxed()
+            },
+        );
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    pool.install(|| {
+        let mut runner = TestRunner::deterministic();
+        for _ in 0..64 {
+            let tree = strategy.new_tree(&mut runner).unwrap().current();
+            for children in tree.nodes() {
+                let branches = children
+                    .iter()
+                    .filter(|child| matches!(child, Tree::Node(_)))
+                    .count();
+                assert!(branches <= 1);
+            }
+        }
+    });
+}
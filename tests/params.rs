@@ -0,0 +1,69 @@
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{RecursiveParams, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    fn size(&self) -> u32 {
+        match self {
+            Tree::Leaf => 1,
+            Tree::Node(children) => 1 + children.iter().map(Tree::size).sum::<u32>(),
+        }
+    }
+}
+
+fn arb_tree(params: RecursiveParams) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive_with(params, &StrategySet::default(), |set| {
+        vec(set.get::<Tree, _>(|_| unreachable!()), 2)
+            .prop_map(Tree::Node)
+            .sboxed()
+    })
+}
+
+fn sizes(strategy: &SBoxedStrategy<Tree>, samples: usize) -> Vec<u32> {
+    let mut runner = TestRunner::deterministic();
+    (0..samples)
+        .map(|_| strategy.new_tree(&mut runner).unwrap().current().size())
+        .collect()
+}
+
+fn mean(sizes: &[u32]) -> f64 {
+    sizes.iter().map(|&s| f64::from(s)).sum::<f64>() / sizes.len() as f64
+}
+
+#[test]
+fn target_size() {
+    let small = sizes(
+        &arb_tree(RecursiveParams::new(8, 0, 2).target_size(1..=1)),
+        500,
+    );
+    let large = sizes(
+        &arb_tree(RecursiveParams::new(8, 0, 2).target_size(64..=64)),
+        500,
+    );
+
+    assert!(small.iter().all(|&s| s == 1));
+    assert!(
+        mean(&large) > 32.0 && mean(&large) < 128.0,
+        "{}",
+        mean(&large)
+    );
+}
+
+#[test]
+fn target_size_range() {
+    let sizes = sizes(
+        &arb_tree(RecursiveParams::new(8, 0, 2).target_size(1..=128)),
+        1000,
+    );
+    let medium = sizes.iter().filter(|&&s| (16..=128).contains(&s)).count();
+
+    assert!(medium > 100, "{}", medium);
+}
@@ -0,0 +1,85 @@
+use proptest::collection::vec;
+use proptest::strategy::Just;
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::{prop_mutual, RecursiveParams, StrategySet};
+
+#[derive(Clone, Debug)]
+enum First {
+    Zero,
+    Second(Vec<Second>),
+}
+
+#[derive(Clone, Debug)]
+enum Second {
+    Zero,
+    First(Box<First>),
+}
+
+impl First {
+    fn depth(&self) -> u32 {
+        match self {
+            First::Zero => 0,
+            First::Second(s) => match s.iter().map(Second::depth).max() {
+                Some(depth) => depth + 1,
+                None => 0,
+            },
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        match self {
+            First::Zero => 0,
+            First::Second(s) => s.iter().map(Second::max_len).fold(s.len(), usize::max),
+        }
+    }
+}
+
+impl Second {
+    fn depth(&self) -> u32 {
+        match self {
+            Second::Zero => 0,
+            Second::First(f) => f.depth() + 1,
+        }
+    }
+
+    fn max_len(&self) -> usize {
+        match self {
+            Second::Zero => 0,
+            Second::First(f) => f.max_len(),
+        }
+    }
+}
+
+prop_mutual! {
+    /// Returns a strategy for `First` with at most `max_len` children per node.
+    fn arb_first(max_len: usize) -> First {
+        base: Just(First::Zero),
+        branch(set) => vec(set.get::<Second, _>(arb_second), 0..=max_len).prop_map(First::Second),
+        params: (5, 32, 8),
+    }
+}
+
+prop_mutual! {
+    pub(crate) fn arb_second() -> Second {
+        base: Just(Second::Zero),
+        branch(set) => set
+            .get::<First, _>(|set| arb_first(set, 8))
+            .prop_map(|first| Second::First(Box::new(first))),
+        params: RecursiveParams::new(3, 32, 1)
+    }
+}
+
+proptest! {
+    #[test]
+    fn create_first(x in arb_first(&mut StrategySet::default(), 2)) {
+        assert!(x.depth() <= 8);
+        assert!(x.max_len() <= 2);
+    }
+
+    #[test]
+    fn create_second(x in arb_second(&mut StrategySet::default())) {
+        assert!(x.depth() <= 8);
+        assert!(x.max_len() <= 8);
+    }
+}
@@ -0,0 +1,94 @@
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::{Just, SBoxedStrategy};
+use proptest_derive::Arbitrary;
+
+use proptest_recurse::{from_set, from_shared_set, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Lit(i32),
+    Block(Vec<Stmt>),
+}
+
+#[derive(Clone, Debug)]
+enum Stmt {
+    Expr(Expr),
+}
+
+impl Expr {
+    fn depth(&self) -> u32 {
+        match self {
+            Expr::Lit(_) => 0,
+            Expr::Block(stmts) => 1 + stmts.iter().map(Stmt::depth).max().unwrap_or(0),
+        }
+    }
+}
+
+impl Stmt {
+    fn depth(&self) -> u32 {
+        match self {
+            Stmt::Expr(expr) => expr.depth(),
+        }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    any::<i32>()
+        .prop_map(Expr::Lit)
+        .prop_mutually_recursive(3, 16, 2, set, |set| {
+            vec(set.get(arb_stmt), 0..3).prop_map(Expr::Block).sboxed()
+        })
+}
+
+fn arb_stmt(set: &mut StrategySet) -> SBoxedStrategy<Stmt> {
+    set.get(arb_expr).prop_map(Stmt::Expr).sboxed()
+}
+
+#[derive(Debug, Arbitrary)]
+struct Function {
+    #[proptest(strategy = "from_set(arb_expr)")]
+    body: Expr,
+}
+
+#[derive(Debug, Arbitrary)]
+#[proptest(params(StrategySet))]
+struct Module {
+    #[proptest(strategy = "from_shared_set(&params, arb_expr)")]
+    init: Expr,
+    #[proptest(strategy = "vec(from_shared_set(&params, arb_stmt), 0..4)")]
+    body: Vec<Stmt>,
+}
+
+proptest! {
+    #[test]
+    fn derived_fields(function in any::<Function>()) {
+        prop_assert!(function.body.depth() <= 3);
+    }
+
+    #[test]
+    fn derived_fields_with_params(module in any::<Module>()) {
+        prop_assert!(module.init.depth() <= 3);
+        for stmt in &module.body {
+            prop_assert!(stmt.depth() <= 3);
+        }
+    }
+
+    #[test]
+    fn derived_fields_use_params(module in any_with::<Module>(literals())) {
+        let exprs = module.body.iter().map(|Stmt::Expr(expr)| expr);
+        for expr in exprs.chain([&module.init]) {
+            match expr {
+                Expr::Lit(value) => prop_assert_eq!(*value, 0),
+                Expr::Block(_) => prop_assert!(false, "expected a literal, got {:?}", expr),
+            }
+        }
+    }
+}
+
+/// A set in which expressions are always the literal `0`.
+fn literals() -> StrategySet {
+    let mut set = StrategySet::default();
+    let _ = set.get(|_| Just(Expr::Lit(0)).sboxed());
+    set
+}
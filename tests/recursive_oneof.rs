@@ -0,0 +1,92 @@
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::{recursive_oneof, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Lit,
+    Block(Vec<Stmt>),
+}
+
+#[derive(Clone, Debug)]
+enum Stmt {
+    Expr(Expr),
+    Let(Expr),
+}
+
+#[derive(Clone, Debug)]
+enum Node {
+    Expr(Expr),
+    Stmt(Stmt),
+}
+
+impl Expr {
+    fn depth(&self) -> u32 {
+        match self {
+            Expr::Lit => 0,
+            Expr::Block(stmts) => 1 + stmts.iter().map(Stmt::depth).max().unwrap_or(0),
+        }
+    }
+}
+
+impl Stmt {
+    fn depth(&self) -> u32 {
+        match self {
+            Stmt::Expr(expr) => 1 + expr.depth(),
+            Stmt::Let(expr) => expr.depth(),
+        }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    Just(Expr::Lit).prop_mutually_recursive(3, 16, 4, set, |set| {
+        vec(set.get(arb_stmt), 0..4).prop_map(Expr::Block).sboxed()
+    })
+}
+
+fn arb_stmt(set: &mut StrategySet) -> SBoxedStrategy<Stmt> {
+    set.get(arb_expr)
+        .prop_map(Stmt::Let)
+        .prop_mutually_recursive(2, 16, 1, set, |set| {
+            set.get(arb_expr).prop_map(Stmt::Expr).sboxed()
+        })
+}
+
+fn arb_node(set: &mut StrategySet) -> SBoxedStrategy<Node> {
+    recursive_oneof![set; Node::Expr(arb_expr), Node::Stmt(arb_stmt)]
+}
+
+fn arb_weighted_node(set: &mut StrategySet) -> SBoxedStrategy<Node> {
+    recursive_oneof![set;
+        9 => Node::Expr(arb_expr),
+        1 => Node::Stmt(arb_stmt),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn create_node(node in arb_node(&mut StrategySet::default())) {
+        match node {
+            Node::Expr(expr) => prop_assert!(expr.depth() <= 10),
+            Node::Stmt(stmt) => prop_assert!(stmt.depth() <= 10),
+        }
+    }
+}
+
+#[test]
+fn weights() {
+    let strategy = arb_weighted_node(&mut StrategySet::default());
+    let mut runner = TestRunner::deterministic();
+    let exprs = (0..1000)
+        .filter(|_| {
+            matches!(
+                strategy.new_tree(&mut runner).unwrap().current(),
+                Node::Expr(_)
+            )
+        })
+        .count();
+    assert!((850..950).contains(&exprs), "{}", exprs);
+}
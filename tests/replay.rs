@@ -0,0 +1,140 @@
+#![cfg(feature = "std")]
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::thread;
+
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::collection::recursive_vec;
+use proptest_recurse::replay::{record, replay, Recording};
+use proptest_recurse::tree::{recursive_tree, Tree};
+use proptest_recurse::{RecursiveParams, Shape, StrategyExt, StrategySet};
+
+fn arb_tree(params: RecursiveParams) -> SBoxedStrategy<Tree<u8>> {
+    recursive_tree(any::<u8>(), 0..4, params)
+}
+
+fn recordings(params: RecursiveParams) -> Vec<(Tree<u8>, Recording)> {
+    let strategy = record(arb_tree(params));
+    let mut runner = TestRunner::deterministic();
+    (0..100)
+        .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+        .collect()
+}
+
+fn replayed(params: RecursiveParams, recording: Recording) -> Tree<u8> {
+    replay(arb_tree(params), recording)
+        .new_tree(&mut TestRunner::default())
+        .unwrap()
+        .current()
+}
+
+#[test]
+fn same_params() {
+    let params = RecursiveParams::new(4, 32, 2).shape(Shape::Linear);
+    for (tree, recording) in recordings(params.clone()) {
+        assert_eq!(replayed(params.clone(), recording), tree);
+    }
+}
+
+#[test]
+fn changed_params() {
+    for (tree, recording) in recordings(RecursiveParams::new(4, 32, 2)) {
+        let deeper = RecursiveParams::new(6, 8, 3).target_size(1..=4);
+        assert_eq!(replayed(deeper, recording.clone()), tree);
+
+        let shallower = replayed(RecursiveParams::new(2, 32, 2), recording);
+        assert!(shallower.depth() <= 2);
+        if tree.depth() <= 2 {
+            assert_eq!(shallower, tree);
+        }
+    }
+}
+
+/// The number of children of each node depends on its level, and many nodes fall through the
+/// outermost levels before recursing, so the values are only reproduced if each node recurses at
+/// its recorded level.
+fn arb_scaled(set: &mut StrategySet) -> SBoxedStrategy<Tree<u8>> {
+    any::<u8>()
+        .prop_map(Tree::Leaf)
+        .prop_mutually_recursive(4, 64, 4, set, |set| {
+            recursive_vec(set, arb_scaled, 1..8)
+                .prop_map(Tree::Node)
+                .sboxed()
+        })
+}
+
+#[test]
+fn level_dependent() {
+    let strategy = record(arb_scaled(&mut StrategySet::default()));
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..100 {
+        let (tree, recording) = strategy.new_tree(&mut runner).unwrap().current();
+        let replayed = replay(arb_scaled(&mut StrategySet::default()), recording)
+            .new_tree(&mut TestRunner::default())
+            .unwrap()
+            .current();
+        assert_eq!(replayed, tree);
+    }
+}
+
+#[test]
+fn parse() {
+    for (_, recording) in recordings(RecursiveParams::new(4, 32, 2)) {
+        assert_eq!(recording.to_string().parse::<Recording>(), Ok(recording));
+    }
+
+    assert!("".parse::<Recording>().is_err());
+    assert!("00:1".parse::<Recording>().is_err());
+    assert!(format!("{}:1,-,x", "0".repeat(64))
+        .parse::<Recording>()
+        .is_err());
+    assert!(format!("{}:1,,2", "0".repeat(64))
+        .parse::<Recording>()
+        .is_err());
+    assert!(format!("{}:1,-,+2", "0".repeat(64))
+        .parse::<Recording>()
+        .is_err());
+    assert!(format!("{}:1,-,0", "g".repeat(64))
+        .parse::<Recording>()
+        .is_err());
+    assert_eq!(
+        format!("{}:1,-,0", "0".repeat(64))
+            .parse::<Recording>()
+            .unwrap()
+            .len(),
+        3
+    );
+    assert!(format!("{}:", "0".repeat(64))
+        .parse::<Recording>()
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn mode_restored_after_panic() {
+    let generate = || {
+        let strategy = arb_tree(RecursiveParams::new(4, 32, 2));
+        let mut runner = TestRunner::deterministic();
+        (0..100)
+            .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+            .collect::<Vec<_>>()
+    };
+    let expected = thread::spawn(generate).join().unwrap();
+
+    let (_, recording) = recordings(RecursiveParams::new(4, 32, 2))
+        .into_iter()
+        .find(|(tree, _)| tree.depth() > 1)
+        .unwrap();
+    let limited = replay(
+        arb_tree(RecursiveParams::new(4, 32, 2).max_nodes(1)),
+        recording,
+    );
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        limited.new_tree(&mut TestRunner::default()).map(|_| ())
+    }));
+    assert!(result.is_err());
+    assert_eq!(generate(), expected);
+}
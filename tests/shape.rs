@@ -0,0 +1,87 @@
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy};
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::{RecursiveParams, Shape, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    fn nodes(&self) -> Vec<&Vec<Tree>> {
+        match self {
+            Tree::Leaf => vec![],
+            Tree::Node(children) => {
+                let mut nodes = vec![children];
+                nodes.extend(children.iter().flat_map(Tree::nodes));
+                nodes
+            }
+        }
+    }
+}
+
+fn arb_tree(shape: Shape) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive_with(
+        RecursiveParams::new(6, 64, 3).shape(shape),
+        &StrategySet::default(),
+        |set| {
+            vec(set.get::<Tree, _>(|_| unreachable!()), 1..4)
+                .prop_map(Tree::Node)
+                .sboxed()
+        },
+    )
+}
+
+fn branches(children: &[Tree]) -> usize {
+    children
+        .iter()
+        .filter(|child| matches!(child, Tree::Node(_)))
+        .count()
+}
+
+proptest! {
+    #[test]
+    fn linear(tree in arb_tree(Shape::Linear)) {
+        for children in tree.nodes() {
+            prop_assert!(branches(children) <= 1);
+        }
+    }
+
+    #[test]
+    fn random_zero_is_linear(tree in arb_tree(Shape::Random(0.0))) {
+        for children in tree.nodes() {
+            prop_assert!(branches(children) <= 1);
+        }
+    }
+
+    #[test]
+    fn balanced(tree in arb_tree(Shape::Balanced)) {
+        for children in tree.nodes() {
+            let branches = branches(children);
+            prop_assert!(branches == 0 || branches == children.len());
+        }
+    }
+
+    #[test]
+    fn random(_ in arb_tree(Shape::Random(0.5))) {}
+}
+
+#[test]
+fn shrinks_to_smallest_branch() {
+    let mut runner = proptest::test_runner::TestRunner::deterministic();
+    let result = runner.run(&arb_tree(Shape::Balanced), |tree| {
+        prop_assert!(matches!(tree, Tree::Leaf));
+        Ok(())
+    });
+
+    match result {
+        Err(proptest::test_runner::TestError::Fail(_, Tree::Node(children))) => {
+            assert_eq!(children.len(), 1);
+            assert!(matches!(children[0], Tree::Leaf));
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
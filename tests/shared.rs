@@ -0,0 +1,112 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::{RecursiveParams, StrategyExt, StrategySet};
+
+#[derive(Debug)]
+enum Dag {
+    Leaf,
+    Node(Vec<Arc<Dag>>),
+}
+
+impl Dag {
+    fn depth(&self) -> u32 {
+        match self {
+            Dag::Leaf => 0,
+            Dag::Node(children) => {
+                1 + children
+                    .iter()
+                    .map(|child| child.depth())
+                    .max()
+                    .unwrap_or(0)
+            }
+        }
+    }
+}
+
+fn arb_dag(sharing_probability: f64) -> SBoxedStrategy<Arc<Dag>> {
+    Just(Arc::new(Dag::Leaf)).prop_mutually_recursive_shared(
+        RecursiveParams::new(4, 32, 3),
+        sharing_probability,
+        &StrategySet::default(),
+        |set| {
+            vec(set.get::<Arc<Dag>, _>(|_| unreachable!()), 2..4)
+                .prop_map(|children| Arc::new(Dag::Node(children)))
+                .sboxed()
+        },
+    )
+}
+
+fn has_shared_subtree(dag: &Arc<Dag>) -> bool {
+    fn visit(dag: &Arc<Dag>, seen: &mut HashSet<*const Dag>) -> bool {
+        match &**dag {
+            Dag::Leaf => false,
+            Dag::Node(children) => {
+                if !seen.insert(Arc::as_ptr(dag)) {
+                    return true;
+                }
+                children.iter().any(|child| visit(child, seen))
+            }
+        }
+    }
+
+    visit(dag, &mut HashSet::new())
+}
+
+fn count_shared(sharing_probability: f64) -> usize {
+    let strategy = arb_dag(sharing_probability);
+    let mut runner = TestRunner::deterministic();
+    (0..200)
+        .filter(|_| has_shared_subtree(&strategy.new_tree(&mut runner).unwrap().current()))
+        .count()
+}
+
+#[test]
+fn shares_subtrees() {
+    assert!(count_shared(0.5) > 0);
+}
+
+#[test]
+fn respects_depth() {
+    let strategy = arb_dag(0.5);
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..2000 {
+        let dag = strategy.new_tree(&mut runner).unwrap().current();
+        assert!(dag.depth() <= 4, "depth {} exceeds limit", dag.depth());
+    }
+}
+
+#[test]
+fn no_sharing() {
+    assert_eq!(count_shared(0.0), 0);
+}
+
+#[test]
+#[should_panic(expected = "sharing probability must be between 0 and 1")]
+fn invalid_probability() {
+    let _ = arb_dag(1.5);
+}
+
+#[test]
+fn reproducible_from_seed() {
+    let strategy = arb_dag(0.5);
+    let mut runner = TestRunner::default();
+    for _ in 0..16 {
+        strategy.new_tree(&mut runner).unwrap();
+    }
+
+    let generate = |strategy: &SBoxedStrategy<Arc<Dag>>| {
+        format!(
+            "{:?}",
+            strategy
+                .new_tree(&mut TestRunner::deterministic())
+                .unwrap()
+                .current()
+        )
+    };
+    assert_eq!(generate(&strategy), generate(&arb_dag(0.5)));
+}
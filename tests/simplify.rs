@@ -0,0 +1,98 @@
+use proptest::prelude::*;
+use proptest::strategy::{SBoxedStrategy, ValueTree};
+use proptest::test_runner::{Config, TestError, TestRunner};
+
+use proptest_recurse::{Simplify, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Lit(i32),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn contains_neg(&self) -> bool {
+        match self {
+            Expr::Lit(_) => false,
+            Expr::Neg(_) => true,
+            Expr::Add(a, b) => a.contains_neg() || b.contains_neg(),
+        }
+    }
+}
+
+impl Simplify for Expr {
+    fn simplify(&self) -> Vec<Self> {
+        match self {
+            Expr::Lit(0) => vec![],
+            Expr::Lit(_) => vec![Expr::Lit(0)],
+            Expr::Neg(a) => {
+                let mut simpler = vec![(**a).clone()];
+                simpler.extend(a.simplify().into_iter().map(|a| Expr::Neg(Box::new(a))));
+                simpler
+            }
+            Expr::Add(a, b) => vec![(**a).clone(), (**b).clone()],
+        }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    (1..100)
+        .prop_map(Expr::Lit)
+        .prop_mutually_recursive_unboxed(6, 64, 2, set, |set| {
+            prop_oneof![
+                set.get(arb_expr).prop_map(|a| Expr::Neg(Box::new(a))),
+                (set.get(arb_expr), set.get(arb_expr))
+                    .prop_map(|(a, b)| Expr::Add(Box::new(a), Box::new(b))),
+            ]
+            .sboxed()
+        })
+        .simplified()
+        .sboxed()
+}
+
+fn minimal_failure(strategy: SBoxedStrategy<Expr>) -> Expr {
+    let mut runner = TestRunner::new(Config {
+        failure_persistence: None,
+        max_shrink_iters: u32::MAX,
+        ..Config::default()
+    });
+    match runner.run(&strategy, |expr| {
+        prop_assert!(!expr.contains_neg());
+        Ok(())
+    }) {
+        Err(TestError::Fail(_, expr)) => expr,
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn simplify() {
+    for _ in 0..10 {
+        let expr = minimal_failure(arb_expr(&mut StrategySet::default()));
+        assert_eq!(expr, Expr::Neg(Box::new(Expr::Lit(0))));
+    }
+}
+
+#[test]
+fn simplifies_before_shrinking() {
+    let strategy = arb_expr(&mut StrategySet::default());
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..100 {
+        let mut tree = strategy.new_tree(&mut runner).unwrap();
+        let original = tree.current();
+        let simplifications = original.simplify();
+        if simplifications.is_empty() {
+            continue;
+        }
+
+        // The outermost node's simplifications are tried first, and the value tree only starts
+        // shrinking once they have all been rejected.
+        for simplification in simplifications {
+            assert!(tree.simplify());
+            assert_eq!(tree.current(), simplification);
+            assert!(tree.complicate());
+            assert_eq!(tree.current(), original);
+        }
+    }
+}
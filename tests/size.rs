@@ -0,0 +1,98 @@
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, Strategy, ValueTree};
+use proptest::test_runner::TestRunner;
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::size::SizeStats;
+use proptest_recurse::{Recursive, StrategyExt, StrategySet, TreeSize};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl TreeSize for Tree {
+    fn node_count(&self) -> u64 {
+        match self {
+            Tree::Leaf => 1,
+            Tree::Node(children) => 1 + children.node_count(),
+        }
+    }
+}
+
+type TreeBranch = fn(&mut StrategySet) -> SBoxedStrategy<Tree>;
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    arb_tree_unboxed(set).sboxed()
+}
+
+fn arb_tree_unboxed(set: &StrategySet) -> Recursive<Just<Tree>, TreeBranch> {
+    Just(Tree::Leaf).prop_mutually_recursive_unboxed(6, 256, 4, set, |set| {
+        vec(set.get::<Tree, _>(arb_tree), 0..8)
+            .prop_map(Tree::Node)
+            .sboxed()
+    })
+}
+
+proptest! {
+    #[test]
+    fn bounded(x in arb_tree_unboxed(&StrategySet::default()).max_size(16)) {
+        prop_assert!(x.node_count() <= 16);
+    }
+}
+
+#[test]
+fn stats() {
+    let stats = SizeStats::new();
+    let strategy = arb_tree_unboxed(&StrategySet::default()).track_size(&stats);
+
+    let mut runner = TestRunner::deterministic();
+    let mut total = 0;
+    for _ in 0..100 {
+        total += strategy
+            .new_tree(&mut runner)
+            .unwrap()
+            .current()
+            .node_count();
+    }
+
+    assert_eq!(stats.samples(), 100);
+    assert_eq!(stats.min(), Some(1));
+    assert_eq!(stats.mean(), Some(total as f64 / 100.0));
+    assert_eq!(stats.histogram().values().sum::<u64>(), 100);
+}
+
+#[cfg(feature = "derive")]
+mod derive {
+    use proptest_recurse::TreeSize;
+
+    #[derive(TreeSize)]
+    enum Expr {
+        Lit(i32),
+        Add(Box<Expr>, Box<Expr>),
+        Block {
+            stmts: Vec<Stmt>,
+            #[tree_size(skip)]
+            _label: Option<Box<Expr>>,
+        },
+    }
+
+    #[derive(TreeSize)]
+    struct Stmt(Expr);
+
+    #[derive(TreeSize)]
+    struct Wrapper<T>(Vec<T>);
+
+    #[test]
+    fn derived() {
+        let lit = || Box::new(Expr::Lit(0));
+        let expr = Expr::Block {
+            stmts: vec![Stmt(Expr::Add(lit(), lit()))],
+            _label: Some(lit()),
+        };
+
+        assert_eq!(expr.node_count(), 5);
+        assert_eq!(Wrapper(vec![Stmt(Expr::Lit(1))]).node_count(), 3);
+    }
+}
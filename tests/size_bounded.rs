@@ -0,0 +1,52 @@
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy};
+use proptest::test_runner::{Config, TestRunner};
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::{size_bounded, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Tree {
+    Leaf,
+    Node(Vec<Tree>),
+}
+
+impl Tree {
+    fn size(&self) -> u64 {
+        match self {
+            Tree::Leaf => 1,
+            Tree::Node(children) => 1 + children.iter().map(Tree::size).sum::<u64>(),
+        }
+    }
+}
+
+fn arb_tree(set: &mut StrategySet) -> SBoxedStrategy<Tree> {
+    Just(Tree::Leaf).prop_mutually_recursive(6, 256, 4, set, |set| {
+        vec(set.get::<Tree, _>(arb_tree), 0..8)
+            .prop_map(Tree::Node)
+            .sboxed()
+    })
+}
+
+proptest! {
+    #[test]
+    fn bounded(x in size_bounded(arb_tree(&mut StrategySet::default()), 20, Tree::size)) {
+        prop_assert!(x.size() <= 20);
+    }
+}
+
+#[test]
+fn too_many_rejects() {
+    let strategy = size_bounded(arb_tree(&mut StrategySet::default()), 0, Tree::size);
+    let mut runner = TestRunner::new(Config {
+        max_local_rejects: 10,
+        ..Config::default()
+    });
+
+    let err = strategy.new_tree(&mut runner).err().unwrap();
+    assert!(
+        err.to_string().contains("Too many local rejects"),
+        "{}",
+        err
+    );
+}
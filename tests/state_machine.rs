@@ -0,0 +1,235 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use proptest::strategy::{Just, SBoxedStrategy};
+use proptest::{prelude::*, prop_oneof, proptest};
+
+use proptest_recurse::state_machine::Transitions;
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Num(i64),
+    Neg(Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    fn depth(&self) -> u32 {
+        match self {
+            Expr::Num(_) => 0,
+            Expr::Neg(expr) => 1 + expr.depth(),
+            Expr::Add(left, right) => 1 + left.depth().max(right.depth()),
+        }
+    }
+
+    fn eval(&self) -> i64 {
+        match self {
+            Expr::Num(value) => *value,
+            Expr::Neg(expr) => expr.eval().wrapping_neg(),
+            Expr::Add(left, right) => left.eval().wrapping_add(right.eval()),
+        }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    any::<i64>()
+        .prop_map(Expr::Num)
+        .prop_mutually_recursive(8, 64, 2, set, |set| {
+            let expr = set.get::<Expr, _>(arb_expr);
+            prop_oneof![
+                expr.clone().prop_map(|expr| Expr::Neg(Box::new(expr))),
+                (expr.clone(), expr).prop_map(|(l, r)| Expr::Add(Box::new(l), Box::new(r))),
+            ]
+            .sboxed()
+        })
+}
+
+/// A transition of a stack calculator, as it might appear in a reference state machine.
+#[derive(Clone, Debug)]
+enum Transition {
+    Push(Expr),
+    Eval(Expr),
+    Pop,
+}
+
+static TRANSITIONS: Transitions = Transitions::new();
+
+/// The `transitions` function of the reference state machine.
+fn transitions(state: &[i64]) -> SBoxedStrategy<Transition> {
+    let push = TRANSITIONS.get(2, arb_expr).prop_map(Transition::Push);
+    let eval = TRANSITIONS.get(5, arb_expr).prop_map(Transition::Eval);
+    if state.is_empty() {
+        prop_oneof![push, eval].sboxed()
+    } else {
+        prop_oneof![push, eval, Just(Transition::Pop)].sboxed()
+    }
+}
+
+fn apply(mut state: Vec<i64>, transition: &Transition) -> Vec<i64> {
+    match transition {
+        Transition::Push(expr) | Transition::Eval(expr) => state.push(expr.eval()),
+        Transition::Pop => {
+            state.pop();
+        }
+    }
+    state
+}
+
+proptest! {
+    #[test]
+    fn depth_limits(transitions in proptest::collection::vec(transitions(&[1]), 0..16)) {
+        let mut state = vec![];
+        for transition in &transitions {
+            match transition {
+                Transition::Push(expr) => prop_assert!(expr.depth() <= 2),
+                Transition::Eval(expr) => prop_assert!(expr.depth() <= 5),
+                Transition::Pop => {}
+            }
+            state = apply(state, transition);
+        }
+    }
+}
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+fn counted_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    CALLS.fetch_add(1, Ordering::SeqCst);
+    arb_expr(set)
+}
+
+#[test]
+fn cached() {
+    let transitions = Transitions::new();
+    let _ = transitions.get(3, counted_expr);
+    let _ = transitions.get(3, counted_expr);
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    let _ = transitions.get(4, counted_expr);
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn distinct_factories() {
+    let transitions = Transitions::new();
+    let _ = transitions.get(3, arb_expr);
+    let literal = transitions.get(3, |_| Just(Expr::Num(7)).sboxed());
+
+    let mut runner = proptest::test_runner::TestRunner::default();
+    let expr = literal.new_tree(&mut runner).unwrap().current();
+    assert_eq!(expr.eval(), 7);
+}
+
+#[test]
+fn capturing_factories() {
+    fn literal(transitions: &Transitions, value: i64) -> SBoxedStrategy<Expr> {
+        transitions.get(3, move |_| Just(Expr::Num(value)).sboxed())
+    }
+
+    let transitions = Transitions::new();
+    let mut runner = proptest::test_runner::TestRunner::default();
+    for value in 0..4 {
+        let expr = literal(&transitions, value)
+            .new_tree(&mut runner)
+            .unwrap()
+            .current();
+        assert_eq!(expr.eval(), value);
+    }
+}
+
+#[test]
+fn empty_state() {
+    let mut runner = proptest::test_runner::TestRunner::default();
+    for _ in 0..64 {
+        let transition = transitions(&[]).new_tree(&mut runner).unwrap().current();
+        assert!(!matches!(transition, Transition::Pop));
+    }
+}
+
+#[cfg(feature = "state-machine")]
+mod reference {
+    use proptest::prelude::*;
+    use proptest::strategy::{BoxedStrategy, SBoxedStrategy};
+    use proptest_state_machine::{prop_state_machine, StateMachineTest};
+
+    use proptest_recurse::state_machine::{RecursiveStateMachine, Reference, Transitions};
+
+    use super::{apply, arb_expr, Transition};
+
+    /// The reference model of a stack calculator.
+    struct Calculator;
+
+    static CALCULATOR_TRANSITIONS: Transitions = Transitions::new();
+
+    impl RecursiveStateMachine for Calculator {
+        type State = Vec<i64>;
+        type Transition = Transition;
+
+        fn cache() -> &'static Transitions {
+            &CALCULATOR_TRANSITIONS
+        }
+
+        fn init_state() -> BoxedStrategy<Self::State> {
+            Just(Vec::new()).boxed()
+        }
+
+        fn transitions(state: &Self::State, cache: &Transitions) -> SBoxedStrategy<Transition> {
+            let push = cache.get(2, arb_expr).prop_map(Transition::Push);
+            let eval = cache.get(5, arb_expr).prop_map(Transition::Eval);
+            if state.is_empty() {
+                prop_oneof![push, eval].sboxed()
+            } else {
+                prop_oneof![push, eval, Just(Transition::Pop)].sboxed()
+            }
+        }
+
+        fn apply(state: Self::State, transition: &Transition) -> Self::State {
+            apply(state, transition)
+        }
+
+        fn preconditions(state: &Self::State, transition: &Transition) -> bool {
+            !matches!(transition, Transition::Pop) || !state.is_empty()
+        }
+    }
+
+    /// The system under test: a stack which keeps a running sum of its elements.
+    #[derive(Default)]
+    struct Summing {
+        sum: i64,
+        values: Vec<i64>,
+    }
+
+    impl StateMachineTest for Summing {
+        type SystemUnderTest = Self;
+        type Reference = Reference<Calculator>;
+
+        fn init_test(_: &Vec<i64>) -> Self {
+            Summing::default()
+        }
+
+        fn apply(mut state: Self, reference: &Vec<i64>, transition: Transition) -> Self {
+            match transition {
+                Transition::Push(expr) | Transition::Eval(expr) => {
+                    let value = expr.eval();
+                    state.sum = state.sum.wrapping_add(value);
+                    state.values.push(value);
+                }
+                Transition::Pop => {
+                    let value = state.values.pop().unwrap();
+                    state.sum = state.sum.wrapping_sub(value);
+                }
+            }
+            assert_eq!(state.values.len(), reference.len());
+            assert_eq!(
+                state.sum,
+                reference
+                    .iter()
+                    .fold(0i64, |sum, value| sum.wrapping_add(*value))
+            );
+            state
+        }
+    }
+
+    prop_state_machine! {
+        #[test]
+        fn calculator(sequential 1..20 => Summing);
+    }
+}
@@ -0,0 +1,86 @@
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy};
+use proptest::{prelude::*, proptest};
+
+use proptest_recurse::strategy_set;
+
+#[derive(Clone, Debug)]
+enum First {
+    Zero,
+    Second(Vec<Second>),
+}
+
+#[derive(Clone, Debug)]
+enum Second {
+    Zero,
+    First(Box<First>),
+}
+
+impl First {
+    fn depth(&self) -> u32 {
+        match self {
+            First::Zero => 0,
+            First::Second(s) => match s.iter().map(Second::depth).max() {
+                Some(depth) => depth + 1,
+                None => 0,
+            },
+        }
+    }
+}
+
+impl Second {
+    fn depth(&self) -> u32 {
+        match self {
+            Second::Zero => 0,
+            Second::First(f) => f.depth() + 1,
+        }
+    }
+}
+
+strategy_set! {
+    fn strategies();
+
+    fn arb_first: First => {
+        base: Just(First::Zero),
+        branch(set) => vec(set.get::<Second, _>(arb_second), 0..8).prop_map(First::Second),
+        params: (5, 32, 8),
+    }
+
+    fn arb_second: Second => {
+        base: Just(Second::Zero),
+        branch(set) => set
+            .get::<First, _>(arb_first)
+            .prop_map(|first| Second::First(Box::new(first))),
+        params: (3, 32, 1)
+    }
+}
+
+proptest! {
+    #[test]
+    fn create_first(x in arb_first(&mut strategies())) {
+        assert!(x.depth() <= 8);
+    }
+
+    #[test]
+    fn create_second(x in arb_second(&mut Default::default())) {
+        assert!(x.depth() <= 8);
+    }
+}
+
+#[test]
+fn registered_strategies_share_depth() {
+    use proptest::strategy::ValueTree;
+    use proptest::test_runner::TestRunner;
+
+    let sample = |strategy: SBoxedStrategy<First>| {
+        let mut runner = TestRunner::deterministic();
+        (0..100)
+            .map(|_| format!("{:?}", strategy.new_tree(&mut runner).unwrap().current()))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        sample(arb_first(&mut strategies())),
+        sample(arb_first(&mut Default::default()))
+    );
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "std")]
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::{Just, SBoxedStrategy};
+use test_strategy::{proptest, Arbitrary};
+
+use proptest_recurse::{global, register_global, StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Lit,
+    Block(Vec<Stmt>),
+}
+
+#[derive(Clone, Debug)]
+enum Stmt {
+    Expr(Expr),
+}
+
+impl Expr {
+    fn depth(&self) -> u32 {
+        match self {
+            Expr::Lit => 0,
+            Expr::Block(stmts) => 1 + stmts.iter().map(Stmt::depth).max().unwrap_or(0),
+        }
+    }
+}
+
+impl Stmt {
+    fn depth(&self) -> u32 {
+        match self {
+            Stmt::Expr(expr) => expr.depth(),
+        }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    Just(Expr::Lit).prop_mutually_recursive(3, 16, 2, set, |set| {
+        vec(set.get_registered::<Stmt>(), 0..3)
+            .prop_map(Expr::Block)
+            .sboxed()
+    })
+}
+
+fn arb_stmt(set: &mut StrategySet) -> SBoxedStrategy<Stmt> {
+    set.get_registered::<Expr>().prop_map(Stmt::Expr).sboxed()
+}
+
+fn register() {
+    register_global!(arb_expr, arb_stmt);
+}
+
+#[proptest]
+fn strategy_attribute(#[strategy({ register(); global::of(arb_expr) })] expr: Expr) {
+    prop_assert!(expr.depth() <= 3);
+}
+
+#[derive(Debug, Arbitrary)]
+struct Function {
+    #[strategy({ register(); global::of(arb_stmt) })]
+    body: Stmt,
+    #[strategy(vec(global::of(arb_stmt), 0..4))]
+    rest: Vec<Stmt>,
+}
+
+#[proptest]
+fn derived_fields(function: Function) {
+    prop_assert!(function.body.depth() <= 3);
+    for stmt in &function.rest {
+        prop_assert!(stmt.depth() <= 3);
+    }
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "std")]
+
+use proptest::prelude::*;
+
+use proptest_recurse::testutil::{
+    assert_reaches_depth, assert_reaches_size, depth_distribution, sample_corpus,
+};
+use proptest_recurse::tree::{recursive_tree, Tree};
+use proptest_recurse::RecursiveParams;
+
+#[test]
+fn depth_distribution_counts_samples() {
+    let trees = recursive_tree(any::<u8>(), 1..4, RecursiveParams::new(4, 32, 2));
+    let distribution = depth_distribution(&trees, 500, Tree::depth);
+
+    assert_eq!(distribution.values().sum::<u64>(), 500);
+    assert!(distribution.keys().all(|&depth| depth <= 4));
+    assert_eq!(distribution, depth_distribution(&trees, 500, Tree::depth));
+}
+
+#[test]
+fn reaches_configured_depth() {
+    let trees = recursive_tree(any::<u8>(), 1..4, RecursiveParams::new(4, 32, 2));
+    assert_reaches_depth(&trees, 4, 1000, Tree::depth);
+    assert_reaches_size(&trees, 16, 1000);
+}
+
+#[test]
+#[should_panic(expected = "the largest was Some(2)")]
+fn shallow_strategy_fails() {
+    let trees = recursive_tree(any::<u8>(), 1..4, RecursiveParams::new(2, 32, 2));
+    assert_reaches_depth(&trees, 3, 100, Tree::depth);
+}
+
+#[test]
+fn sample_corpus_is_deterministic() {
+    let trees = recursive_tree(any::<u8>(), 1..4, RecursiveParams::new(4, 32, 2));
+    let corpus = sample_corpus(&trees, 50, 7);
+
+    assert_eq!(corpus.len(), 50);
+    assert_eq!(corpus, sample_corpus(&trees, 50, 7));
+    assert_ne!(corpus, sample_corpus(&trees, 50, 8));
+    assert!(corpus.iter().any(|tree| tree.depth() > 0));
+}
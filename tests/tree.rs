@@ -0,0 +1,68 @@
+use proptest::prelude::*;
+use proptest::proptest;
+
+use proptest_recurse::tree::{recursive_expr, recursive_tree, Expr, Tree};
+use proptest_recurse::{RecursiveParams, TreeSize};
+
+#[derive(Clone, Copy, Debug)]
+enum Unary {
+    Neg,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Binary {
+    Add,
+    Mul,
+}
+
+fn eval(expr: &Expr<i32, Unary, Binary>) -> i32 {
+    match expr {
+        Expr::Leaf(value) => *value,
+        Expr::Unary(Unary::Neg, operand) => eval(operand).wrapping_neg(),
+        Expr::Binary(Binary::Add, left, right) => eval(left).wrapping_add(eval(right)),
+        Expr::Binary(Binary::Mul, left, right) => eval(left).wrapping_mul(eval(right)),
+    }
+}
+
+fn leaves<T: Clone>(tree: &Tree<T>) -> Vec<T> {
+    match tree {
+        Tree::Leaf(value) => vec![value.clone()],
+        Tree::Node(children) => children.iter().flat_map(leaves).collect(),
+    }
+}
+
+proptest! {
+    #[test]
+    fn tree(tree in recursive_tree(0..10u8, 1..4, RecursiveParams::new(4, 32, 2))) {
+        prop_assert!(tree.depth() <= 4);
+        prop_assert!(leaves(&tree).iter().all(|&value| value < 10));
+        prop_assert!(tree.node_count() as usize >= leaves(&tree).len());
+    }
+
+    #[test]
+    fn expr(expr in recursive_expr(
+        any::<i32>(),
+        Just(Unary::Neg),
+        prop_oneof![Just(Binary::Add), Just(Binary::Mul)],
+        RecursiveParams::new(6, 64, 2),
+    )) {
+        prop_assert!(expr.depth() <= 6);
+        eval(&expr);
+    }
+}
+
+#[test]
+fn node_count() {
+    let tree = Tree::Node(vec![Tree::Leaf(1), Tree::Node(vec![Tree::Leaf(2)])]);
+    assert_eq!(tree.node_count(), 4);
+    assert_eq!(tree.depth(), 2);
+
+    let expr: Expr<i32, Unary, Binary> = Expr::Binary(
+        Binary::Add,
+        Box::new(Expr::Leaf(1)),
+        Box::new(Expr::Unary(Unary::Neg, Box::new(Expr::Leaf(2)))),
+    );
+    assert_eq!(expr.node_count(), 4);
+    assert_eq!(expr.depth(), 2);
+    assert_eq!(eval(&expr), -1);
+}
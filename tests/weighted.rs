@@ -0,0 +1,111 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use proptest::collection::vec;
+use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+use proptest::{prelude::*, prop_oneof};
+
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug)]
+enum First {
+    Zero,
+    Nested(Box<First>),
+    Second(Vec<Second>),
+}
+
+#[derive(Clone, Debug)]
+enum Second {
+    Zero,
+    First(Box<First>),
+}
+
+fn arb_first(weight: f64) -> impl Fn(&mut StrategySet) -> SBoxedStrategy<First> {
+    move |set| {
+        Just(First::Zero).prop_mutually_recursive(4, 32, 2, set, move |set| {
+            prop_oneof![
+                set.get::<First, _>(arb_first(weight))
+                    .prop_map(|first| First::Nested(Box::new(first))),
+                vec(set.get_weighted::<Second, _>(weight, arb_second), 1..3)
+                    .prop_map(First::Second),
+            ]
+            .sboxed()
+        })
+    }
+}
+
+fn arb_second(set: &mut StrategySet) -> SBoxedStrategy<Second> {
+    Just(Second::Zero).prop_mutually_recursive(4, 32, 1, set, |set| {
+        set.get::<First, _>(arb_first(1.0))
+            .prop_map(|first| Second::First(Box::new(first)))
+            .sboxed()
+    })
+}
+
+/// Counts the `Second`s which are not `Second::Zero` in `first`, along with all `Second`s.
+fn count(first: &First) -> (u32, u32) {
+    match first {
+        First::Zero => (0, 0),
+        First::Nested(first) => count(first),
+        First::Second(seconds) => seconds.iter().fold((0, 0), |(nested, total), second| {
+            let (n, t) = match second {
+                Second::Zero => (0, 0),
+                Second::First(first) => {
+                    let (n, t) = count(first);
+                    (n + 1, t)
+                }
+            };
+            (nested + n, total + t + 1)
+        }),
+    }
+}
+
+fn nested_fraction(weight: f64) -> f64 {
+    let strategy = StrategySet::default().get(arb_first(weight));
+    let mut runner = TestRunner::deterministic();
+    let (mut nested, mut total) = (0, 0);
+    for _ in 0..512 {
+        let (n, t) = count(&strategy.new_tree(&mut runner).unwrap().current());
+        nested += n;
+        total += t;
+    }
+    f64::from(nested) / f64::from(total)
+}
+
+#[test]
+fn never() {
+    assert_eq!(nested_fraction(0.0), 0.0);
+}
+
+#[test]
+fn rarer() {
+    let always = nested_fraction(1.0);
+    let sometimes = nested_fraction(0.25);
+    assert!(always > 0.0);
+    assert!(sometimes > 0.0);
+    assert!(sometimes < always * 0.6, "{} vs {}", sometimes, always);
+}
+
+#[test]
+#[should_panic(expected = "invalid edge weight")]
+fn invalid_weight() {
+    let _ = StrategySet::default().get_weighted(1.5, arb_second);
+}
+
+#[test]
+fn cached() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    fn counted_second(set: &mut StrategySet) -> SBoxedStrategy<Second> {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        arb_second(set)
+    }
+
+    let mut set = StrategySet::default();
+    let _ = set.get_weighted(0.5, counted_second);
+    // Called once for the full strategy and once for the minimal one.
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+    let _ = set.get_weighted(0.5, counted_second);
+    let _ = set.get_weighted(0.25, counted_second);
+    assert_eq!(CALLS.load(Ordering::SeqCst), 2);
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "std")]
+
+use proptest::prelude::*;
+use proptest::strategy::ValueTree;
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::tree::{recursive_expr, recursive_tree};
+use proptest_recurse::{with_depth, RecursiveParams, Shape, TreeSize};
+
+proptest! {
+    #[test]
+    fn tree_depth(tree in with_depth(recursive_tree(any::<u8>(), 0..4, RecursiveParams::new(6, 64, 2)))) {
+        prop_assert_eq!(tree.depth, tree.value.depth());
+        prop_assert_eq!(tree.node_count, tree.value.node_count());
+    }
+
+    #[test]
+    fn expr_depth(
+        expr in with_depth(recursive_expr(
+            any::<i32>(),
+            Just('-'),
+            Just('+'),
+            RecursiveParams::new(8, 0, 2).target_size(1..=64).shape(Shape::Linear),
+        ))
+    ) {
+        prop_assert_eq!(expr.depth, expr.value.depth());
+        prop_assert_eq!(expr.node_count, expr.value.node_count());
+    }
+}
+
+#[test]
+fn shrinking() {
+    let strategy = with_depth(recursive_tree(
+        any::<u8>(),
+        1..4,
+        RecursiveParams::new(6, 64, 2),
+    ));
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..50 {
+        let mut tree = strategy.new_tree(&mut runner).unwrap();
+        loop {
+            let current = tree.current();
+            assert_eq!(current.depth, current.value.depth());
+            assert_eq!(current.node_count, current.value.node_count());
+            if !tree.simplify() {
+                break;
+            }
+        }
+    }
+}
+
+#[test]
+fn non_recursive() {
+    let mut runner = TestRunner::deterministic();
+    let value = with_depth(any::<u8>())
+        .new_tree(&mut runner)
+        .unwrap()
+        .current();
+    assert_eq!((value.depth, value.node_count), (0, 0));
+}
@@ -0,0 +1,115 @@
+#![cfg(feature = "std")]
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::strategy::{Just, SBoxedStrategy, ValueTree};
+use proptest::test_runner::TestRunner;
+
+use proptest_recurse::zipper::{with_path, Path};
+use proptest_recurse::{StrategyExt, StrategySet};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Expr {
+    Lit(i32),
+    Call(Box<Expr>, Vec<Expr>),
+    Block(Vec<Stmt>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Stmt {
+    Nop,
+    Expr(Expr),
+}
+
+/// A reference to a node of either type.
+#[derive(Debug)]
+enum Node<'a> {
+    Expr(&'a Expr),
+    Stmt(&'a Stmt),
+}
+
+impl<'a> Node<'a> {
+    fn children(&self) -> Vec<Node<'a>> {
+        match self {
+            Node::Expr(Expr::Lit(_)) => vec![],
+            Node::Expr(Expr::Call(callee, args)) => std::iter::once(Node::Expr(callee))
+                .chain(args.iter().map(Node::Expr))
+                .collect(),
+            Node::Expr(Expr::Block(stmts)) => stmts.iter().map(Node::Stmt).collect(),
+            Node::Stmt(Stmt::Nop) => vec![],
+            Node::Stmt(Stmt::Expr(expr)) => vec![Node::Expr(expr)],
+        }
+    }
+
+    fn get(self, path: &[usize]) -> Option<Node<'a>> {
+        match path.split_first() {
+            None => Some(self),
+            Some((&index, rest)) => self.children().into_iter().nth(index)?.get(rest),
+        }
+    }
+}
+
+fn arb_expr(set: &mut StrategySet) -> SBoxedStrategy<Expr> {
+    any::<i32>()
+        .prop_map(Expr::Lit)
+        .prop_mutually_recursive(4, 32, 3, set, |set| {
+            prop_oneof![
+                (set.get(arb_expr), vec(set.get(arb_expr), 0..3))
+                    .prop_map(|(callee, args)| Expr::Call(Box::new(callee), args)),
+                vec(set.get(arb_stmt), 0..3).prop_map(Expr::Block),
+            ]
+            .sboxed()
+        })
+}
+
+fn arb_stmt(set: &mut StrategySet) -> SBoxedStrategy<Stmt> {
+    Just(Stmt::Nop).prop_mutually_recursive(2, 8, 1, set, |set| {
+        set.get(arb_expr).prop_map(Stmt::Expr).sboxed()
+    })
+}
+
+proptest! {
+    #[test]
+    fn path_is_valid((expr, path) in with_path(arb_expr(&mut StrategySet::default()))) {
+        prop_assert!(Node::Expr(&expr).get(path.indices()).is_some(), "{:?}", path);
+        if let Some(parent) = path.parent() {
+            prop_assert!(Node::Expr(&expr).get(parent.indices()).is_some());
+        }
+    }
+}
+
+#[test]
+fn reaches_nested_nodes() {
+    let strategy = with_path(arb_expr(&mut StrategySet::default()));
+
+    let mut runner = TestRunner::deterministic();
+    let paths: Vec<Path> = (0..200)
+        .map(|_| strategy.new_tree(&mut runner).unwrap().current().1)
+        .collect();
+    assert!(paths.iter().any(Path::is_root));
+    assert!(paths.iter().any(|path| path.indices().len() >= 2));
+}
+
+#[test]
+fn valid_while_shrinking() {
+    let strategy = with_path(arb_expr(&mut StrategySet::default()));
+
+    let mut runner = TestRunner::deterministic();
+    for _ in 0..20 {
+        let mut tree = strategy.new_tree(&mut runner).unwrap();
+        while tree.simplify() {
+            let (expr, path) = tree.current();
+            assert!(Node::Expr(&expr).get(path.indices()).is_some());
+        }
+    }
+}
+
+#[test]
+fn no_nodes() {
+    let (value, path) = with_path(Just(1))
+        .new_tree(&mut TestRunner::deterministic())
+        .unwrap()
+        .current();
+    assert_eq!(value, 1);
+    assert!(path.is_root());
+}